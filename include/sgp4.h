@@ -0,0 +1,116 @@
+#ifndef SGP4_H
+#define SGP4_H
+
+/* Generated with cbindgen from src/ffi.rs; regenerate with `cbindgen --config cbindgen.toml --output include/sgp4.h src/ffi.rs` */
+
+#include <stdbool.h>
+
+/**
+ * No error
+ */
+#define SGP4_OK 0
+
+/**
+ * Mean elements out of range: eccentricity >= 1 or < -0.001
+ */
+#define SGP4_ERROR_INVALID_ELEMENTS 1
+
+/**
+ * Mean motion less than or equal to zero
+ */
+#define SGP4_ERROR_NEGATIVE_MEAN_MOTION 2
+
+/**
+ * Perturbed eccentricity out of range
+ */
+#define SGP4_ERROR_ECCENTRICITY_OUT_OF_RANGE 3
+
+/**
+ * Semi-latus rectum less than zero
+ */
+#define SGP4_ERROR_NEGATIVE_SEMILATUS_RECTUM 4
+
+/**
+ * Satellite has decayed
+ */
+#define SGP4_ERROR_ORBITAL_DECAY 6
+
+/**
+ * The TLE lines could not be parsed
+ */
+#define SGP4_ERROR_PARSE 10
+
+/**
+ * A pointer argument is null, a string is not UTF-8 or the gravity model is unknown
+ */
+#define SGP4_ERROR_INVALID_ARGUMENT -1
+
+/**
+ * WGS-72 constants with the low precision mu and xke of the original code
+ */
+#define SGP4_WGS72_LOW_PRECISION 0
+
+/**
+ * WGS-72 constants, the ones the TLEs are generated with
+ */
+#define SGP4_WGS72 1
+
+/**
+ * WGS-84 constants
+ */
+#define SGP4_WGS84 2
+
+/**
+ * Opaque handle to an initialized satellite.
+ */
+typedef struct Sgp4Satellite Sgp4Satellite;
+
+/**
+ * Initialize a satellite from the two lines of a TLE.
+ *
+ * `model` is one of `SGP4_WGS72_LOW_PRECISION`, `SGP4_WGS72` or `SGP4_WGS84`.
+ * Returns null on failure and stores the error code in `error` if it isn't null.
+ * Release the satellite with `sgp4_free`.
+ *
+ * # Safety
+ *
+ * `line1` and `line2` must be null or valid nul-terminated strings, and `error` must
+ * be null or valid for writes.
+ */
+struct Sgp4Satellite *sgp4_init(const char *line1,
+                                const char *line2,
+                                int model,
+                                bool afspc_mode,
+                                int *error);
+
+/**
+ * Propagate a satellite to `tsince_min` minutes since epoch, writing the TEME position
+ * in km to `r` and velocity in km/s to `v`. Returns the error code, also available
+ * from `sgp4_error` until the next call.
+ *
+ * # Safety
+ *
+ * `satellite` must be null or returned by `sgp4_init` and not yet freed. `r` and `v`
+ * must be null or valid for writes of three doubles.
+ */
+int sgp4_propagate(struct Sgp4Satellite *satellite, double tsince_min, double *r, double *v);
+
+/**
+ * Error code of the last propagation of a satellite.
+ *
+ * # Safety
+ *
+ * `satellite` must be null or returned by `sgp4_init` and not yet freed.
+ */
+int sgp4_error(const struct Sgp4Satellite *satellite);
+
+/**
+ * Release a satellite returned by `sgp4_init`. Null is ignored.
+ *
+ * # Safety
+ *
+ * `satellite` must be null or returned by `sgp4_init` and not yet freed.
+ */
+void sgp4_free(struct Sgp4Satellite *satellite);
+
+#endif  /* SGP4_H */
@@ -0,0 +1,288 @@
+//! Propagate the TLEs in a file (or stdin) over a time range and print the states.
+//!
+//! ```text
+//! sgp4 [--start MIN] [--stop MIN] [--step MIN] [--frame teme|ecef|geodetic]
+//!      [--model wgs72|wgs72old|wgs84] [--afspc] [--max-days DAYS] [--tcppver] [FILE]
+//! ```
+
+use std::io::{self, BufReader, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+use sgp4_rust::catalog::read_element_sets;
+use sgp4_rust::constants::DAY2MIN;
+use sgp4_rust::sgp4::{OperationMode, SGP4, ValidityMode, ValidityWindow};
+use sgp4_rust::state::State;
+use sgp4_rust::tle::ParseOptions;
+use sgp4_rust::utils::{WGSModel, degrees};
+use sgp4_rust::verification::{schedule, write_tcppver_header, write_tcppver_state};
+
+const USAGE: &str = "usage: sgp4 [--start MIN] [--stop MIN] [--step MIN] \
+[--frame teme|ecef|geodetic] [--model wgs72|wgs72old|wgs84] [--afspc] [--max-days DAYS] \
+[--tcppver] [FILE]
+
+Reads two or three line element sets from FILE, or stdin if no FILE is given, and
+prints the state of each satellite from --start to --stop minutes since its epoch
+(default 0 to 1440 in steps of 60). Positions are in km, velocities in km/s and
+angles in degrees. Lines starting with '#' are comments. With --max-days, a warning
+is printed for satellites propagated further than that from their epoch.
+
+With --tcppver the TEME states are written in the format of tcppver.out, the output
+of the reference implementation, starting with the state at epoch. The start, stop
+and step after line 2 of SGP4-VER.TLE style files override the options; use
+--model wgs72 --afspc to reproduce the reference output.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Teme,
+    Ecef,
+    Geodetic,
+}
+
+#[derive(Debug)]
+struct Options {
+    start: f64,
+    stop: f64,
+    step: f64,
+    frame: Frame,
+    model: WGSModel,
+    operation_mode: OperationMode,
+    max_days: Option<f64>,
+    tcppver: bool,
+    path: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        start: 0.0,
+        stop: DAY2MIN,
+        step: 60.0,
+        frame: Frame::Teme,
+        model: WGSModel::WGS_72,
+        operation_mode: OperationMode::Improved,
+        max_days: None,
+        tcppver: false,
+        path: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        let mut minutes = |name: &str| {
+            let text = value(name)?;
+            text.parse::<f64>()
+                .map_err(|_| format!("invalid {name} {text:?}"))
+        };
+        match arg.as_str() {
+            "--start" => options.start = minutes("--start")?,
+            "--stop" => options.stop = minutes("--stop")?,
+            "--step" => options.step = minutes("--step")?,
+            "--frame" => {
+                options.frame = match value("--frame")?.as_str() {
+                    "teme" => Frame::Teme,
+                    "ecef" => Frame::Ecef,
+                    "geodetic" => Frame::Geodetic,
+                    frame => return Err(format!("unknown frame {frame:?}")),
+                }
+            }
+            "--model" => {
+                options.model = match value("--model")?.as_str() {
+                    "wgs72" => WGSModel::WGS_72,
+                    "wgs72old" => WGSModel::WGS_72_LOW_PRECISION,
+                    "wgs84" => WGSModel::WGS_84,
+                    model => return Err(format!("unknown model {model:?}")),
+                }
+            }
+            "--afspc" => options.operation_mode = OperationMode::Afspc,
+            "--max-days" => {
+                let text = value("--max-days")?;
+                let days = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid --max-days {text:?}"))?;
+                options.max_days = Some(days);
+            }
+            "--tcppver" => options.tcppver = true,
+            "-h" | "--help" => return Err(USAGE.into()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg:?}")),
+            _ if options.path.is_none() => options.path = Some(arg),
+            _ => return Err("only one FILE can be given".into()),
+        }
+    }
+    if options.step <= 0.0 {
+        return Err("--step must be positive".into());
+    }
+    if options.tcppver && options.frame != Frame::Teme {
+        return Err("--tcppver writes TEME states only".into());
+    }
+    Ok(options)
+}
+
+fn run(options: &Options, out: &mut impl Write) -> io::Result<()> {
+    let parse_options = ParseOptions::default();
+    let records = match &options.path {
+        Some(path) => read_element_sets(BufReader::new(fs::File::open(path)?), parse_options)?,
+        None => read_element_sets(io::stdin().lock(), parse_options)?,
+    };
+    for record in records {
+        let tle = match record {
+            Ok((_, tle)) => tle,
+            Err(error) => {
+                eprintln!("skipping element set: {error}");
+                continue;
+            }
+        };
+        let (satnum, name) = (tle.satnum(), tle.name().unwrap_or("").to_string());
+        for warning in tle.warnings() {
+            eprintln!("warning: {satnum}: {warning}");
+        }
+        let run_schedule = verification_schedule(tle.line2());
+        let sgp4 = SGP4::from_tle(options.model, options.operation_mode, tle);
+        if options.tcppver {
+            let (start, stop, step) =
+                run_schedule.unwrap_or((options.start, options.stop, options.step));
+            let mut text = String::new();
+            write_tcppver_header(&mut text, satnum).map_err(io::Error::other)?;
+            // the reference output keeps the header of satellites that fail to initialize
+            match sgp4 {
+                Ok(sgp4) => {
+                    for t in schedule(start, stop, step) {
+                        match sgp4.propagate(t) {
+                            Ok(state) => write_tcppver_state(
+                                &mut text,
+                                t,
+                                state.position_km,
+                                state.velocity_km_s,
+                            )
+                            .map_err(io::Error::other)?,
+                            Err(error) => {
+                                eprintln!("propagation failed for {error}");
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(error) => eprintln!("{satnum}: initialization failed: {error}"),
+            }
+            out.write_all(text.as_bytes())?;
+            continue;
+        }
+        let mut sgp4 = match sgp4 {
+            Ok(sgp4) => sgp4,
+            Err(error) => {
+                eprintln!("skipping {satnum}: {error}");
+                continue;
+            }
+        };
+        sgp4.set_validity_window(
+            options
+                .max_days
+                .map(|days| ValidityWindow::days(days, ValidityMode::Warn)),
+        );
+        writeln!(out, "# {satnum} {name}")?;
+        let epoch = sgp4.epoch();
+        let mut warned = false;
+        let states = match sgp4.iter(options.start, options.stop, options.step) {
+            Ok(states) => states,
+            Err(error) => {
+                eprintln!("propagation failed for {error}");
+                continue;
+            }
+        };
+        for state in states {
+            let state = match state {
+                Ok(state) => state,
+                Err(error) => {
+                    eprintln!("propagation failed for {error}");
+                    break;
+                }
+            };
+            let t = state.epoch.minutes_since(epoch);
+            if let Some(stale) = sgp4.stale(t)
+                && !warned
+            {
+                eprintln!("warning: {stale}");
+                warned = true;
+            }
+            match options.frame {
+                Frame::Teme => write_state(out, t, &state)?,
+                Frame::Ecef => write_state(out, t, &state.to_ecef())?,
+                Frame::Geodetic => {
+                    let geodetic = state.to_geodetic();
+                    writeln!(
+                        out,
+                        "{t:14.8} {:12.6} {:12.6} {:12.3}",
+                        degrees(geodetic.latitude),
+                        degrees(geodetic.longitude),
+                        geodetic.altitude
+                    )?
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start, stop and step of the run after the checksum of line 2, as in SGP4-VER.TLE.
+fn verification_schedule(line2: &str) -> Option<(f64, f64, f64)> {
+    let mut values = line2.get(69..)?.split_whitespace().map(str::parse::<f64>);
+    match (values.next(), values.next(), values.next(), values.next()) {
+        (Some(Ok(start)), Some(Ok(stop)), Some(Ok(step)), None) => Some((start, stop, step)),
+        _ => None,
+    }
+}
+
+fn write_state(out: &mut impl Write, t: f64, state: &State) -> io::Result<()> {
+    let (r, v) = (state.position_km, state.velocity_km_s);
+    writeln!(
+        out,
+        "{t:14.8} {:16.8} {:16.8} {:16.8} {:13.9} {:13.9} {:13.9}",
+        r[0], r[1], r[2], v[0], v[1], v[2]
+    )
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&options, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("sgp4: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = ["--frame", "geodetic", "--step", "10", "catalog.txt"];
+        let options = parse_args(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(options.frame, Frame::Geodetic);
+        assert_eq!(
+            (options.start, options.stop, options.step),
+            (0.0, 1440.0, 10.0)
+        );
+        assert_eq!(options.path.as_deref(), Some("catalog.txt"));
+        assert!(parse_args(["--step", "0"].into_iter().map(String::from)).is_err());
+        let args = ["--max-days", "7"];
+        let options = parse_args(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(options.max_days, Some(7.0));
+        assert!(parse_args(["--frame", "icrf"].into_iter().map(String::from)).is_err());
+        let args = ["--tcppver", "--frame", "ecef"];
+        assert!(parse_args(args.into_iter().map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_verification_schedule() {
+        let line2 = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+        assert_eq!(verification_schedule(line2), None);
+        let line2 = format!("{line2}     0.0      4320.0        360.0");
+        assert_eq!(verification_schedule(&line2), Some((0.0, 4320.0, 360.0)));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::utils::*;
+
+/// Half angle of the earth's umbra cone
+const ALPHA_UMBRA: f64 = 0.264121687 * core::f64::consts::PI / 180.0;
+/// Half angle of the earth's penumbra cone
+const ALPHA_PENUMBRA: f64 = 0.269007205 * core::f64::consts::PI / 180.0;
+
+/// Whether a satellite is lit by the sun.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Illumination {
+    Sunlit,
+    /// Partially shadowed by the earth
+    Penumbra,
+    /// Fully shadowed by the earth
+    Umbra,
+}
+
+/// Geocentric position of the sun in km, in the mean equator and equinox of date.
+///
+/// Low precision analytic model (Vallado, algorithm 29), good to about 0.01 degrees
+/// between 1950 and 2050. This is close enough to TEME to compare with propagated
+/// states. `jd` is the Julian date (UT1, though any time scale will do at this
+/// accuracy).
+pub fn sun_position(jd: f64) -> [f64; 3] {
+    let tut1 = (jd - J2000) / CENT2DAY;
+
+    let meanlong = (280.460 + 36000.771285 * tut1) % 360.0;
+    let meananomaly = radians((357.529109 + 35999.050957 * tut1) % 360.0);
+    let eclplong = radians(
+        (meanlong + 1.914666471 * meananomaly.sin() + 0.019994643 * (2.0 * meananomaly).sin())
+            % 360.0,
+    );
+    let obliquity = radians(23.439291 - 0.0130042 * tut1);
+
+    // distance in AU
+    let magr =
+        1.000140612 - 0.016708617 * meananomaly.cos() - 0.000139589 * (2.0 * meananomaly).cos();
+
+    [
+        magr * AU * eclplong.cos(),
+        magr * AU * obliquity.cos() * eclplong.sin(),
+        magr * AU * obliquity.sin() * eclplong.sin(),
+    ]
+}
+
+/// Geocentric position of the moon in km, in the mean equator and equinox of date.
+///
+/// Truncated analytic series (Vallado, algorithm 31, after the Astronomical Almanac),
+/// good to about 0.3 degrees in longitude and 0.2 degrees in latitude. `jd` is the
+/// Julian date (TDB, though UT1 will do at this accuracy).
+pub fn moon_position(jd: f64) -> [f64; 3] {
+    let ttdb = (jd - J2000) / CENT2DAY;
+    let sind = |deg: f64| radians(deg).sin();
+    let cosd = |deg: f64| radians(deg).cos();
+
+    let eclplong = radians(
+        218.32 + 481267.8813 * ttdb + 6.29 * sind(134.9 + 477198.85 * ttdb)
+            - 1.27 * sind(259.2 - 413335.38 * ttdb)
+            + 0.66 * sind(235.7 + 890534.23 * ttdb)
+            + 0.21 * sind(269.9 + 954397.70 * ttdb)
+            - 0.19 * sind(357.5 + 35999.05 * ttdb)
+            - 0.11 * sind(186.6 + 966404.05 * ttdb),
+    );
+    let eclplat = radians(
+        5.13 * sind(93.3 + 483202.03 * ttdb) + 0.28 * sind(228.2 + 960400.87 * ttdb)
+            - 0.28 * sind(318.3 + 6003.18 * ttdb)
+            - 0.17 * sind(217.6 - 407332.20 * ttdb),
+    );
+    let hzparal = radians(
+        0.9508
+            + 0.0518 * cosd(134.9 + 477198.85 * ttdb)
+            + 0.0095 * cosd(259.2 - 413335.38 * ttdb)
+            + 0.0078 * cosd(235.7 + 890534.23 * ttdb)
+            + 0.0028 * cosd(269.9 + 954397.70 * ttdb),
+    );
+    let obliquity = radians(23.439291 - 0.0130042 * ttdb);
+
+    // distance from the horizontal parallax
+    let magr = RE / hzparal.sin();
+
+    let (sinlong, coslong) = eclplong.sin_cos();
+    let (sinlat, coslat) = eclplat.sin_cos();
+    let (sineps, coseps) = obliquity.sin_cos();
+    [
+        magr * coslat * coslong,
+        magr * (coseps * coslat * sinlong - sineps * sinlat),
+        magr * (sineps * coslat * sinlong + coseps * sinlat),
+    ]
+}
+
+/// Illumination of a satellite at TEME position `r` (km) at `jd`, using a conical
+/// model of the earth's shadow.
+pub fn illumination(r: [f64; 3], jd: f64) -> Illumination {
+    let rsun = sun_position(jd);
+    let dot = r[0] * rsun[0] + r[1] * rsun[1] + r[2] * rsun[2];
+    if dot >= 0.0 {
+        return Illumination::Sunlit;
+    }
+
+    // distances from the satellite to the shadow axis and along it
+    let magr = (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt();
+    let magsun = (rsun[0] * rsun[0] + rsun[1] * rsun[1] + rsun[2] * rsun[2]).sqrt();
+    let angle = (-dot / (magr * magsun)).min(1.0).acos();
+    let sathoriz = magr * angle.cos();
+    let satvert = magr * angle.sin();
+
+    let penvert = ALPHA_PENUMBRA.tan() * (RE / ALPHA_PENUMBRA.sin() + sathoriz);
+    if satvert > penvert {
+        return Illumination::Sunlit;
+    }
+    let umbvert = ALPHA_UMBRA.tan() * (RE / ALPHA_UMBRA.sin() - sathoriz);
+    if satvert > umbvert {
+        Illumination::Penumbra
+    } else {
+        Illumination::Umbra
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::jday;
+
+    #[test]
+    fn test_sun_position() {
+        // Vallado, example 5-1
+        let (jd, jdfrac) = jday(2006, (4, 2, 0, 0, 0.0));
+        let r = sun_position(jd + jdfrac);
+        let r_expected = [0.9771945, 0.1924424, 0.0834308];
+        for i in 0..3 {
+            assert!((r[i] / AU - r_expected[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_moon_position() {
+        // Vallado, example 5-3
+        let (jd, jdfrac) = jday(1994, (4, 28, 0, 0, 0.0));
+        let r = moon_position(jd + jdfrac);
+        let r_expected = [-134240.626, -311571.590, -126693.785];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_illumination() {
+        let (jd, jdfrac) = jday(2006, (4, 2, 0, 0, 0.0));
+        let rsun = sun_position(jd + jdfrac);
+        let magsun = (rsun[0] * rsun[0] + rsun[1] * rsun[1] + rsun[2] * rsun[2]).sqrt();
+        let unit = rsun.map(|x| x / magsun);
+
+        // on the sun side and straight behind the earth
+        let r = unit.map(|x| x * 7000.0);
+        assert_eq!(illumination(r, jd + jdfrac), Illumination::Sunlit);
+        let r = unit.map(|x| -x * 7000.0);
+        assert_eq!(illumination(r, jd + jdfrac), Illumination::Umbra);
+
+        // behind the earth, offset to the edge of the shadow
+        let side = [unit[1], -unit[0], 0.0];
+        let norm = (side[0] * side[0] + side[1] * side[1]).sqrt();
+        let offset = |d: f64| [0, 1, 2].map(|i| -unit[i] * 7000.0 + side[i] / norm * d);
+        assert_eq!(
+            illumination(offset(RE - 40.0), jd + jdfrac),
+            Illumination::Umbra
+        );
+        assert_eq!(
+            illumination(offset(RE), jd + jdfrac),
+            Illumination::Penumbra
+        );
+        assert_eq!(
+            illumination(offset(RE + 40.0), jd + jdfrac),
+            Illumination::Sunlit
+        );
+    }
+}
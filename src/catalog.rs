@@ -0,0 +1,739 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::array;
+use core::fmt;
+use core::slice;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+use crate::constants::{DAY2MIN, SMALL};
+use crate::frames::{EarthRotation, Geodetic, ecef_to_geodetic};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+#[cfg(feature = "std")]
+use crate::sgp4::Sgp4Builder;
+use crate::sgp4::{
+    Ephemeris, PropagationError, PropagationFailure, PropagationResult, SGP4, Sgp4Error,
+};
+use crate::state::{Frame, State};
+use crate::time::{Epoch, ToJday};
+#[cfg(feature = "std")]
+use crate::tle::{ParseOptions, TLE};
+
+/// Initialized satellites, in the order they were added, with lookup by NORAD catalog
+/// number, international designator and name.
+///
+/// A catalog may hold several element sets of a satellite; the lookups by number and
+/// designator find the one added last, and `best_for` the one for a time.
+#[derive(Debug, Default, Clone)]
+pub struct Catalog {
+    satellites: Vec<SGP4>,
+    /// the element sets of each satellite, in the order they were added
+    by_satnum: BTreeMap<u64, Vec<usize>>,
+    by_intldesg: BTreeMap<String, usize>,
+}
+
+/// Which element set of a satellite `Catalog::best_for` picks for a time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// The element set with the epoch nearest to the time, before or after it
+    #[default]
+    Nearest,
+    /// The latest element set with an epoch at or before the time, as would have been
+    /// available then
+    NearestBefore,
+}
+
+/// How `Catalog::ephemeris` goes over from one element set of a satellite to the next.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Transition {
+    /// Switch element sets halfway between their epochs, as `Selection::Nearest`
+    /// does. The states jump by the difference of the two predictions there, which is
+    /// often kilometres.
+    #[default]
+    Switch,
+    /// Blend the states of both element sets over a window of `window_min` minutes
+    /// centred on the switch, so the ephemeris is continuous. The window is narrowed to
+    /// the time between the epochs if they are closer than that.
+    Blend { window_min: f64 },
+}
+
+impl Catalog {
+    pub fn new() -> Catalog {
+        Catalog::default()
+    }
+
+    /// Add an initialized satellite at the end of the catalog.
+    pub fn push(&mut self, sgp4: SGP4) {
+        let index = self.satellites.len();
+        self.by_satnum.entry(sgp4.satnum()).or_default().push(index);
+        if !sgp4.international_designator().is_empty() {
+            self.by_intldesg
+                .insert(sgp4.international_designator().into(), index);
+        }
+        self.satellites.push(sgp4);
+    }
+
+    /// Satellite with a NORAD catalog number
+    pub fn get(&self, satnum: u64) -> Option<&SGP4> {
+        self.element_sets(satnum).last()
+    }
+
+    /// All element sets of a satellite, in the order they were added.
+    pub fn element_sets(&self, satnum: u64) -> impl DoubleEndedIterator<Item = &SGP4> {
+        self.by_satnum
+            .get(&satnum)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.satellites[i])
+    }
+
+    /// The element set of a satellite to propagate to `time`, e.g. an `Epoch` or a
+    /// `chrono::DateTime<Utc>`, as selected. The errors of SGP4 grow with the time from
+    /// epoch, so the nearest element set is the most accurate one.
+    ///
+    /// Returns `None` if the satellite isn't in the catalog, or has no element set
+    /// before `time` with `Selection::NearestBefore`.
+    pub fn best_for(&self, satnum: u64, time: &impl ToJday, selection: Selection) -> Option<&SGP4> {
+        let (jd, jdfrac) = time.to_jday();
+        let time = Epoch::new(jd, jdfrac);
+        let sets = self.element_sets(satnum);
+        match selection {
+            Selection::Nearest => sets.min_by(|a, b| {
+                let distance = |sgp4: &SGP4| (time - sgp4.epoch()).abs();
+                distance(a).total_cmp(&distance(b))
+            }),
+            Selection::NearestBefore => sets
+                .filter(|sgp4| sgp4.epoch() <= time)
+                .max_by(|a, b| (a.epoch() - b.epoch()).total_cmp(&0.0)),
+        }
+    }
+
+    /// States of a satellite from `start` to `stop` in steps of `step_min` minutes,
+    /// propagated with the element set nearest each time and going over from one to
+    /// the next as given by `transition`. Empty if the satellite isn't in the catalog.
+    ///
+    /// As in `SGP4::iter`, a failed step doesn't end the ephemeris; within a blend
+    /// window a failure of either element set fails the step. A step that isn't
+    /// positive and finite, or a blend window that is negative or not finite, fails the
+    /// whole ephemeris with `PropagationError::InvalidStep`, at the start in minutes
+    /// since the epoch of the first element set.
+    pub fn ephemeris(
+        &self,
+        satnum: u64,
+        start: &impl ToJday,
+        stop: &impl ToJday,
+        step_min: f64,
+        transition: Transition,
+    ) -> Result<Vec<PropagationResult>, PropagationFailure> {
+        let mut sets: Vec<&SGP4> = self.element_sets(satnum).collect();
+        if sets.is_empty() {
+            return Ok(Vec::new());
+        }
+        sets.sort_by(|a, b| (a.epoch() - b.epoch()).total_cmp(&0.0));
+        let window_min = match transition {
+            Transition::Switch => 0.0,
+            Transition::Blend { window_min } => window_min,
+        };
+        let (start, stop) = (start.to_jday(), stop.to_jday());
+        let start = Epoch::new(start.0, start.1);
+        let stop = Epoch::new(stop.0, stop.1);
+        let valid_step = step_min > 0.0 && step_min.is_finite();
+        if !(valid_step && window_min >= 0.0 && window_min.is_finite()) {
+            let tsince_min = start.minutes_since(sets[0].epoch());
+            return Err(sets[0].failure(PropagationError::InvalidStep, tsince_min));
+        }
+        let steps = (stop.minutes_since(start) / step_min + SMALL)
+            .floor()
+            .max(-1.0)
+            + 1.0;
+        Ok((0..steps as usize)
+            .map(|i| {
+                // multiply rather than accumulate the step so long runs don't drift
+                let time = start + i as f64 * step_min / DAY2MIN;
+                state_across(&sets, time, window_min)
+            })
+            .collect())
+    }
+
+    /// Propagate every satellite to one time, e.g. to map the whole catalog, in
+    /// catalog order. See `CatalogSnapshot`.
+    pub fn snapshot(&self, time: &impl ToJday) -> CatalogSnapshot<'_> {
+        let (jd, jdfrac) = time.to_jday();
+        let epoch = Epoch::new(jd, jdfrac);
+        CatalogSnapshot {
+            epoch,
+            rotation: EarthRotation::new(epoch.julian_date()),
+            states: self
+                .satellites
+                .iter()
+                .map(|sgp4| (sgp4, sgp4.propagate(epoch.minutes_since(sgp4.epoch()))))
+                .collect(),
+        }
+    }
+
+    /// Satellite with an international designator in the TLE form, e.g. "98067A"
+    pub fn by_international_designator(&self, intldesg: &str) -> Option<&SGP4> {
+        self.by_intldesg
+            .get(intldesg.trim())
+            .map(|&i| &self.satellites[i])
+    }
+
+    /// Satellites whose name contains `text`, ignoring case, in catalog order.
+    pub fn search_name<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a SGP4> {
+        let text = text.to_lowercase();
+        self.satellites.iter().filter(move |sgp4| {
+            sgp4.name()
+                .is_some_and(|name| name.to_lowercase().contains(&text))
+        })
+    }
+
+    /// Propagate every satellite over its run schedule (see `SGP4::run`), in catalog
+    /// order, as in the catalog runs of the reference implementation. A satellite with
+    /// an invalid step in its schedule has the failure in place of its ephemeris.
+    pub fn run(&self) -> impl Iterator<Item = (&SGP4, Result<Ephemeris<'_>, PropagationFailure>)> {
+        self.satellites.iter().map(|sgp4| (sgp4, sgp4.run()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.satellites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.satellites.is_empty()
+    }
+
+    /// Iterate over the satellites in catalog order.
+    pub fn iter(&self) -> slice::Iter<'_, SGP4> {
+        self.satellites.iter()
+    }
+}
+
+impl FromIterator<SGP4> for Catalog {
+    fn from_iter<I: IntoIterator<Item = SGP4>>(iter: I) -> Catalog {
+        let mut catalog = Catalog::new();
+        for sgp4 in iter {
+            catalog.push(sgp4);
+        }
+        catalog
+    }
+}
+
+impl IntoIterator for Catalog {
+    type Item = SGP4;
+    type IntoIter = alloc::vec::IntoIter<SGP4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.satellites.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Catalog {
+    type Item = &'a SGP4;
+    type IntoIter = slice::Iter<'a, SGP4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.satellites.iter()
+    }
+}
+
+/// The TEME states of the satellites of a catalog at one time, from
+/// `Catalog::snapshot`.
+///
+/// The terms that only depend on the time, the sidereal time and the rotation of the
+/// earth, are computed once for the snapshot, so converting the states to the earth
+/// fixed frame or geodetic coordinates costs a matrix product per satellite.
+#[derive(Debug, Clone)]
+pub struct CatalogSnapshot<'a> {
+    epoch: Epoch,
+    rotation: EarthRotation,
+    states: Vec<(&'a SGP4, PropagationResult)>,
+}
+
+impl<'a> CatalogSnapshot<'a> {
+    /// Time of the snapshot in UTC
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    /// Greenwich mean sidereal time of the snapshot in radians
+    pub fn gmst(&self) -> f64 {
+        self.rotation.gmst()
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// The satellites and their TEME states, in catalog order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a SGP4, &PropagationResult)> {
+        self.states.iter().map(|(sgp4, state)| (*sgp4, state))
+    }
+
+    /// The satellites and their earth fixed states, see `frames::teme_to_ecef`.
+    pub fn ecef(&self) -> impl Iterator<Item = (&'a SGP4, Result<State, PropagationFailure>)> {
+        self.iter().map(|(sgp4, state)| {
+            let state = state.map(|state| {
+                let (position_km, velocity_km_s) = self
+                    .rotation
+                    .teme_to_ecef(state.position_km, state.velocity_km_s);
+                State {
+                    position_km,
+                    velocity_km_s,
+                    frame: Frame::Ecef,
+                    ..state
+                }
+            });
+            (sgp4, state)
+        })
+    }
+
+    /// The satellites and the geodetic coordinates of their positions.
+    pub fn geodetic(
+        &self,
+    ) -> impl Iterator<Item = (&'a SGP4, Result<Geodetic, PropagationFailure>)> {
+        self.ecef()
+            .map(|(sgp4, state)| (sgp4, state.map(|state| ecef_to_geodetic(state.position_km))))
+    }
+}
+
+/// State at `time` from the element sets of a satellite sorted by epoch, blended over
+/// `window_min` minutes around each switch.
+fn state_across(sets: &[&SGP4], time: Epoch, window_min: f64) -> PropagationResult {
+    for pair in sets.windows(2) {
+        let (before, after) = (pair[0], pair[1]);
+        let gap_min = after.epoch().minutes_since(before.epoch());
+        let switch = before.epoch() + 0.5 * gap_min / DAY2MIN;
+        let from_switch_min = time.minutes_since(switch);
+        let half_window_min = 0.5 * window_min.min(gap_min);
+        if from_switch_min < -half_window_min {
+            return before.propagate_at(&time);
+        }
+        if from_switch_min < half_window_min {
+            let a = before.propagate_at(&time)?;
+            let b = after.propagate_at(&time)?;
+            // smoothstep weight of the later element set, whose rate is zero at the
+            // edges of the window so the velocity is continuous too
+            let s = 0.5 * (from_switch_min / half_window_min + 1.0);
+            let weight = s * s * (3.0 - 2.0 * s);
+            let weight_rate = 6.0 * s * (1.0 - s) / (2.0 * half_window_min * 60.0);
+            let position_km = array::from_fn(|i| {
+                a.position_km[i] + weight * (b.position_km[i] - a.position_km[i])
+            });
+            let velocity_km_s = array::from_fn(|i| {
+                a.velocity_km_s[i]
+                    + weight * (b.velocity_km_s[i] - a.velocity_km_s[i])
+                    + weight_rate * (b.position_km[i] - a.position_km[i])
+            });
+            return Ok(State {
+                position_km,
+                velocity_km_s,
+                ..a
+            });
+        }
+    }
+    sets[sets.len() - 1].propagate_at(&time)
+}
+
+/// An element set of a TLE file that couldn't be added to a catalog. Lines are
+/// numbered from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordError {
+    /// A line 1 that isn't followed by a line 2, or a line 2 without a line 1
+    Unpaired { line: usize },
+    /// The element set starting at `line` could not be parsed or initialized
+    Invalid { line: usize, error: Sgp4Error },
+}
+
+impl RecordError {
+    /// Line of the file the record starts at
+    pub fn line(&self) -> usize {
+        match self {
+            RecordError::Unpaired { line } | RecordError::Invalid { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Unpaired { line } => {
+                write!(f, "line {line} is not part of a complete element set")
+            }
+            RecordError::Invalid { line, error } => {
+                write!(f, "element set at line {line}: {error}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordError::Unpaired { .. } => None,
+            RecordError::Invalid { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Read a file of two or three line element sets and initialize every satellite in it
+/// with the default configuration of `SGP4::builder`. See `from_reader_with`.
+#[cfg(feature = "std")]
+pub fn from_reader(reader: impl BufRead) -> io::Result<(Catalog, Vec<RecordError>)> {
+    from_reader_with(reader, Sgp4Builder::default())
+}
+
+/// Read a file of two or three line element sets and initialize every satellite in it
+/// as configured by `builder`.
+///
+/// The element sets are read by `read_element_sets`. Element sets that can't be
+/// paired, parsed or initialized are returned as errors next to the catalog instead of
+/// ending the read; only I/O errors do that. Checksum mismatches accepted by
+/// `ChecksumMode::Warn` are kept with their satellites, see `SGP4::warnings`.
+#[cfg(feature = "std")]
+pub fn from_reader_with(
+    reader: impl BufRead,
+    builder: Sgp4Builder,
+) -> io::Result<(Catalog, Vec<RecordError>)> {
+    let template = builder.build();
+    let mut catalog = Catalog::new();
+    let mut errors = Vec::new();
+    for record in read_element_sets(reader, template.parse_options())? {
+        let init = record.and_then(|(line, tle)| {
+            let mut sgp4 = template.clone();
+            match sgp4.tle2rv(tle) {
+                Ok(_) => Ok(sgp4),
+                Err(error) => Err(RecordError::Invalid { line, error }),
+            }
+        });
+        match init {
+            Ok(sgp4) => catalog.push(sgp4),
+            Err(error) => errors.push(error),
+        }
+    }
+    Ok((catalog, errors))
+}
+
+/// Read the element sets of a file of two or three line element sets, in file order,
+/// each with the line it starts at.
+///
+/// A line 2 completes the line 1 before it, and a line that precedes a line 1 and
+/// isn't a TLE line is taken as the object name. Blank lines and lines starting with
+/// '#' are skipped. A line 1 or 2 without its partner and an element set that can't
+/// be parsed with `options` are errors in place of the element set.
+#[cfg(feature = "std")]
+pub fn read_element_sets(
+    reader: impl BufRead,
+    options: ParseOptions,
+) -> io::Result<Vec<Result<(usize, TLE), RecordError>>> {
+    let mut records = Vec::new();
+    let mut name: Option<(usize, String)> = None;
+    let mut line1: Option<(usize, String)> = None;
+    for (i, line) in reader.lines().enumerate() {
+        let (number, line) = (i + 1, line?.trim_end().to_string());
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("2 ")
+            && let Some((start, line1)) = line1.take()
+        {
+            let name = name.take();
+            let start = name.as_ref().map_or(start, |(start, _)| *start);
+            let record = match TLE::parse_with_options(line1, line, options) {
+                Ok(mut tle) => {
+                    if let Some((_, name)) = &name {
+                        tle.set_name(name);
+                    }
+                    Ok((start, tle))
+                }
+                Err(error) => Err(RecordError::Invalid {
+                    line: start,
+                    error: error.into(),
+                }),
+            };
+            records.push(record);
+            continue;
+        }
+        // anything else ends the element set that was started, with its name
+        if let Some((start, _)) = line1.take() {
+            let start = name.take().map_or(start, |(start, _)| start);
+            records.push(Err(RecordError::Unpaired { line: start }));
+        }
+        if line.starts_with("1 ") {
+            line1 = Some((number, line));
+        } else if line.starts_with("2 ") {
+            records.push(Err(RecordError::Unpaired { line: number }));
+            name = None;
+        } else {
+            name = Some((number, line));
+        }
+    }
+    if let Some((start, _)) = line1 {
+        let start = name.map_or(start, |(start, _)| start);
+        records.push(Err(RecordError::Unpaired { line: start }));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ISS_LINE1, ISS_LINE2, VANGUARD_LINE1, VANGUARD_LINE2};
+    use crate::sgp4::OperationMode;
+    use crate::tle::TLE;
+    use crate::utils::WGSModel;
+
+    fn sgp4(line0: &str, line1: &str, line2: &str) -> SGP4 {
+        let tle = TLE::from_3le(line0.into(), line1.into(), line2.into()).unwrap();
+        SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let catalog: Catalog = [
+            sgp4("ISS (ZARYA)", ISS_LINE1, ISS_LINE2),
+            sgp4("VANGUARD 1", VANGUARD_LINE1, VANGUARD_LINE2),
+            // a later element set of the ISS
+            sgp4(
+                "ISS (ZARYA)",
+                &ISS_LINE1.replace("24001.5", "24002.5"),
+                ISS_LINE2,
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.get(5).unwrap().name(), Some("VANGUARD 1"));
+        let iss = catalog.get(25544).unwrap();
+        assert_eq!(iss.epoch(), catalog.iter().nth(2).unwrap().epoch());
+        assert_eq!(
+            catalog
+                .by_international_designator("58002B")
+                .unwrap()
+                .satnum(),
+            5
+        );
+        assert!(catalog.get(8195).is_none());
+        assert!(catalog.by_international_designator("75081A").is_none());
+
+        let satnums: Vec<u64> = catalog.search_name("zarya").map(SGP4::satnum).collect();
+        assert_eq!(satnums, [25544, 25544]);
+        assert_eq!(catalog.search_name("STARLINK").count(), 0);
+
+        let run: Vec<usize> = catalog
+            .run()
+            .map(|(_, ephemeris)| ephemeris.unwrap().count())
+            .collect();
+        assert_eq!(run, [289, 289, 289]);
+    }
+
+    #[test]
+    fn test_best_for() {
+        let line2 = ISS_LINE2;
+        let catalog: Catalog = ["24003.50000000", "24001.50000000", "24002.50000000"]
+            .into_iter()
+            .map(|epoch| {
+                let line1 = alloc::format!(
+                    "1 25544U 98067A   {epoch}  .00016717  00000-0  30292-3 0  9993"
+                );
+                sgp4("ISS (ZARYA)", &line1, line2)
+            })
+            .collect();
+        let epochs: Vec<Epoch> = catalog.iter().map(SGP4::epoch).collect();
+        let best = |days: f64, selection| {
+            catalog
+                .best_for(25544, &(epochs[1] + days), selection)
+                .map(SGP4::epoch)
+        };
+
+        assert_eq!(catalog.element_sets(25544).count(), 3);
+        assert_eq!(best(-3.0, Selection::Nearest), Some(epochs[1]));
+        assert_eq!(best(0.7, Selection::Nearest), Some(epochs[2]));
+        assert_eq!(best(1.4, Selection::Nearest), Some(epochs[2]));
+        assert_eq!(best(10.0, Selection::Nearest), Some(epochs[0]));
+        assert_eq!(best(-0.1, Selection::NearestBefore), None);
+        assert_eq!(best(0.0, Selection::NearestBefore), Some(epochs[1]));
+        assert_eq!(best(1.9, Selection::NearestBefore), Some(epochs[2]));
+        assert_eq!(best(2.0, Selection::NearestBefore), Some(epochs[0]));
+        assert!(
+            catalog
+                .best_for(5, &epochs[0], Selection::Nearest)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ephemeris() {
+        // the same elements a day later put the satellite far from the first prediction
+        let catalog: Catalog = [
+            sgp4(
+                "ISS (ZARYA)",
+                &ISS_LINE1.replace("24001.5", "24002.5"),
+                ISS_LINE2,
+            ),
+            sgp4("ISS (ZARYA)", ISS_LINE1, ISS_LINE2),
+        ]
+        .into_iter()
+        .collect();
+        let (later, earlier) = (
+            catalog.iter().next().unwrap(),
+            catalog.iter().nth(1).unwrap(),
+        );
+        let switch = earlier.epoch() + 0.5;
+        let at = |minutes: f64| switch + minutes / DAY2MIN;
+        let blend = Transition::Blend { window_min: 60.0 };
+        let state = |minutes: f64, transition| {
+            catalog
+                .ephemeris(25544, &at(minutes), &at(minutes), 1.0, transition)
+                .unwrap()[0]
+                .unwrap()
+        };
+        let distance =
+            |a: [f64; 3], b: [f64; 3]| crate::utils::mag([a[0] - b[0], a[1] - b[1], a[2] - b[2]]);
+
+        // switching jumps from one prediction to the other
+        let before = earlier.propagate_at(&at(-1e-3)).unwrap();
+        let after = later.propagate_at(&at(0.0)).unwrap();
+        assert_eq!(state(-1e-3, Transition::Switch), before);
+        assert_eq!(state(0.0, Transition::Switch), after);
+        assert!(distance(before.position_km, after.position_km) > 100.0);
+
+        // blending meets each prediction at the edges of the window and halves them
+        // at the switch
+        for (minutes, sgp4) in [(-30.0, earlier), (30.0, later)] {
+            let expected = sgp4.propagate_at(&at(minutes)).unwrap();
+            let state = state(minutes, blend);
+            assert!(distance(state.position_km, expected.position_km) < 1e-6);
+            assert!(distance(state.velocity_km_s, expected.velocity_km_s) < 1e-9);
+        }
+        let a = earlier.propagate_at(&at(0.0)).unwrap().position_km;
+        let b = later.propagate_at(&at(0.0)).unwrap().position_km;
+        let middle = state(0.0, blend).position_km;
+        assert!(distance(middle, [0, 1, 2].map(|i| 0.5 * (a[i] + b[i]))) < 1e-6);
+
+        // the blended velocity is the rate of the blended position
+        let h = 0.01;
+        let states = catalog
+            .ephemeris(25544, &at(-10.0 - h), &at(-10.0 + h), h, blend)
+            .unwrap();
+        assert_eq!(states.len(), 3);
+        let r0 = states[0].unwrap().position_km;
+        let r2 = states[2].unwrap().position_km;
+        let rate = [0, 1, 2].map(|i| (r2[i] - r0[i]) / (2.0 * h * 60.0));
+        assert!(distance(rate, states[1].unwrap().velocity_km_s) < 1e-4);
+
+        assert_eq!(
+            catalog.ephemeris(5, &at(0.0), &at(10.0), 1.0, blend),
+            Ok(Vec::new())
+        );
+
+        for step in [0.0, f64::NAN] {
+            let failure = catalog
+                .ephemeris(25544, &at(0.0), &at(10.0), step, blend)
+                .unwrap_err();
+            assert_eq!(failure.error, PropagationError::InvalidStep);
+            assert_eq!(failure.epoch, earlier.epoch());
+        }
+        for window_min in [-1.0, f64::NAN, f64::INFINITY] {
+            let blend = Transition::Blend { window_min };
+            let failure = catalog
+                .ephemeris(25544, &at(0.0), &at(10.0), 1.0, blend)
+                .unwrap_err();
+            assert_eq!(failure.error, PropagationError::InvalidStep);
+        }
+        let blend = Transition::Blend { window_min: 0.0 };
+        assert_eq!(state(0.0, blend), state(0.0, Transition::Switch));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let catalog: Catalog = [
+            sgp4("ISS (ZARYA)", ISS_LINE1, ISS_LINE2),
+            sgp4("VANGUARD 1", VANGUARD_LINE1, VANGUARD_LINE2),
+        ]
+        .into_iter()
+        .collect();
+        let time = Epoch::new(2460311.5, 0.25);
+        let snapshot = catalog.snapshot(&time);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.gmst(), crate::time::gstime(time.julian_date()));
+
+        // the same as converting the states one at a time
+        let ecef = snapshot.ecef().zip(snapshot.geodetic());
+        for ((sgp4, state), ((_, ecef), (_, geodetic))) in snapshot.iter().zip(ecef) {
+            let expected = sgp4.propagate_at(&time).unwrap();
+            assert_eq!(*state, Ok(expected));
+            assert_eq!(state.unwrap().epoch, time);
+            assert_eq!(ecef.unwrap(), expected.to_ecef());
+            assert_eq!(geodetic.unwrap(), expected.to_geodetic());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_reader() {
+        let text = "\
+# three line element sets, and a two line one
+ISS (ZARYA)
+1 25544U 98067A   24001.50000000  .00016717  00000-0  30292-3 0  9993
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.50074720432548
+
+1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667
+BROKEN
+1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2x82 348.7242 1859667 331.7664  19.3264 10.82419157413667
+1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813
+2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656
+1 25544U 98067A   24001.50000000  .00016717  00000-0  30292-3 0  9993
+";
+        let (catalog, errors) = from_reader(text.as_bytes()).unwrap();
+        let satnums: Vec<u64> = catalog.iter().map(SGP4::satnum).collect();
+        assert_eq!(satnums, [25544, 5, 8195]);
+        assert_eq!(catalog.iter().next().unwrap().name(), Some("ISS (ZARYA)"));
+        assert_eq!(catalog.iter().nth(1).unwrap().name(), None);
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0],
+            RecordError::Invalid {
+                line: 8,
+                error: Sgp4Error::Parse(_)
+            }
+        ));
+        assert_eq!(errors[1], RecordError::Unpaired { line: 13 });
+        assert!(catalog.iter().all(|sgp4| sgp4.warnings().is_empty()));
+
+        // a wrong checksum is reported with the satellite in the default Warn mode
+        let text = format!("{}0\n{ISS_LINE2}\n", &ISS_LINE1[..68]);
+        let (catalog, errors) = from_reader(text.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        assert!(matches!(
+            catalog.get(25544).unwrap().warnings(),
+            [crate::tle::TleParseError::ChecksumMismatch { line: 1, .. }]
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_read_element_sets() {
+        // an orphan line 2, and a line 1 followed by another line 1
+        let text = format!(
+            "{ISS_LINE2}\nVANGUARD 1\n{ISS_LINE1}\n{ISS_LINE1}\n{ISS_LINE2}\n{ISS_LINE2}\n"
+        );
+        let records = read_element_sets(text.as_bytes(), ParseOptions::default()).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0], Err(RecordError::Unpaired { line: 1 }));
+        assert_eq!(records[1], Err(RecordError::Unpaired { line: 2 }));
+        let (line, tle) = records[2].as_ref().unwrap();
+        assert_eq!((*line, tle.satnum(), tle.name()), (4, 25544, None));
+        assert_eq!(records[3], Err(RecordError::Unpaired { line: 6 }));
+    }
+}
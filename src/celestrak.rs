@@ -0,0 +1,243 @@
+use core::fmt;
+use core::time::Duration;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::elements::Elements;
+use crate::http::{HttpClient, percent_encode};
+use crate::omm::{OmmParseError, parse_json};
+use crate::tle::TLE;
+
+/// Base URL of Celestrak
+pub const CELESTRAK_URL: &str = "https://celestrak.org";
+
+/// Default maximum age of cached responses. Celestrak updates the elements a few times
+/// a day and asks not to download them more often than that.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Errors of Celestrak requests.
+#[derive(Debug)]
+pub enum CelestrakError {
+    /// The request got no response, and nothing was cached
+    Http(io::Error),
+    /// The response has an error status
+    Status { status: u16, body: String },
+    /// The response doesn't hold valid GP records, e.g. for an unknown group
+    Parse(OmmParseError),
+    /// The cache couldn't be read or written
+    Cache(io::Error),
+}
+
+impl fmt::Display for CelestrakError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CelestrakError::Http(error) => write!(f, "request failed: {error}"),
+            CelestrakError::Status { status, body } => {
+                write!(f, "request failed with status {status}: {body:?}")
+            }
+            CelestrakError::Parse(error) => write!(f, "invalid response: {error}"),
+            CelestrakError::Cache(error) => write!(f, "cache error: {error}"),
+        }
+    }
+}
+
+impl Error for CelestrakError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CelestrakError::Http(error) | CelestrakError::Cache(error) => Some(error),
+            CelestrakError::Parse(error) => Some(error),
+            CelestrakError::Status { .. } => None,
+        }
+    }
+}
+
+impl From<OmmParseError> for CelestrakError {
+    fn from(error: OmmParseError) -> Self {
+        CelestrakError::Parse(error)
+    }
+}
+
+/// Fetcher of the GP element groups of Celestrak (e.g. "stations", "starlink",
+/// "active"), returning the mean elements of the records.
+///
+/// With a cache directory, responses are kept on disk and reused until they are older
+/// than the maximum age, so repeated runs don't download the same elements again. When
+/// a request fails, an outdated response is used rather than none, so applications keep
+/// working offline.
+#[derive(Debug)]
+pub struct CelestrakClient<C: HttpClient> {
+    http: C,
+    base_url: String,
+    cache_dir: Option<PathBuf>,
+    max_age: Duration,
+}
+
+impl<C: HttpClient> CelestrakClient<C> {
+    /// Create a client sending requests through `http`, without a cache.
+    pub fn new(http: C) -> Self {
+        CelestrakClient {
+            http,
+            base_url: CELESTRAK_URL.into(),
+            cache_dir: None,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// Cache the responses in `dir`, which is created if needed.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Download the elements again once the cached response is older than `max_age`
+    /// (`DEFAULT_MAX_AGE` by default).
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Send the requests to another server than `CELESTRAK_URL`, e.g. a mirror.
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').into();
+        self
+    }
+
+    /// Elements of the satellites of a group, e.g. "starlink".
+    pub fn group(&mut self, group: &str) -> Result<Vec<Elements>, CelestrakError> {
+        let cache = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", percent_encode(&group.to_lowercase()))));
+        if let Some(path) = &cache
+            && let Some(age) = age(path)
+            && age <= self.max_age
+        {
+            return read_cache(path);
+        }
+
+        let url = format!(
+            "{}/NORAD/elements/gp.php?GROUP={}&FORMAT=json",
+            self.base_url,
+            percent_encode(group)
+        );
+        let (body, tles) = match (self.download(&url), &cache) {
+            (Ok(response), _) => response,
+            // an error status or unusable records, e.g. when rate limited, are no
+            // better than no response
+            (Err(_), Some(path)) if path.exists() => return read_cache(path),
+            (Err(error), _) => return Err(error),
+        };
+        // only valid records are cached
+        if let Some(path) = &cache {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(CelestrakError::Cache)?;
+            }
+            fs::write(path, &body).map_err(CelestrakError::Cache)?;
+        }
+        Ok(tles.into_iter().map(Elements::from).collect())
+    }
+
+    /// Body and records of a successful response.
+    fn download(&mut self, url: &str) -> Result<(String, Vec<TLE>), CelestrakError> {
+        let response = self.http.get(url, &[]).map_err(CelestrakError::Http)?;
+        if !response.is_success() {
+            return Err(CelestrakError::Status {
+                status: response.status,
+                body: response.body,
+            });
+        }
+        let tles = parse_json(&response.body)?;
+        Ok((response.body, tles))
+    }
+}
+
+/// Time since a cached response was written, or `None` if there is none
+fn age(path: &Path) -> Option<Duration> {
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+    // a file from the future is as good as new
+    Some(
+        SystemTime::now()
+            .duration_since(modified.ok()?)
+            .unwrap_or_default(),
+    )
+}
+
+fn read_cache(path: &Path) -> Result<Vec<Elements>, CelestrakError> {
+    let text = fs::read_to_string(path).map_err(CelestrakError::Cache)?;
+    Ok(parse_json(&text)?.into_iter().map(Elements::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+    use crate::http::mock::{GP, MockHttp};
+
+    fn ok(body: &str) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            body: body.into(),
+            ..HttpResponse::default()
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let dir = std::env::temp_dir().join(format!("sgp4-celestrak-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let http = MockHttp {
+            responses: vec![ok(GP), ok("No GP data found")],
+            ..MockHttp::default()
+        };
+        let mut client = CelestrakClient::new(http).cache_dir(&dir);
+        let elements = client.group("Stations").unwrap();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].satnum, 5);
+        assert_eq!(
+            client.http.requests[0].url,
+            "https://celestrak.org/NORAD/elements/gp.php?GROUP=Stations&FORMAT=json"
+        );
+        let request = &client.http.requests[0];
+        assert!(request.headers.is_empty() && request.body.is_none());
+
+        // cached, then outdated but used as the client is offline
+        assert_eq!(client.group("stations").unwrap(), elements);
+        assert_eq!(client.http.requests.len(), 1);
+        let mut client = client.max_age(Duration::ZERO);
+        assert!(matches!(
+            client.group("unknown"),
+            Err(CelestrakError::Parse(_))
+        ));
+        assert!(!dir.join("unknown.json").exists());
+        assert_eq!(client.group("stations").unwrap(), elements);
+        assert_eq!(client.http.requests.len(), 3);
+
+        // as it is when rate limited or sent something unusable
+        let mut client = CelestrakClient::new(MockHttp {
+            responses: vec![
+                HttpResponse {
+                    status: 403,
+                    ..ok("rate limited")
+                },
+                ok("No GP data found"),
+            ],
+            ..MockHttp::default()
+        })
+        .cache_dir(&dir)
+        .max_age(Duration::ZERO);
+        assert_eq!(client.group("stations").unwrap(), elements);
+        assert_eq!(client.group("stations").unwrap(), elements);
+        assert_eq!(client.http.requests.len(), 2);
+
+        let mut client = CelestrakClient::new(MockHttp::default());
+        assert!(matches!(
+            client.group("stations"),
+            Err(CelestrakError::Http(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
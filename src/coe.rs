@@ -0,0 +1,254 @@
+use core::f64::consts::PI;
+
+use crate::constants::*;
+use crate::kepler::{newtonm, newtonnu};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::utils::{angle, cross, dot, mag};
+
+/// Eccentricities and inclinations below this are taken as circular and equatorial, as
+/// in the reference rv2coe
+const TOLERANCE: f64 = 1.0e-8;
+
+/// Classical orbital elements of a state vector, as found by `rv2coe`.
+///
+/// Angles are in radians in [0, 2pi). Angles that are undefined for the type of orbit
+/// are `None`: the node of equatorial orbits, the perigee of circular orbits, and the
+/// alternate angles of the orbit types they are meant for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassicalElements {
+    /// Semilatus rectum in km
+    pub semilatus_rectum: f64,
+    /// Semimajor axis in km, negative for hyperbolic and infinite for parabolic orbits
+    pub semimajor_axis: f64,
+    pub eccentricity: f64,
+    /// Inclination in [0, pi]
+    pub inclination: f64,
+    /// Right ascension of the ascending node
+    pub raan: Option<f64>,
+    pub arg_of_perigee: Option<f64>,
+    pub true_anomaly: Option<f64>,
+    /// Mean anomaly, or the argument of latitude or true longitude of circular orbits
+    pub mean_anomaly: Option<f64>,
+    /// Argument of latitude, for circular inclined orbits
+    pub arg_of_latitude: Option<f64>,
+    /// True longitude, for circular equatorial orbits
+    pub true_longitude: Option<f64>,
+    /// Longitude of periapsis, for elliptical equatorial orbits
+    pub longitude_of_periapsis: Option<f64>,
+}
+
+impl ClassicalElements {
+    /// Elements of an orbit with the semimajor axis in km and the angles in radians,
+    /// e.g. to set up a synthetic orbit for `coe2rv`.
+    pub fn new(
+        semimajor_axis: f64,
+        eccentricity: f64,
+        inclination: f64,
+        raan: f64,
+        arg_of_perigee: f64,
+        true_anomaly: f64,
+    ) -> ClassicalElements {
+        ClassicalElements {
+            semilatus_rectum: semimajor_axis * (1.0 - eccentricity * eccentricity),
+            semimajor_axis,
+            eccentricity,
+            inclination,
+            raan: Some(raan),
+            arg_of_perigee: Some(arg_of_perigee),
+            true_anomaly: Some(true_anomaly),
+            mean_anomaly: newtonnu(eccentricity, true_anomaly).map(|(_, m)| m),
+            arg_of_latitude: None,
+            true_longitude: None,
+            longitude_of_periapsis: None,
+        }
+    }
+}
+
+/// Osculating classical orbital elements of a position in km and velocity in km/s,
+/// with the gravitational parameter `mu` in km^3/s^2 (e.g. `GravitationalConstants::mu`).
+///
+/// The elements are in the frame of the state: equatorial elements of a TEME state from
+/// `propagate` are referred to the true equator and mean equinox. Returns `None` for
+/// rectilinear motion, which has no orbital plane.
+pub fn rv2coe(r: [f64; 3], v: [f64; 3], mu: f64) -> Option<ClassicalElements> {
+    let magr = mag(r);
+    let magv = mag(v);
+    let hbar = cross(r, v);
+    let magh = mag(hbar);
+    if magh <= TOLERANCE {
+        return None;
+    }
+
+    // node vector and eccentricity vector
+    let nbar = [-hbar[1], hbar[0], 0.0];
+    let magn = mag(nbar);
+    let c1 = magv * magv - mu / magr;
+    let rdotv = dot(r, v);
+    let ebar = [0, 1, 2].map(|i| (c1 * r[i] - rdotv * v[i]) / mu);
+    let ecc = mag(ebar);
+
+    // semimajor axis from the specific mechanical energy
+    let sme = magv * magv * 0.5 - mu / magr;
+    let a = if sme.abs() > TOLERANCE {
+        -mu / (2.0 * sme)
+    } else {
+        f64::INFINITY
+    };
+    let p = magh * magh / mu;
+    let incl = (hbar[2] / magh).clamp(-1.0, 1.0).acos();
+
+    let equatorial = incl < TOLERANCE || (incl - PI).abs() < TOLERANCE;
+    let circular = ecc < TOLERANCE;
+    // reflect an angle measured with acos into the half given by `negative`
+    let quadrant = |angle: f64, negative: bool| if negative { TWOPI - angle } else { angle };
+
+    let raan = (magn > TOLERANCE)
+        .then(|| quadrant((nbar[0] / magn).clamp(-1.0, 1.0).acos(), nbar[1] < 0.0));
+    let argp = (!circular && !equatorial).then(|| quadrant(angle(nbar, ebar), ebar[2] < 0.0));
+    let nu = (!circular).then(|| quadrant(angle(ebar, r), rdotv < 0.0));
+    let arglat = (circular && !equatorial).then(|| quadrant(angle(nbar, r), r[2] < 0.0));
+    let lonper = (!circular && equatorial).then(|| {
+        let lonper = quadrant((ebar[0] / ecc).clamp(-1.0, 1.0).acos(), ebar[1] < 0.0);
+        quadrant(lonper, incl > HALFPI)
+    });
+    let truelon = (circular && equatorial && magr > TOLERANCE).then(|| {
+        let truelon = quadrant((r[0] / magr).clamp(-1.0, 1.0).acos(), r[1] < 0.0);
+        quadrant(truelon, incl > HALFPI)
+    });
+    let m = match nu {
+        Some(nu) => newtonnu(ecc, nu).map(|(_, m)| m),
+        None => arglat.or(truelon),
+    };
+
+    Some(ClassicalElements {
+        semilatus_rectum: p,
+        semimajor_axis: a,
+        eccentricity: ecc,
+        inclination: incl,
+        raan,
+        arg_of_perigee: argp,
+        true_anomaly: nu,
+        mean_anomaly: m,
+        arg_of_latitude: arglat,
+        true_longitude: truelon,
+        longitude_of_periapsis: lonper,
+    })
+}
+
+/// Position in km and velocity in km/s of classical orbital elements, with the
+/// gravitational parameter `mu` in km^3/s^2. The inverse of `rv2coe`.
+///
+/// The state is in the frame the elements are referred to. As in `rv2coe`, circular
+/// orbits are placed by the argument of latitude or true longitude, and elliptical
+/// equatorial orbits by the longitude of periapsis; when those are `None` they are
+/// made up from the node, perigee and true anomaly, with missing angles taken as zero.
+/// Without a true anomaly it is solved for from the mean anomaly.
+pub fn coe2rv(coe: &ClassicalElements, mu: f64) -> ([f64; 3], [f64; 3]) {
+    let ecc = coe.eccentricity;
+    let incl = coe.inclination;
+    let raan = coe.raan.unwrap_or(0.0);
+    let argp = coe.arg_of_perigee.unwrap_or(0.0);
+    let nu = match (coe.true_anomaly, coe.mean_anomaly) {
+        (Some(nu), _) => nu,
+        (None, Some(m)) => newtonm(ecc, m).1,
+        (None, None) => 0.0,
+    };
+
+    let equatorial = incl < TOLERANCE || (incl - PI).abs() < TOLERANCE;
+    let (raan, argp, nu) = match (ecc < TOLERANCE, equatorial) {
+        (true, true) => (0.0, 0.0, coe.true_longitude.unwrap_or(raan + argp + nu)),
+        (true, false) => (raan, 0.0, coe.arg_of_latitude.unwrap_or(argp + nu)),
+        (false, true) => (0.0, coe.longitude_of_periapsis.unwrap_or(raan + argp), nu),
+        (false, false) => (raan, argp, nu),
+    };
+
+    // position and velocity in the perifocal frame
+    let p = coe.semilatus_rectum.max(0.0001);
+    let (sinnu, cosnu) = nu.sin_cos();
+    let temp = p / (1.0 + ecc * cosnu);
+    let rpqw = [temp * cosnu, temp * sinnu, 0.0];
+    let vpqw = [
+        -sinnu * (mu / p).sqrt(),
+        (ecc + cosnu) * (mu / p).sqrt(),
+        0.0,
+    ];
+
+    // rotate by the argument of perigee, inclination and node
+    let (sinw, cosw) = argp.sin_cos();
+    let (sini, cosi) = incl.sin_cos();
+    let (sino, coso) = raan.sin_cos();
+    let rotate = |v: [f64; 3]| {
+        let (x, y) = (cosw * v[0] - sinw * v[1], sinw * v[0] + cosw * v[1]);
+        let (y, z) = (cosi * y, sini * y);
+        [coso * x - sino * y, sino * x + coso * y, z]
+    };
+    (rotate(rpqw), rotate(vpqw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::degrees;
+
+    #[test]
+    fn test_rv2coe() {
+        // Vallado, Fundamentals of Astrodynamics and Applications, example 2-5
+        let r = [6524.834, 6862.875, 6448.296];
+        let v = [4.901327, 5.533756, -1.976341];
+        let coe = rv2coe(r, v, 398600.4418).unwrap();
+        assert!((coe.semilatus_rectum - 11067.79).abs() < 1e-2);
+        assert!((coe.semimajor_axis - 36127.34).abs() < 1e-2);
+        assert!((coe.eccentricity - 0.832853).abs() < 1e-6);
+        assert!((degrees(coe.inclination) - 87.870).abs() < 1e-3);
+        assert!((degrees(coe.raan.unwrap()) - 227.898).abs() < 1e-3);
+        assert!((degrees(coe.arg_of_perigee.unwrap()) - 53.38).abs() < 1e-2);
+        assert!((degrees(coe.true_anomaly.unwrap()) - 92.335).abs() < 1e-3);
+        assert!((degrees(coe.mean_anomaly.unwrap()) - 7.6047).abs() < 1e-3);
+        assert_eq!(coe.arg_of_latitude, None);
+
+        // circular equatorial: only the true longitude is defined
+        let coe = rv2coe(
+            [7000.0, 0.0, 0.0],
+            [0.0, (398600.4418f64 / 7000.0).sqrt(), 0.0],
+            398600.4418,
+        )
+        .unwrap();
+        assert!(coe.eccentricity < 1e-8);
+        assert_eq!(
+            (coe.raan, coe.arg_of_perigee, coe.true_anomaly),
+            (None, None, None)
+        );
+        assert_eq!(coe.true_longitude, Some(0.0));
+        assert_eq!(coe.mean_anomaly, Some(0.0));
+
+        assert_eq!(rv2coe(r, r, 398600.4418), None);
+    }
+
+    #[test]
+    fn test_coe2rv() {
+        let mu = 398600.4418;
+        let r = [6524.834, 6862.875, 6448.296];
+        let v = [4.901327, 5.533756, -1.976341];
+        let (r_coe, v_coe) = coe2rv(&rv2coe(r, v, mu).unwrap(), mu);
+        for i in 0..3 {
+            assert!((r_coe[i] - r[i]).abs() < 1e-6);
+            assert!((v_coe[i] - v[i]).abs() < 1e-9);
+        }
+
+        // circular and equatorial orbits go through the alternate angles
+        for coe in [
+            ClassicalElements::new(7000.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+            ClassicalElements::new(7000.0, 0.0, 0.9, 2.0, 0.0, 1.0),
+            ClassicalElements::new(7000.0, 0.1, 0.0, 0.0, 0.5, 1.0),
+        ] {
+            let (r, v) = coe2rv(&coe, mu);
+            let (r_round_trip, v_round_trip) = coe2rv(&rv2coe(r, v, mu).unwrap(), mu);
+            for i in 0..3 {
+                assert!((r_round_trip[i] - r[i]).abs() < 1e-6);
+                assert!((v_round_trip[i] - v[i]).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use alloc::vec::Vec;
+
+use crate::constants::*;
+use crate::sgp4::{PropagationFailure, SGP4};
+use crate::time::Epoch;
+use crate::utils::{dot, mag};
+
+/// Time step of the sieve, in minutes.
+///
+/// The relative motion of two satellites has at most one close approach per step, as
+/// the approaches of orbits that cross happen about half a revolution apart.
+const SIEVE_STEP_MIN: f64 = 1.0;
+
+/// Margin on the relative speed in the sieve, which bounds how close the satellites
+/// can come within a step from the distances and relative speeds at its ends
+const SPEED_MARGIN: f64 = 1.2;
+
+/// Resolution of the time of closest approach, in minutes (about 6 microseconds).
+const RESOLUTION_MIN: f64 = 1e-7;
+
+/// A close approach of two satellites, from `find_close_approaches`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CloseApproach {
+    /// Time of closest approach (TCA) in minutes since the epoch of the primary
+    pub t: f64,
+    /// Epoch of the TCA in UTC
+    pub tca: Epoch,
+    /// Distance between the satellites at the TCA in km
+    pub miss_distance_km: f64,
+    /// Position of the secondary relative to the primary at the TCA, in km in TEME
+    pub relative_position_km: [f64; 3],
+    /// Velocity of the secondary relative to the primary at the TCA, in km/s in TEME
+    pub relative_velocity_km_s: [f64; 3],
+}
+
+impl CloseApproach {
+    /// Relative speed at the TCA in km/s
+    pub fn relative_speed_km_s(&self) -> f64 {
+        mag(self.relative_velocity_km_s)
+    }
+}
+
+/// Find the close approaches of two satellites closer than `threshold_km` between
+/// `start_min` and `stop_min` minutes since the epoch of the primary.
+///
+/// The relative motion is sampled at a coarse step, and steps that can't hold an
+/// approach within the threshold are sieved out using the distances and relative
+/// speeds at their ends. In the others, the minimum of the distance is found by
+/// bisection on the range rate. Approaches at `start_min` or `stop_min` that are still
+/// closing or already opening are not reported, as their TCA is outside the window.
+///
+/// This is a screening tool: the errors of SGP4 states grow to kilometers within days
+/// of epoch, so close approaches are worth a closer look, not conclusions.
+pub fn find_close_approaches(
+    primary: &SGP4,
+    secondary: &SGP4,
+    start_min: f64,
+    stop_min: f64,
+    threshold_km: f64,
+) -> Result<Vec<CloseApproach>, PropagationFailure> {
+    let offset_min = primary.epoch().minutes_since(secondary.epoch());
+    let relative = |t: f64| -> Result<([f64; 3], [f64; 3]), PropagationFailure> {
+        let a = primary.propagate(t)?;
+        let b = secondary.propagate(t + offset_min)?;
+        Ok((
+            [0, 1, 2].map(|i| b.position_km[i] - a.position_km[i]),
+            [0, 1, 2].map(|i| b.velocity_km_s[i] - a.velocity_km_s[i]),
+        ))
+    };
+
+    let mut approaches = Vec::new();
+    let mut t0 = start_min;
+    let (mut r0, mut v0) = relative(t0)?;
+    while t0 < stop_min {
+        let t1 = (t0 + SIEVE_STEP_MIN).min(stop_min);
+        let (r1, v1) = relative(t1)?;
+        // the distance has a minimum where the range rate goes from closing to opening
+        let minimum = dot(r0, v0) < 0.0 && dot(r1, v1) >= 0.0;
+        let reach_km = SPEED_MARGIN * mag(v0).max(mag(v1)) * (t1 - t0) * 60.0;
+        if minimum && 0.5 * (mag(r0) + mag(r1) - reach_km) <= threshold_km {
+            let (mut a, mut b) = (t0, t1);
+            while b - a > RESOLUTION_MIN {
+                let mid = 0.5 * (a + b);
+                let (r, v) = relative(mid)?;
+                if dot(r, v) < 0.0 {
+                    a = mid;
+                } else {
+                    b = mid;
+                }
+            }
+            let t = 0.5 * (a + b);
+            let (r, v) = relative(t)?;
+            if mag(r) <= threshold_km {
+                approaches.push(CloseApproach {
+                    t,
+                    tca: primary.epoch() + t / DAY2MIN,
+                    miss_distance_km: mag(r),
+                    relative_position_km: r,
+                    relative_velocity_km_s: v,
+                });
+            }
+        }
+        (t0, r0, v0) = (t1, r1, v1);
+    }
+    Ok(approaches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ISS_LINE1, ISS_LINE2};
+    use crate::sgp4::OperationMode;
+    use crate::tle::{ChecksumMode, TLE};
+    use crate::utils::WGSModel;
+
+    fn sgp4(line1: &str, line2: &str) -> SGP4 {
+        let tle = TLE::parse(line1.into(), line2.into(), ChecksumMode::Ignore);
+        SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle.unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_find_close_approaches() {
+        let primary = sgp4(ISS_LINE1, ISS_LINE2);
+        // an orbit with a slightly different plane, crossing the primary's twice a
+        // revolution, with an epoch 1.44 minutes later
+        let secondary = sgp4(
+            &ISS_LINE1.replace("24001.50000000", "24001.50100000"),
+            "2 25544  51.6416 247.9627 0006703 130.5360 330.8091 15.50074720432548",
+        );
+
+        let approaches = find_close_approaches(&primary, &secondary, 0.0, 360.0, 100.0).unwrap();
+        assert!(approaches.len() >= 4, "{approaches:?}");
+        for approach in &approaches {
+            assert!(approach.miss_distance_km < 100.0);
+            // LEO orbits with close planes pass each other slowly
+            assert!(approach.relative_speed_km_s() < 1.0);
+            assert!(
+                dot(
+                    approach.relative_position_km,
+                    approach.relative_velocity_km_s
+                )
+                .abs()
+                    < 1e-3
+            );
+
+            // nothing closer nearby, a second either side
+            let offset = primary.epoch().minutes_since(secondary.epoch());
+            for dt in [-1.0 / 60.0, 1.0 / 60.0] {
+                let t = approach.t + dt;
+                let a = primary.propagate(t).unwrap().position_km;
+                let b = secondary.propagate(t + offset).unwrap().position_km;
+                let distance = mag([0, 1, 2].map(|i| b[i] - a[i]));
+                assert!(distance > approach.miss_distance_km);
+            }
+        }
+
+        // compare with a brute force scan every 5 seconds
+        let offset = primary.epoch().minutes_since(secondary.epoch());
+        let distance = |t: f64| {
+            let a = primary.propagate(t).unwrap().position_km;
+            let b = secondary.propagate(t + offset).unwrap().position_km;
+            mag([0, 1, 2].map(|i| b[i] - a[i]))
+        };
+        let samples: Vec<f64> = (0..=360 * 12).map(|i| distance(i as f64 / 12.0)).collect();
+        let minima = samples
+            .windows(3)
+            .filter(|w| w[1] < w[0] && w[1] <= w[2] && w[1] < 100.0)
+            .count();
+        assert_eq!(approaches.len(), minima);
+
+        assert!(
+            find_close_approaches(&primary, &secondary, 0.0, 360.0, 1.0)
+                .unwrap()
+                .len()
+                < approaches.len()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::f64::consts::PI;
+use core::f64::consts::PI;
 
 // --- mathematical operations ---
 pub const SMALL: f64 = 1e-10;
@@ -34,6 +34,11 @@ pub const HR2RAD: f64 = DEG2HR * 1.0 * PI / 180.0;
 
 // --- astrodynamics operations ---
 
+/// astronomical unit in km
+pub const AU: f64 = 149597870.7;
+/// speed of light in km/s
+pub const SPEED_OF_LIGHT: f64 = 299792.458;
+
 // time
 /// Julian date of the epoch J2000.0 (noon)
 pub const J2000: f64 = 2451545.0;
@@ -41,6 +46,14 @@ pub const J2000: f64 = 2451545.0;
 pub const J2000_UTC: f64 = 2451544.5;
 /// offset between Julian dates and Modified Julian dates
 pub const JD_TO_MJD_OFFSET: f64 = 2400000.5;
+/// Julian date of the Unix epoch, 1970 Jan 1 0 hr
+pub const JD_UNIX_EPOCH: f64 = 2440587.5;
+/// Julian date of 1950 Jan 0.0, the reference epoch used by sgp4init
+pub const JD_EPOCH_1950: f64 = 2433281.5;
+
+// WGS-84 ellipsoid
+pub const WGS84_RE: f64 = 6378.137;
+pub const WGS84_FLAT: f64 = 1.0 / 298.257223563;
 
 // EGM-08 (Earth) constants used here
 pub const RE: f64 = 6378.1363;
@@ -0,0 +1,182 @@
+//! CZML documents of ephemerides, for 3D visualization in CesiumJS.
+//!
+//! Positions are written in the earth fixed frame (Cesium's `FIXED`), as Cesium's
+//! inertial frame is ICRF rather than TEME, with the Lagrange interpolation Cesium
+//! needs to draw smooth orbits between the states.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde_json::{Value, json};
+
+use crate::constants::*;
+use crate::sgp4::SGP4;
+use crate::state::State;
+
+/// Options of the packets written by `czml_packet` and `czml_document`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CzmlOptions {
+    /// Degree of the Lagrange interpolation of the positions
+    pub interpolation_degree: u32,
+    /// Label the satellites with their names, or satellite numbers if unnamed
+    pub label: bool,
+}
+
+impl Default for CzmlOptions {
+    /// Degree 5 interpolation, enough for states a few minutes apart, with labels.
+    fn default() -> Self {
+        CzmlOptions {
+            interpolation_degree: 5,
+            label: true,
+        }
+    }
+}
+
+/// The CZML packet of the ephemeris of a satellite: a point at its positions over the
+/// interval they cover, with the interpolation metadata and an optional label. The
+/// packet id is the satellite number.
+///
+/// Returns `None` if there are no states. The states must be sorted by epoch.
+pub fn czml_packet(sgp4: &SGP4, states: &[State], options: &CzmlOptions) -> Option<Value> {
+    let (first, last) = (states.first()?, states.last()?);
+    let cartesian: Vec<f64> = states
+        .iter()
+        .flat_map(|state| {
+            let ecef = state.to_ecef();
+            let t = (state.epoch - first.epoch) * DAY2SEC;
+            [t].into_iter().chain(ecef.position_km.map(|x| x * KM2M))
+        })
+        .collect();
+    let name = sgp4
+        .name()
+        .map_or_else(|| sgp4.satnum().to_string(), ToString::to_string);
+
+    let mut packet = json!({
+        "id": sgp4.satnum().to_string(),
+        "name": name,
+        "availability": interval(first, last),
+        "point": { "pixelSize": 5 },
+        "position": {
+            "epoch": first.epoch.to_iso8601(),
+            "interpolationAlgorithm": "LAGRANGE",
+            "interpolationDegree": options.interpolation_degree,
+            "referenceFrame": "FIXED",
+            "cartesian": cartesian,
+        },
+    });
+    if options.label {
+        packet["label"] = json!({
+            "text": name,
+            "horizontalOrigin": "LEFT",
+            "pixelOffset": { "cartesian2": [8, 0] },
+        });
+    }
+    Some(packet)
+}
+
+/// A CZML document of the ephemerides of satellites: the document packet, with a
+/// clock over the interval of all the states, followed by the packet of each satellite
+/// with states (see `czml_packet`).
+///
+/// Serialize it with `serde_json::to_string` to load it in Cesium.
+pub fn czml_document(
+    name: &str,
+    ephemerides: &[(&SGP4, &[State])],
+    options: &CzmlOptions,
+) -> Value {
+    let mut document = json!({ "id": "document", "name": name, "version": "1.0" });
+    let bounds = ephemerides
+        .iter()
+        .filter_map(|(_, states)| Some((states.first()?, states.last()?)))
+        .reduce(|(first, last), (start, stop)| {
+            (
+                if start.epoch < first.epoch {
+                    start
+                } else {
+                    first
+                },
+                if stop.epoch > last.epoch { stop } else { last },
+            )
+        });
+    if let Some((first, last)) = bounds {
+        document["clock"] = json!({
+            "interval": interval(first, last),
+            "currentTime": first.epoch.to_iso8601(),
+            "multiplier": 60,
+            "range": "LOOP_STOP",
+            "step": "SYSTEM_CLOCK_MULTIPLIER",
+        });
+    }
+    let packets = ephemerides
+        .iter()
+        .filter_map(|(sgp4, states)| czml_packet(sgp4, states, options));
+    Value::Array([document].into_iter().chain(packets).collect())
+}
+
+/// ISO 8601 interval from the epoch of one state to that of another.
+fn interval(first: &State, last: &State) -> String {
+    let mut interval = first.epoch.to_iso8601();
+    interval.push('/');
+    interval.push_str(&last.epoch.to_iso8601());
+    interval
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::utils::{WGSModel, mag};
+
+    #[test]
+    fn test_czml_document() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let states: Vec<State> = sgp4
+            .iter(0.0, 90.0, 1.0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        let document = czml_document(
+            "ISS",
+            &[(&sgp4, &states), (&sgp4, &[])],
+            &CzmlOptions::default(),
+        );
+        let packets = document.as_array().unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0]["id"], "document");
+        assert_eq!(
+            packets[0]["clock"]["interval"],
+            "2024-01-01T12:00:00.000Z/2024-01-01T13:30:00.000Z"
+        );
+
+        let packet = &packets[1];
+        assert_eq!(packet["id"], "25544");
+        assert_eq!(packet["label"]["text"], "25544");
+        assert_eq!(packet["availability"], packets[0]["clock"]["interval"]);
+        let position = &packet["position"];
+        assert_eq!(position["interpolationDegree"], 5);
+        assert_eq!(position["referenceFrame"], "FIXED");
+        let cartesian: Vec<f64> = position["cartesian"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x.as_f64().unwrap())
+            .collect();
+        assert_eq!(cartesian.len(), 4 * 91);
+        assert_eq!((cartesian[0], cartesian[4 * 90]), (0.0, 5400.0));
+        let r = [cartesian[1], cartesian[2], cartesian[3]];
+        assert!((mag(r) - states[0].norm() * KM2M).abs() < 1e-3);
+
+        let unlabeled = CzmlOptions {
+            label: false,
+            ..CzmlOptions::default()
+        };
+        assert!(
+            czml_packet(&sgp4, &states, &unlabeled)
+                .unwrap()
+                .get("label")
+                .is_none()
+        );
+        assert_eq!(czml_packet(&sgp4, &[], &unlabeled), None);
+    }
+}
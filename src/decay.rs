@@ -0,0 +1,125 @@
+use crate::coe::rv2coe;
+use crate::constants::*;
+use crate::sgp4::{PropagationError, SGP4};
+use crate::time::Epoch;
+use crate::utils::get_grav_c;
+
+/// Time step of the coarse scan for the decay, in minutes.
+///
+/// A step is a small part of a low orbit, so a perigee below the threshold is caught
+/// within a revolution of it happening.
+const SCAN_STEP_MIN: f64 = 10.0;
+
+/// Resolution of the decay time, in minutes. The estimate is much less certain than
+/// this, as drag is modelled crudely.
+const RESOLUTION_MIN: f64 = 1e-3;
+
+/// An estimated reentry of a satellite, from `estimate_decay`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decay {
+    /// Time of the decay in minutes since the epoch of the elements
+    pub t: f64,
+    /// Epoch of the decay in UTC
+    pub epoch: Epoch,
+    /// The propagation error that ended the orbit, or `None` if the perigee altitude
+    /// went below the threshold first
+    pub error: Option<PropagationError>,
+}
+
+/// Estimate when a satellite reenters, between `start_min` and `stop_min` minutes
+/// since epoch.
+///
+/// The orbit is scanned at a coarse step until propagation fails, usually with
+/// `PropagationError::OrbitalDecay` as the satellite goes below the surface, or the
+/// perigee altitude of the osculating elements drops below `perigee_altitude_km` if
+/// given. The time is then refined by bisection. Returns `None` if the satellite is
+/// still in orbit at `stop_min`.
+///
+/// Other propagation errors, e.g. the eccentricity running out of range as the mean
+/// motion grows, are taken as the decay too: the elements are no longer usable.
+pub fn estimate_decay(
+    sgp4: &SGP4,
+    perigee_altitude_km: Option<f64>,
+    start_min: f64,
+    stop_min: f64,
+) -> Option<Decay> {
+    let grav_const = get_grav_c(&sgp4.model());
+    let check = |t: f64| -> Result<(), Option<PropagationError>> {
+        let state = sgp4.propagate(t).map_err(|failure| Some(failure.error))?;
+        let Some(threshold) = perigee_altitude_km else {
+            return Ok(());
+        };
+        let perigee_altitude = rv2coe(state.position_km, state.velocity_km_s, grav_const.mu)
+            .map_or(0.0, |coe| {
+                coe.semilatus_rectum / (1.0 + coe.eccentricity) - grav_const.radiusearthkm
+            });
+        if perigee_altitude < threshold {
+            Err(None)
+        } else {
+            Ok(())
+        }
+    };
+
+    if let Err(error) = check(start_min) {
+        return Some(decay(sgp4, start_min, error));
+    }
+    let mut t = start_min;
+    while t < stop_min {
+        let t_next = (t + SCAN_STEP_MIN).min(stop_min);
+        if check(t_next).is_err() {
+            // bisect to the first time that fails
+            let (mut t0, mut t1) = (t, t_next);
+            while t1 - t0 > RESOLUTION_MIN {
+                let mid = 0.5 * (t0 + t1);
+                if check(mid).is_ok() {
+                    t0 = mid;
+                } else {
+                    t1 = mid;
+                }
+            }
+            let error = check(t1).err().flatten();
+            return Some(decay(sgp4, t1, error));
+        }
+        t = t_next;
+    }
+    None
+}
+
+fn decay(sgp4: &SGP4, t: f64, error: Option<PropagationError>) -> Decay {
+    Decay {
+        t,
+        epoch: sgp4.epoch() + t / DAY2MIN,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgp4::OperationMode;
+    use crate::tle::TLE;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_estimate_decay() {
+        // decays during the verification run, after the state at 50 minutes
+        let tle = TLE::new(
+            "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534".into(),
+            "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle).unwrap();
+
+        let decay = estimate_decay(&sgp4, None, 0.0, 1440.0).unwrap();
+        assert!(decay.t > 50.0 && decay.t < 60.0, "{decay:?}");
+        assert_eq!(decay.error, Some(PropagationError::OrbitalDecay));
+        assert!(sgp4.propagate(decay.t - 2.0 * RESOLUTION_MIN).is_ok());
+        assert!(((decay.epoch - sgp4.epoch()) * DAY2MIN - decay.t).abs() < 1e-6);
+
+        // the perigee is already below 150 km at epoch
+        let early = estimate_decay(&sgp4, Some(150.0), 0.0, 1440.0).unwrap();
+        assert_eq!((early.t, early.error), (0.0, None));
+
+        assert_eq!(estimate_decay(&sgp4, None, 0.0, 40.0), None);
+    }
+}
@@ -0,0 +1,822 @@
+use core::f64::consts::PI;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::sgp4::{
+    LunarSolarPeriodics, OperationMode, PeriodicTerms, PropagationError, Resonance,
+    ResonanceOptions,
+};
+
+// lunar-solar constants
+const ZNS: f64 = 1.19459e-5;
+const ZES: f64 = 0.01675;
+const ZNL: f64 = 1.5835218e-4;
+const ZEL: f64 = 0.05490;
+
+/// Earth rotation rate in rad/min (7.29211514668855e-5 rad/s)
+const RPTIM: f64 = 4.375_269_088_011_3e-3;
+
+/// Resonance integration step of the reference implementation, in minutes
+pub(crate) const RESONANCE_STEP_MIN: f64 = 720.0;
+
+/// Mean motion bands of the resonance regimes in rad/min, 0.8 to 1.2 and 1.893 to
+/// 2.118 revolutions a day
+const SYNCHRONOUS_BAND: (f64, f64) = (0.0034906585, 0.0052359877);
+const HALF_DAY_BAND: (f64, f64) = (8.26e-3, 9.24e-3);
+
+/// Intermediate quantities from `dscom` that are only needed while initializing.
+#[derive(Default)]
+struct DsCom {
+    sinim: f64,
+    cosim: f64,
+    emsq: f64,
+    s1: f64,
+    s2: f64,
+    s3: f64,
+    s4: f64,
+    s5: f64,
+    s6: f64,
+    s7: f64,
+    ss1: f64,
+    ss2: f64,
+    ss3: f64,
+    ss4: f64,
+    ss5: f64,
+    ss6: f64,
+    ss7: f64,
+    z1: f64,
+    z2: f64,
+    z3: f64,
+    z11: f64,
+    z12: f64,
+    z13: f64,
+    z21: f64,
+    z22: f64,
+    z23: f64,
+    z31: f64,
+    z32: f64,
+    z33: f64,
+    sz1: f64,
+    sz2: f64,
+    sz3: f64,
+    sz11: f64,
+    sz12: f64,
+    sz13: f64,
+    sz21: f64,
+    sz22: f64,
+    sz23: f64,
+    sz31: f64,
+    sz32: f64,
+    sz33: f64,
+}
+
+/// Mean elements at epoch and their secular rates, as computed by sgp4init.
+pub(crate) struct DsInitInput {
+    pub epoch: f64,
+    pub ecco: f64,
+    pub eccsq: f64,
+    pub inclo: f64,
+    pub nodeo: f64,
+    pub argpo: f64,
+    pub mo: f64,
+    pub no: f64,
+    pub mdot: f64,
+    pub nodedot: f64,
+    pub xpidot: f64,
+    pub gsto: f64,
+    pub xke: f64,
+    pub resonance: ResonanceOptions,
+}
+
+/// Lunar-solar and resonance terms used by deep space (SDP4) propagation.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DeepSpace {
+    // lunar-solar periodics
+    e3: f64,
+    ee2: f64,
+    peo: f64,
+    pgho: f64,
+    pho: f64,
+    pinco: f64,
+    plo: f64,
+    se2: f64,
+    se3: f64,
+    sgh2: f64,
+    sgh3: f64,
+    sgh4: f64,
+    sh2: f64,
+    sh3: f64,
+    si2: f64,
+    si3: f64,
+    sl2: f64,
+    sl3: f64,
+    sl4: f64,
+    xgh2: f64,
+    xgh3: f64,
+    xgh4: f64,
+    xh2: f64,
+    xh3: f64,
+    xi2: f64,
+    xi3: f64,
+    xl2: f64,
+    xl3: f64,
+    xl4: f64,
+    zmol: f64,
+    zmos: f64,
+
+    // lunar-solar secular rates
+    dedt: f64,
+    didt: f64,
+    dmdt: f64,
+    dnodt: f64,
+    domdt: f64,
+
+    // resonance terms
+    irez: Resonance,
+    resonance: ResonanceOptions,
+    d2201: f64,
+    d2211: f64,
+    d3210: f64,
+    d3222: f64,
+    d4410: f64,
+    d4422: f64,
+    d5220: f64,
+    d5232: f64,
+    d5421: f64,
+    d5433: f64,
+    del1: f64,
+    del2: f64,
+    del3: f64,
+    xfact: f64,
+    xlamo: f64,
+}
+
+impl DeepSpace {
+    /// Initialize the deep space terms (dscom followed by dsinit).
+    pub(crate) fn new(input: &DsInitInput) -> DeepSpace {
+        let mut ds = DeepSpace::default();
+        let dscom = ds.dscom(
+            input.epoch,
+            input.ecco,
+            input.argpo,
+            0.0,
+            input.inclo,
+            input.nodeo,
+            input.no,
+        );
+        // the periodics are not applied at epoch, so dpper is a no-op here
+        ds.dsinit(input, &dscom);
+        ds
+    }
+
+    /// Deep space common terms, shared between initialization and the periodics.
+    #[allow(clippy::too_many_arguments)]
+    fn dscom(
+        &mut self,
+        epoch: f64,
+        ep: f64,
+        argpp: f64,
+        tc: f64,
+        inclp: f64,
+        nodep: f64,
+        np: f64,
+    ) -> DsCom {
+        let c1ss = 2.9864797e-6;
+        let c1l = 4.7968065e-7;
+        let zsinis = 0.39785416;
+        let zcosis = 0.91744867;
+        let zcosgs = 0.1945905;
+        let zsings = -0.98088458;
+
+        let nm = np;
+        let em = ep;
+        let snodm = nodep.sin();
+        let cnodm = nodep.cos();
+        let sinomm = argpp.sin();
+        let cosomm = argpp.cos();
+        let sinim = inclp.sin();
+        let cosim = inclp.cos();
+        let emsq = em * em;
+        let betasq = 1.0 - emsq;
+        let rtemsq = betasq.sqrt();
+
+        // initialize lunar solar terms
+        self.peo = 0.0;
+        self.pinco = 0.0;
+        self.plo = 0.0;
+        self.pgho = 0.0;
+        self.pho = 0.0;
+        let day = epoch + 18261.5 + tc / DAY2MIN;
+        let xnodce = (4.5236020 - 9.2422029e-4 * day) % TWOPI;
+        let stem = xnodce.sin();
+        let ctem = xnodce.cos();
+        let zcosil = 0.91375164 - 0.03568096 * ctem;
+        let zsinil = (1.0 - zcosil * zcosil).sqrt();
+        let zsinhl = 0.089683511 * stem / zsinil;
+        let zcoshl = (1.0 - zsinhl * zsinhl).sqrt();
+        let gam = 5.8351514 + 0.0019443680 * day;
+        let mut zx = 0.39785416 * stem / zsinil;
+        let zy = zcoshl * ctem + 0.91744867 * zsinhl * stem;
+        zx = zx.atan2(zy);
+        zx = gam + zx - xnodce;
+        let zcosgl = zx.cos();
+        let zsingl = zx.sin();
+
+        // do solar terms first, then lunar terms
+        let mut zcosg = zcosgs;
+        let mut zsing = zsings;
+        let mut zcosi = zcosis;
+        let mut zsini = zsinis;
+        let mut zcosh = cnodm;
+        let mut zsinh = snodm;
+        let mut cc = c1ss;
+        let xnoi = 1.0 / nm;
+
+        let mut out = DsCom {
+            sinim,
+            cosim,
+            emsq,
+            ..DsCom::default()
+        };
+        for lsflg in 1..=2 {
+            let a1 = zcosg * zcosh + zsing * zcosi * zsinh;
+            let a3 = -zsing * zcosh + zcosg * zcosi * zsinh;
+            let a7 = -zcosg * zsinh + zsing * zcosi * zcosh;
+            let a8 = zsing * zsini;
+            let a9 = zsing * zsinh + zcosg * zcosi * zcosh;
+            let a10 = zcosg * zsini;
+            let a2 = cosim * a7 + sinim * a8;
+            let a4 = cosim * a9 + sinim * a10;
+            let a5 = -sinim * a7 + cosim * a8;
+            let a6 = -sinim * a9 + cosim * a10;
+
+            let x1 = a1 * cosomm + a2 * sinomm;
+            let x2 = a3 * cosomm + a4 * sinomm;
+            let x3 = -a1 * sinomm + a2 * cosomm;
+            let x4 = -a3 * sinomm + a4 * cosomm;
+            let x5 = a5 * sinomm;
+            let x6 = a6 * sinomm;
+            let x7 = a5 * cosomm;
+            let x8 = a6 * cosomm;
+
+            let z31 = 12.0 * x1 * x1 - 3.0 * x3 * x3;
+            let z32 = 24.0 * x1 * x2 - 6.0 * x3 * x4;
+            let z33 = 12.0 * x2 * x2 - 3.0 * x4 * x4;
+            let mut z1 = 3.0 * (a1 * a1 + a2 * a2) + z31 * emsq;
+            let mut z2 = 6.0 * (a1 * a3 + a2 * a4) + z32 * emsq;
+            let mut z3 = 3.0 * (a3 * a3 + a4 * a4) + z33 * emsq;
+            let z11 = -6.0 * a1 * a5 + emsq * (-24.0 * x1 * x7 - 6.0 * x3 * x5);
+            let z12 = -6.0 * (a1 * a6 + a3 * a5)
+                + emsq * (-24.0 * (x2 * x7 + x1 * x8) - 6.0 * (x3 * x6 + x4 * x5));
+            let z13 = -6.0 * a3 * a6 + emsq * (-24.0 * x2 * x8 - 6.0 * x4 * x6);
+            let z21 = 6.0 * a2 * a5 + emsq * (24.0 * x1 * x5 - 6.0 * x3 * x7);
+            let z22 = 6.0 * (a4 * a5 + a2 * a6)
+                + emsq * (24.0 * (x2 * x5 + x1 * x6) - 6.0 * (x4 * x7 + x3 * x8));
+            let z23 = 6.0 * a4 * a6 + emsq * (24.0 * x2 * x6 - 6.0 * x4 * x8);
+            z1 = z1 + z1 + betasq * z31;
+            z2 = z2 + z2 + betasq * z32;
+            z3 = z3 + z3 + betasq * z33;
+            let s3 = cc * xnoi;
+            let s2 = -0.5 * s3 / rtemsq;
+            let s4 = s3 * rtemsq;
+            let s1 = -15.0 * em * s4;
+            let s5 = x1 * x3 + x2 * x4;
+            let s6 = x2 * x3 + x1 * x4;
+            let s7 = x2 * x4 - x1 * x3;
+
+            if lsflg == 1 {
+                // keep the solar terms and set up the lunar pass
+                out.ss1 = s1;
+                out.ss2 = s2;
+                out.ss3 = s3;
+                out.ss4 = s4;
+                out.ss5 = s5;
+                out.ss6 = s6;
+                out.ss7 = s7;
+                out.sz1 = z1;
+                out.sz2 = z2;
+                out.sz3 = z3;
+                out.sz11 = z11;
+                out.sz12 = z12;
+                out.sz13 = z13;
+                out.sz21 = z21;
+                out.sz22 = z22;
+                out.sz23 = z23;
+                out.sz31 = z31;
+                out.sz32 = z32;
+                out.sz33 = z33;
+                zcosg = zcosgl;
+                zsing = zsingl;
+                zcosi = zcosil;
+                zsini = zsinil;
+                zcosh = zcoshl * cnodm + zsinhl * snodm;
+                zsinh = snodm * zcoshl - cnodm * zsinhl;
+                cc = c1l;
+            } else {
+                out.s1 = s1;
+                out.s2 = s2;
+                out.s3 = s3;
+                out.s4 = s4;
+                out.s5 = s5;
+                out.s6 = s6;
+                out.s7 = s7;
+                out.z1 = z1;
+                out.z2 = z2;
+                out.z3 = z3;
+                out.z11 = z11;
+                out.z12 = z12;
+                out.z13 = z13;
+                out.z21 = z21;
+                out.z22 = z22;
+                out.z23 = z23;
+                out.z31 = z31;
+                out.z32 = z32;
+                out.z33 = z33;
+            }
+        }
+
+        self.zmol = (4.7199672 + 0.22997150 * day - gam) % TWOPI;
+        self.zmos = (6.2565837 + 0.017201977 * day) % TWOPI;
+
+        // solar terms
+        self.se2 = 2.0 * out.ss1 * out.ss6;
+        self.se3 = 2.0 * out.ss1 * out.ss7;
+        self.si2 = 2.0 * out.ss2 * out.sz12;
+        self.si3 = 2.0 * out.ss2 * (out.sz13 - out.sz11);
+        self.sl2 = -2.0 * out.ss3 * out.sz2;
+        self.sl3 = -2.0 * out.ss3 * (out.sz3 - out.sz1);
+        self.sl4 = -2.0 * out.ss3 * (-21.0 - 9.0 * emsq) * ZES;
+        self.sgh2 = 2.0 * out.ss4 * out.sz32;
+        self.sgh3 = 2.0 * out.ss4 * (out.sz33 - out.sz31);
+        self.sgh4 = -18.0 * out.ss4 * ZES;
+        self.sh2 = -2.0 * out.ss2 * out.sz22;
+        self.sh3 = -2.0 * out.ss2 * (out.sz23 - out.sz21);
+
+        // lunar terms
+        self.ee2 = 2.0 * out.s1 * out.s6;
+        self.e3 = 2.0 * out.s1 * out.s7;
+        self.xi2 = 2.0 * out.s2 * out.z12;
+        self.xi3 = 2.0 * out.s2 * (out.z13 - out.z11);
+        self.xl2 = -2.0 * out.s3 * out.z2;
+        self.xl3 = -2.0 * out.s3 * (out.z3 - out.z1);
+        self.xl4 = -2.0 * out.s3 * (-21.0 - 9.0 * emsq) * ZEL;
+        self.xgh2 = 2.0 * out.s4 * out.z32;
+        self.xgh3 = 2.0 * out.s4 * (out.z33 - out.z31);
+        self.xgh4 = -18.0 * out.s4 * ZEL;
+        self.xh2 = -2.0 * out.s2 * out.z22;
+        self.xh3 = -2.0 * out.s2 * (out.z23 - out.z21);
+
+        out
+    }
+
+    /// Deep space initialization: lunar-solar secular rates and resonance terms.
+    fn dsinit(&mut self, input: &DsInitInput, dscom: &DsCom) {
+        let q22 = 1.7891679e-6;
+        let q31 = 2.1460748e-6;
+        let q33 = 2.2123015e-7;
+        let root22 = 1.7891679e-6;
+        let root44 = 7.3636953e-9;
+        let root54 = 2.1765803e-9;
+        let root32 = 3.7393792e-7;
+        let root52 = 1.1428639e-7;
+
+        let DsCom {
+            sinim,
+            cosim,
+            emsq,
+            s1,
+            s2,
+            s3,
+            s4,
+            s5,
+            ss1,
+            ss2,
+            ss3,
+            ss4,
+            ss5,
+            sz1,
+            sz3,
+            sz11,
+            sz13,
+            sz21,
+            sz23,
+            sz31,
+            sz33,
+            z1,
+            z3,
+            z11,
+            z13,
+            z21,
+            z23,
+            z31,
+            z33,
+            ..
+        } = *dscom;
+        let nm = input.no;
+        let em = input.ecco;
+        let inclm = input.inclo;
+
+        self.resonance = input.resonance;
+        self.irez = if SYNCHRONOUS_BAND.0 < nm && nm < SYNCHRONOUS_BAND.1 {
+            Resonance::Synchronous
+        } else if (HALF_DAY_BAND.0..=HALF_DAY_BAND.1).contains(&nm) && em >= 0.5 {
+            Resonance::HalfDay
+        } else {
+            Resonance::None
+        };
+        if (self.irez == Resonance::Synchronous && !input.resonance.synchronous)
+            || (self.irez == Resonance::HalfDay && !input.resonance.half_day)
+        {
+            self.irez = Resonance::None;
+        }
+
+        // solar terms
+        let ses = ss1 * ZNS * ss5;
+        let sis = ss2 * ZNS * (sz11 + sz13);
+        let sls = -ZNS * ss3 * (sz1 + sz3 - 14.0 - 6.0 * emsq);
+        let sghs = ss4 * ZNS * (sz31 + sz33 - 6.0);
+        let mut shs = -ZNS * ss2 * (sz21 + sz23);
+        // avoid the singularity for 0 and 180 deg inclinations
+        if !(5.2359877e-2..=PI - 5.2359877e-2).contains(&inclm) {
+            shs = 0.0;
+        }
+        if sinim != 0.0 {
+            shs /= sinim;
+        }
+        let sgs = sghs - cosim * shs;
+
+        // lunar terms
+        self.dedt = ses + s1 * ZNL * s5;
+        self.didt = sis + s2 * ZNL * (z11 + z13);
+        self.dmdt = sls - ZNL * s3 * (z1 + z3 - 14.0 - 6.0 * emsq);
+        let sghl = s4 * ZNL * (z31 + z33 - 6.0);
+        let mut shll = -ZNL * s2 * (z21 + z23);
+        if !(5.2359877e-2..=PI - 5.2359877e-2).contains(&inclm) {
+            shll = 0.0;
+        }
+        self.domdt = sgs + sghl;
+        self.dnodt = shs;
+        if sinim != 0.0 {
+            self.domdt -= cosim / sinim * shll;
+            self.dnodt += shll / sinim;
+        }
+
+        // deep space resonance effects
+        let theta = input.gsto % TWOPI;
+        if self.irez == Resonance::None {
+            return;
+        }
+        let aonv = (nm / input.xke).powf(2.0 / 3.0);
+
+        if self.irez == Resonance::HalfDay {
+            // geopotential resonance for 12 hour orbits
+            let cosisq = cosim * cosim;
+            let em = input.ecco;
+            let emsq = input.eccsq;
+            let eoc = em * emsq;
+            let g201 = -0.306 - (em - 0.64) * 0.440;
+
+            let (g211, g310, g322, g410, g422, g520);
+            if em <= 0.65 {
+                g211 = 3.616 - 13.2470 * em + 16.2900 * emsq;
+                g310 = -19.302 + 117.3900 * em - 228.4190 * emsq + 156.5910 * eoc;
+                g322 = -18.9068 + 109.7927 * em - 214.6334 * emsq + 146.5816 * eoc;
+                g410 = -41.122 + 242.6940 * em - 471.0940 * emsq + 313.9530 * eoc;
+                g422 = -146.407 + 841.8800 * em - 1629.014 * emsq + 1083.4350 * eoc;
+                g520 = -532.114 + 3017.977 * em - 5740.032 * emsq + 3708.2760 * eoc;
+            } else {
+                g211 = -72.099 + 331.819 * em - 508.738 * emsq + 266.724 * eoc;
+                g310 = -346.844 + 1582.851 * em - 2415.925 * emsq + 1246.113 * eoc;
+                g322 = -342.585 + 1554.908 * em - 2366.899 * emsq + 1215.972 * eoc;
+                g410 = -1052.797 + 4758.686 * em - 7193.992 * emsq + 3651.957 * eoc;
+                g422 = -3581.690 + 16178.110 * em - 24462.770 * emsq + 12422.520 * eoc;
+                g520 = if em > 0.715 {
+                    -5149.66 + 29936.92 * em - 54087.36 * emsq + 31324.56 * eoc
+                } else {
+                    1464.74 - 4664.75 * em + 3763.64 * emsq
+                };
+            }
+
+            let (g533, g521, g532);
+            if em < 0.7 {
+                g533 = -919.22770 + 4988.6100 * em - 9064.7700 * emsq + 5542.21 * eoc;
+                g521 = -822.71072 + 4568.6173 * em - 8491.4146 * emsq + 5337.524 * eoc;
+                g532 = -853.66600 + 4690.2500 * em - 8624.7700 * emsq + 5341.4 * eoc;
+            } else {
+                g533 = -37995.780 + 161616.52 * em - 229838.20 * emsq + 109377.94 * eoc;
+                g521 = -51752.104 + 218913.95 * em - 309468.16 * emsq + 146349.42 * eoc;
+                g532 = -40023.880 + 170470.89 * em - 242699.48 * emsq + 115605.82 * eoc;
+            }
+
+            let sini2 = sinim * sinim;
+            let f220 = 0.75 * (1.0 + 2.0 * cosim + cosisq);
+            let f221 = 1.5 * sini2;
+            let f321 = 1.875 * sinim * (1.0 - 2.0 * cosim - 3.0 * cosisq);
+            let f322 = -1.875 * sinim * (1.0 + 2.0 * cosim - 3.0 * cosisq);
+            let f441 = 35.0 * sini2 * f220;
+            let f442 = 39.3750 * sini2 * sini2;
+            let f522 = 9.84375
+                * sinim
+                * (sini2 * (1.0 - 2.0 * cosim - 5.0 * cosisq)
+                    + 0.33333333 * (-2.0 + 4.0 * cosim + 6.0 * cosisq));
+            let f523 = sinim
+                * (4.92187512 * sini2 * (-2.0 - 4.0 * cosim + 10.0 * cosisq)
+                    + 6.56250012 * (1.0 + 2.0 * cosim - 3.0 * cosisq));
+            let f542 = 29.53125
+                * sinim
+                * (2.0 - 8.0 * cosim + cosisq * (-12.0 + 8.0 * cosim + 10.0 * cosisq));
+            let f543 = 29.53125
+                * sinim
+                * (-2.0 - 8.0 * cosim + cosisq * (12.0 + 8.0 * cosim - 10.0 * cosisq));
+            let xno2 = nm * nm;
+            let ainv2 = aonv * aonv;
+            let mut temp1 = 3.0 * xno2 * ainv2;
+            let mut temp = temp1 * root22;
+            self.d2201 = temp * f220 * g201;
+            self.d2211 = temp * f221 * g211;
+            temp1 *= aonv;
+            temp = temp1 * root32;
+            self.d3210 = temp * f321 * g310;
+            self.d3222 = temp * f322 * g322;
+            temp1 *= aonv;
+            temp = 2.0 * temp1 * root44;
+            self.d4410 = temp * f441 * g410;
+            self.d4422 = temp * f442 * g422;
+            temp1 *= aonv;
+            temp = temp1 * root52;
+            self.d5220 = temp * f522 * g520;
+            self.d5232 = temp * f523 * g532;
+            temp = 2.0 * temp1 * root54;
+            self.d5421 = temp * f542 * g521;
+            self.d5433 = temp * f543 * g533;
+            self.xlamo = (input.mo + input.nodeo + input.nodeo - theta - theta) % TWOPI;
+            self.xfact =
+                input.mdot + self.dmdt + 2.0 * (input.nodedot + self.dnodt - RPTIM) - input.no;
+        } else {
+            // synchronous resonance terms
+            let g200 = 1.0 + emsq * (-2.5 + 0.8125 * emsq);
+            let g310 = 1.0 + 2.0 * emsq;
+            let g300 = 1.0 + emsq * (-6.0 + 6.60937 * emsq);
+            let f220 = 0.75 * (1.0 + cosim) * (1.0 + cosim);
+            let f311 = 0.9375 * sinim * sinim * (1.0 + 3.0 * cosim) - 0.75 * (1.0 + cosim);
+            let f330 = 1.875 * (1.0 + cosim).powi(3);
+            let del1 = 3.0 * nm * nm * aonv * aonv;
+            self.del2 = 2.0 * del1 * f220 * g200 * q22;
+            self.del3 = 3.0 * del1 * f330 * g300 * q33 * aonv;
+            self.del1 = del1 * f311 * g310 * q31 * aonv;
+            self.xlamo = (input.mo + input.nodeo + input.argpo - theta) % TWOPI;
+            self.xfact =
+                input.mdot + input.xpidot - RPTIM + self.dmdt + self.domdt + self.dnodt - input.no;
+        }
+    }
+
+    /// Solar and lunar long period periodic terms at `t` minutes since epoch, from the
+    /// mean anomalies of the sun and moon.
+    pub(crate) fn periodics(&self, t: f64) -> LunarSolarPeriodics {
+        // calculate time varying periodics
+        let zm = self.zmos + ZNS * t;
+        let zf = zm + 2.0 * ZES * zm.sin();
+        let sinzf = zf.sin();
+        let f2 = 0.5 * sinzf * sinzf - 0.25;
+        let f3 = -0.5 * sinzf * zf.cos();
+        let solar = PeriodicTerms {
+            eccentricity: self.se2 * f2 + self.se3 * f3,
+            inclination: self.si2 * f2 + self.si3 * f3,
+            mean_anomaly: self.sl2 * f2 + self.sl3 * f3 + self.sl4 * sinzf,
+            perigee: self.sgh2 * f2 + self.sgh3 * f3 + self.sgh4 * sinzf,
+            node: self.sh2 * f2 + self.sh3 * f3,
+        };
+
+        let zm = self.zmol + ZNL * t;
+        let zf = zm + 2.0 * ZEL * zm.sin();
+        let sinzf = zf.sin();
+        let f2 = 0.5 * sinzf * sinzf - 0.25;
+        let f3 = -0.5 * sinzf * zf.cos();
+        let lunar = PeriodicTerms {
+            eccentricity: self.ee2 * f2 + self.e3 * f3,
+            inclination: self.xi2 * f2 + self.xi3 * f3,
+            mean_anomaly: self.xl2 * f2 + self.xl3 * f3 + self.xl4 * sinzf,
+            perigee: self.xgh2 * f2 + self.xgh3 * f3 + self.xgh4 * sinzf,
+            node: self.xh2 * f2 + self.xh3 * f3,
+        };
+        LunarSolarPeriodics { solar, lunar }
+    }
+
+    /// Deep space long period periodic contributions to the mean elements.
+    ///
+    /// At initialization (`init == true`) the periodics are not applied.
+    /// Returns the perturbed (ep, inclp, nodep, argpp, mp).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn dpper(
+        &self,
+        t: f64,
+        init: bool,
+        operation_mode: OperationMode,
+        mut ep: f64,
+        mut inclp: f64,
+        mut nodep: f64,
+        mut argpp: f64,
+        mut mp: f64,
+    ) -> (f64, f64, f64, f64, f64) {
+        if init {
+            return (ep, inclp, nodep, argpp, mp);
+        }
+        let LunarSolarPeriodics { solar, lunar } = self.periodics(t);
+
+        let pe = solar.eccentricity + lunar.eccentricity - self.peo;
+        let pinc = solar.inclination + lunar.inclination - self.pinco;
+        let pl = solar.mean_anomaly + lunar.mean_anomaly - self.plo;
+        let mut pgh = solar.perigee + lunar.perigee - self.pgho;
+        let mut ph = solar.node + lunar.node - self.pho;
+        inclp += pinc;
+        ep += pe;
+        let sinip = inclp.sin();
+        let cosip = inclp.cos();
+
+        // apply periodics directly above 0.2 rad (11.45916 deg) perturbed inclination,
+        // otherwise use the Lyddane modification
+        if inclp >= 0.2 {
+            ph /= sinip;
+            pgh -= cosip * ph;
+            argpp += pgh;
+            nodep += ph;
+            mp += pl;
+        } else {
+            let sinop = nodep.sin();
+            let cosop = nodep.cos();
+            let mut alfdp = sinip * sinop;
+            let mut betdp = sinip * cosop;
+            let dalf = ph * cosop + pinc * cosip * sinop;
+            let dbet = -ph * sinop + pinc * cosip * cosop;
+            alfdp += dalf;
+            betdp += dbet;
+            nodep %= TWOPI;
+            // node is used without a trigonometric function ahead
+            if nodep < 0.0 && operation_mode == OperationMode::Afspc {
+                nodep += TWOPI;
+            }
+            let mut xls = mp + argpp + cosip * nodep;
+            let dls = pl + pgh - pinc * nodep * sinip;
+            xls += dls;
+            let xnoh = nodep;
+            nodep = alfdp.atan2(betdp);
+            if nodep < 0.0 && operation_mode == OperationMode::Afspc {
+                nodep += TWOPI;
+            }
+            if (xnoh - nodep).abs() > PI {
+                if nodep < xnoh {
+                    nodep += TWOPI;
+                } else {
+                    nodep -= TWOPI;
+                }
+            }
+            mp += pl;
+            argpp = xls - mp - cosip * nodep;
+        }
+
+        (ep, inclp, nodep, argpp, mp)
+    }
+
+    /// Deep space secular effects and resonance integration.
+    ///
+    /// Takes the near earth secularly updated (em, argpm, inclm, nodem, mm) and returns
+    /// them with the lunar-solar secular rates and resonance effects applied, along
+    /// with the updated mean motion nm.
+    ///
+    /// The resonance integrator starts from epoch on every call rather than resuming
+    /// from the previous time as the reference does. It steps on the same grid either
+    /// way, so the results are the same and propagation needs no mutable state.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn dspace(
+        &self,
+        t: f64,
+        gsto: f64,
+        no: f64,
+        argpo: f64,
+        argpdot: f64,
+        mut em: f64,
+        mut argpm: f64,
+        mut inclm: f64,
+        mut nodem: f64,
+        mut mm: f64,
+    ) -> Result<(f64, f64, f64, f64, f64, f64), PropagationError> {
+        let fasx2 = 0.13130908;
+        let fasx4 = 2.8843198;
+        let fasx6 = 0.37448087;
+        let g22 = 5.7686396;
+        let g32 = 0.95240898;
+        let g44 = 1.8014998;
+        let g52 = 1.0508330;
+        let g54 = 4.4108898;
+        let stepp = self.resonance.step_min;
+        let stepn = -stepp;
+        let step2 = 0.5 * stepp * stepp;
+
+        // secular lunar-solar effects
+        let theta = (gsto + t * RPTIM) % TWOPI;
+        em += self.dedt * t;
+        inclm += self.didt * t;
+        argpm += self.domdt * t;
+        nodem += self.dnodt * t;
+        mm += self.dmdt * t;
+
+        let mut nm = no;
+        if self.irez == Resonance::None {
+            return Ok((em, argpm, inclm, nodem, mm, nm));
+        }
+        if self.resonance.strict && !t.is_finite() {
+            return Err(PropagationError::ResonanceDiverged);
+        }
+
+        // resonance integrator state, from epoch
+        let mut atime = 0.0;
+        let mut xni = no;
+        let mut xli = self.xlamo;
+        let delt = if t > 0.0 { stepp } else { stepn };
+
+        let (xndt, xldot, xnddt, ft) = loop {
+            // dot terms
+            let (xndt, xldot, mut xnddt);
+            if self.irez != Resonance::HalfDay {
+                // near-synchronous resonance terms
+                xndt = self.del1 * (xli - fasx2).sin()
+                    + self.del2 * (2.0 * (xli - fasx4)).sin()
+                    + self.del3 * (3.0 * (xli - fasx6)).sin();
+                xldot = xni + self.xfact;
+                xnddt = self.del1 * (xli - fasx2).cos()
+                    + 2.0 * self.del2 * (2.0 * (xli - fasx4)).cos()
+                    + 3.0 * self.del3 * (3.0 * (xli - fasx6)).cos();
+                xnddt *= xldot;
+            } else {
+                // near half-day resonance terms
+                let xomi = argpo + argpdot * atime;
+                let x2omi = xomi + xomi;
+                let x2li = xli + xli;
+                xndt = self.d2201 * (x2omi + xli - g22).sin()
+                    + self.d2211 * (xli - g22).sin()
+                    + self.d3210 * (xomi + xli - g32).sin()
+                    + self.d3222 * (-xomi + xli - g32).sin()
+                    + self.d4410 * (x2omi + x2li - g44).sin()
+                    + self.d4422 * (x2li - g44).sin()
+                    + self.d5220 * (xomi + xli - g52).sin()
+                    + self.d5232 * (-xomi + xli - g52).sin()
+                    + self.d5421 * (xomi + x2li - g54).sin()
+                    + self.d5433 * (-xomi + x2li - g54).sin();
+                xldot = xni + self.xfact;
+                xnddt = self.d2201 * (x2omi + xli - g22).cos()
+                    + self.d2211 * (xli - g22).cos()
+                    + self.d3210 * (xomi + xli - g32).cos()
+                    + self.d3222 * (-xomi + xli - g32).cos()
+                    + self.d5220 * (xomi + xli - g52).cos()
+                    + self.d5232 * (-xomi + xli - g52).cos()
+                    + 2.0
+                        * (self.d4410 * (x2omi + x2li - g44).cos()
+                            + self.d4422 * (x2li - g44).cos()
+                            + self.d5421 * (xomi + x2li - g54).cos()
+                            + self.d5433 * (-xomi + x2li - g54).cos());
+                xnddt *= xldot;
+            }
+
+            // integrator
+            if (t - atime).abs() < stepp {
+                break (xndt, xldot, xnddt, t - atime);
+            }
+            xli += xldot * delt + xndt * step2;
+            xni += xndt * delt + xnddt * step2;
+            atime += delt;
+            if self.resonance.strict && !(self.in_band(xni) && xli.is_finite()) {
+                return Err(PropagationError::ResonanceDiverged);
+            }
+        };
+
+        nm = xni + xndt * ft + xnddt * ft * ft * 0.5;
+        if self.resonance.strict && !self.in_band(nm) {
+            return Err(PropagationError::ResonanceDiverged);
+        }
+        let xl = xli + xldot * ft + xndt * ft * ft * 0.5;
+        mm = if self.irez != Resonance::Synchronous {
+            xl - 2.0 * nodem + 2.0 * theta
+        } else {
+            xl - nodem - argpm + theta
+        };
+
+        Ok((em, argpm, inclm, nodem, mm, nm))
+    }
+
+    /// Resonance regime of the orbit.
+    pub(crate) fn resonance(&self) -> Resonance {
+        self.irez
+    }
+
+    /// Whether an integrated mean motion is still in the band of the resonance regime,
+    /// which is false for NaN.
+    fn in_band(&self, nm: f64) -> bool {
+        let (low, high) = match self.irez {
+            Resonance::Synchronous => SYNCHRONOUS_BAND,
+            _ => HALF_DAY_BAND,
+        };
+        (low..=high).contains(&nm)
+    }
+}
@@ -0,0 +1,300 @@
+use alloc::string::String;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::{Epoch, days2mdh};
+use crate::tle::{Classification, DEFAULT_YEAR_PIVOT, TLE, TleParseError};
+use crate::utils::{GravitationalConstants, WGSModel, get_grav_c, radians};
+
+/// Perigee altitude below which a satellite is taken as decayed, in km: below the
+/// Karman line, an orbit lasts a few revolutions at most
+const DECAYED_PERIGEE_KM: f64 = 100.0;
+
+/// Relative tolerance of the mean motion in `brouwer_to_kozai`
+const KOZAI_TOLERANCE: f64 = 1e-15;
+
+/// Iteration limit of `brouwer_to_kozai`; it converges in a few iterations, as the
+/// correction is of the order of J2
+const KOZAI_MAX_ITERATIONS: usize = 20;
+
+/// Orbit regime of a satellite, from `Elements::regime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Regime {
+    /// Low earth orbit: more than 11.25 revolutions a day (a period below 128 minutes)
+    Leo,
+    /// Medium earth orbit: more than 1.1 and up to 11.25 revolutions a day
+    Meo,
+    /// Geosynchronous orbit: 0.9 to 1.1 revolutions a day, at any inclination and an
+    /// eccentricity below 0.25
+    Geo,
+    /// Highly elliptical orbit (eccentricity of 0.25 or more), or beyond
+    /// geosynchronous orbit
+    Heo,
+    /// Perigee below 100 km
+    Decayed,
+}
+
+/// Mean elements of a satellite in the units SGP4 works in: angles in radians and mean
+/// motion in radians per minute.
+///
+/// Parsing and converting a TLE to elements doesn't initialize a propagator, so
+/// elements can be inspected, stored and modified cheaply; `SGP4::from_elements`
+/// initializes one when it's needed. Field names follow the satellite record.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Elements {
+    pub satnum: u64,
+    /// Object name from the title line of a three line element set
+    pub name: Option<String>,
+    pub classification: Option<Classification>,
+    /// International designator
+    pub intldesg: String,
+    /// Four digit year of the epoch, so epochs outside the 1957-2056 of two digit years
+    /// survive a conversion back to a TLE
+    pub epochyr: u64,
+    /// Day of the year of the epoch, starting at 1.0 at midnight on January 1st
+    pub epochdays: f64,
+    pub epoch: Epoch,
+    /// First derivative of the mean motion in radians per minute^2
+    pub ndot: f64,
+    /// Second derivative of the mean motion in radians per minute^3
+    pub nddot: f64,
+    /// Drag term in inverse earth radii
+    pub bstar: f64,
+    /// Element set number
+    pub elnum: u64,
+    /// Inclination
+    pub inclo: f64,
+    /// Right ascension of the ascending node
+    pub nodeo: f64,
+    /// Eccentricity
+    pub ecco: f64,
+    /// Argument of perigee
+    pub argpo: f64,
+    /// Mean anomaly
+    pub mo: f64,
+    /// Mean motion (Kozai) in radians per minute
+    pub no_kozai: f64,
+    /// Revolution number at epoch
+    pub revnum: u64,
+}
+
+impl Elements {
+    /// Parse the lines of a TLE and convert its elements, warning about wrong checksums.
+    pub fn from_tle(line1: &str, line2: &str) -> Result<Elements, TleParseError> {
+        Ok(TLE::new(line1.into(), line2.into())?.into())
+    }
+
+    /// Convert the elements of a parsed TLE, with two digit epoch years below
+    /// `year_pivot` in the 2000s and the others in the 1900s (see
+    /// `TLE::epoch_year_with_pivot`). The warnings of the TLE aren't part of the
+    /// elements; see `SGP4::warnings` to keep them with a propagator.
+    pub fn from_parsed_tle(tle: TLE, year_pivot: u64) -> Elements {
+        let xpdotp = DAY2MIN / TWOPI;
+        let year = tle.epoch_year_with_pivot(year_pivot);
+        let mut elements = Elements {
+            satnum: tle.satnum(),
+            epochyr: year,
+            epochdays: tle.epoch_days(),
+            epoch: Epoch::from_calendar(year, days2mdh(year, tle.epoch_days())),
+            ndot: tle.mean_motion_dot() / (xpdotp * DAY2MIN),
+            nddot: tle.mean_motion_ddot() / (xpdotp * DAY2MIN.powi(2)),
+            bstar: tle.bstar(),
+            elnum: tle.element_number(),
+            inclo: radians(tle.inclination()),
+            nodeo: radians(tle.raan()),
+            ecco: tle.eccentricity(),
+            argpo: radians(tle.arg_of_perigee()),
+            mo: radians(tle.mean_anomaly()),
+            no_kozai: tle.mean_motion() / xpdotp,
+            revnum: tle.rev_number(),
+            ..Elements::default()
+        };
+        (elements.name, elements.classification, elements.intldesg) = tle.into_parts();
+        elements
+    }
+}
+
+impl Elements {
+    /// Classify the orbit by the mean motion, eccentricity and perigee altitude, with
+    /// the WGS-72 constants of the element sets. The first match wins: a perigee below
+    /// 100 km is decayed, an eccentricity of 0.25 or more HEO, more than 11.25
+    /// revolutions a day LEO, more than 1.1 MEO, 0.9 to 1.1 GEO and fewer HEO. GEO
+    /// doesn't check the inclination, so it also holds inclined geosynchronous orbits.
+    pub fn regime(&self) -> Regime {
+        let grav_const = get_grav_c(&WGSModel::WGS_72);
+        let revs_per_day = self.no_kozai * DAY2MIN / TWOPI;
+        let a = (grav_const.xke / self.no_kozai).powf(2.0 / 3.0);
+        let perigee_altitude = (a * (1.0 - self.ecco) - 1.0) * grav_const.radiusearthkm;
+        if perigee_altitude < DECAYED_PERIGEE_KM {
+            Regime::Decayed
+        } else if self.ecco >= 0.25 {
+            Regime::Heo
+        } else if revs_per_day > 11.25 {
+            Regime::Leo
+        } else if revs_per_day > 1.1 {
+            Regime::Meo
+        } else if revs_per_day >= 0.9 {
+            Regime::Geo
+        } else {
+            Regime::Heo
+        }
+    }
+}
+
+/// Convert a Kozai mean motion, as in element sets, to the Brouwer mean motion SGP4
+/// propagates with (the "un-Kozai'd" mean motion of the satellite record), both in
+/// radians per minute. The conversion depends on the eccentricity and inclination (in
+/// radians) through the J2 perturbation of the model.
+pub fn kozai_to_brouwer(no_kozai: f64, ecco: f64, inclo: f64, model: &WGSModel) -> f64 {
+    no_kozai / (1.0 + kozai_correction(no_kozai, ecco, inclo, &get_grav_c(model)))
+}
+
+/// Convert a Brouwer mean motion to the Kozai mean motion of element sets, the inverse
+/// of `kozai_to_brouwer`, e.g. to write out elements from a mean motion fitted or taken
+/// from another theory. Solved by fixed point iteration.
+pub fn brouwer_to_kozai(no_unkozai: f64, ecco: f64, inclo: f64, model: &WGSModel) -> f64 {
+    let grav_const = get_grav_c(model);
+    let mut no_kozai = no_unkozai;
+    for _ in 0..KOZAI_MAX_ITERATIONS {
+        let next = no_unkozai * (1.0 + kozai_correction(no_kozai, ecco, inclo, &grav_const));
+        let converged = (next - no_kozai).abs() <= KOZAI_TOLERANCE * next.abs();
+        no_kozai = next;
+        if converged {
+            break;
+        }
+    }
+    no_kozai
+}
+
+/// Relative correction from the Kozai to the Brouwer mean motion, as in `initl` of the
+/// reference: `no_unkozai = no_kozai / (1 + del)`.
+pub(crate) fn kozai_correction(
+    no_kozai: f64,
+    ecco: f64,
+    inclo: f64,
+    grav_const: &GravitationalConstants,
+) -> f64 {
+    let omeosq = 1.0 - ecco * ecco;
+    let rteosq = omeosq.sqrt();
+    let cosio = inclo.cos();
+    let cosio2 = cosio * cosio;
+    let ak = (grav_const.xke / no_kozai).powf(2.0 / 3.0);
+    let d1 = 0.75 * grav_const.j2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+    let del = d1 / (ak * ak);
+    let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+    d1 / (adel * adel)
+}
+
+impl From<TLE> for Elements {
+    /// Convert the elements of a TLE, with the epoch year pivot of the reference.
+    fn from(tle: TLE) -> Elements {
+        Elements::from_parsed_tle(tle, DEFAULT_YEAR_PIVOT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ISS_LINE1, ISS_LINE2, VANGUARD_LINE1, VANGUARD_LINE2};
+    use crate::sgp4::{OperationMode, SGP4};
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_from_tle() {
+        let elements = Elements::from_tle(VANGUARD_LINE1, VANGUARD_LINE2).unwrap();
+        assert_eq!(
+            (elements.satnum, elements.epochyr, elements.revnum),
+            (5, 2000, 41366)
+        );
+        assert!((elements.epoch - Epoch::new(2451722.5, 0.78495062)).abs() < 1e-12);
+        assert!((elements.no_kozai - 10.82419157 * TWOPI / DAY2MIN).abs() < 1e-12);
+        assert_eq!(elements.inclo, radians(34.2682));
+
+        // initializing from the elements is the same as from the TLE
+        let sgp4 =
+            SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &elements).unwrap();
+        let tle = TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap();
+        let expected = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        assert_eq!(sgp4.propagate(360.0), expected.propagate(360.0));
+        assert_eq!(sgp4.elements(), elements);
+
+        // elements can be changed before initialization
+        let raised = Elements {
+            no_kozai: elements.no_kozai * 0.9,
+            ..elements
+        };
+        let sgp4 = SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &raised).unwrap();
+        assert!(sgp4.semimajor_axis_km() > expected.semimajor_axis_km());
+
+        assert!(Elements::from_tle(VANGUARD_LINE1, &VANGUARD_LINE2[..60]).is_err());
+    }
+
+    #[test]
+    fn test_kozai_to_brouwer() {
+        let elements = Elements::from_tle(VANGUARD_LINE1, VANGUARD_LINE2).unwrap();
+        let (ecco, inclo) = (elements.ecco, elements.inclo);
+        let no_unkozai = kozai_to_brouwer(elements.no_kozai, ecco, inclo, &WGSModel::WGS_72);
+        let sgp4 =
+            SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &elements).unwrap();
+        let mean_motion = sgp4.mean_elements(0.0).unwrap().mean_motion;
+        assert!((no_unkozai / mean_motion - 1.0).abs() < 1e-15);
+        assert!(no_unkozai < elements.no_kozai);
+
+        let no_kozai = brouwer_to_kozai(no_unkozai, ecco, inclo, &WGSModel::WGS_72);
+        assert!((no_kozai / elements.no_kozai - 1.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn test_regime() {
+        let regime = |line1: &str, line2: &str| Elements::from_tle(line1, line2).unwrap().regime();
+        assert_eq!(regime(ISS_LINE1, ISS_LINE2), Regime::Leo);
+        assert_eq!(
+            regime(
+                "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+                "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656",
+            ),
+            Regime::Heo
+        );
+        assert_eq!(
+            regime(
+                "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+                "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+            ),
+            Regime::Decayed
+        );
+
+        let leo = Elements::from_tle(ISS_LINE1, ISS_LINE2).unwrap();
+        let at = |revs_per_day: f64| Elements {
+            no_kozai: revs_per_day * TWOPI / DAY2MIN,
+            ..leo.clone()
+        };
+        assert_eq!(at(2.0).regime(), Regime::Meo);
+        assert_eq!(at(1.0027).regime(), Regime::Geo);
+        assert_eq!(at(0.5).regime(), Regime::Heo);
+
+        // either side of each boundary
+        let step = 1e-9;
+        for (revs_per_day, below, above) in [
+            (11.25, Regime::Meo, Regime::Leo),
+            (1.1, Regime::Geo, Regime::Meo),
+            (0.9, Regime::Heo, Regime::Geo),
+        ] {
+            assert_eq!(at(revs_per_day - step).regime(), below, "{revs_per_day}");
+            assert_eq!(at(revs_per_day + step).regime(), above, "{revs_per_day}");
+        }
+        let with_ecco = |ecco: f64| Elements { ecco, ..at(2.0) };
+        assert_eq!(with_ecco(0.25 - step).regime(), Regime::Meo);
+        assert_eq!(with_ecco(0.25).regime(), Regime::Heo);
+        let grav_const = get_grav_c(&WGSModel::WGS_72);
+        let a = (grav_const.xke / leo.no_kozai).powf(2.0 / 3.0);
+        let with_perigee = |perigee_km: f64| Elements {
+            ecco: 1.0 - (1.0 + perigee_km / grav_const.radiusearthkm) / a,
+            ..leo.clone()
+        };
+        assert_eq!(with_perigee(99.9).regime(), Regime::Decayed);
+        assert_eq!(with_perigee(100.1).regime(), Regime::Leo);
+    }
+}
@@ -0,0 +1,367 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+#[cfg(feature = "std")]
+use std::{fs, io};
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::jd_to_mjd;
+
+/// Earth orientation parameters at a time.
+///
+/// All zero by default, which reduces the conversions that take them to the
+/// approximations that don't: UT1 = UTC and no polar motion.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eop {
+    /// UT1 - UTC in seconds
+    pub ut1_utc: f64,
+    /// Polar motion x in radians
+    pub xp: f64,
+    /// Polar motion y in radians
+    pub yp: f64,
+    /// Excess length of day in seconds
+    pub lod: f64,
+}
+
+/// A source of earth orientation parameters for the conversions that take them, e.g.
+/// `frames::teme_to_itrf`.
+///
+/// Implemented by tables parsed from IERS finals files (`EopTable`), by records in
+/// memory, e.g. a `static` array (`[EopRecord]`), and by finals files that are reloaded
+/// when updated (`EopFile`). Applications with their own EOP pipelines implement it to
+/// inject their data.
+pub trait EopProvider {
+    /// The parameters at `jd_utc`, the Julian date in UTC, or `None` if they aren't
+    /// known then.
+    fn eop(&self, jd_utc: f64) -> Option<Eop>;
+}
+
+/// The earth orientation parameters of a day, from an IERS finals file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EopRecord {
+    /// Modified Julian date of 0h UTC
+    pub mjd: f64,
+    pub eop: Eop,
+    /// Whether the values are predictions rather than observations
+    pub predicted: bool,
+}
+
+/// Errors returned when parsing an IERS finals file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EopParseError {
+    /// A field does not hold a valid value
+    InvalidField {
+        line: usize,
+        field: &'static str,
+        text: String,
+    },
+    /// The dates don't increase from the previous line
+    OutOfOrder { line: usize },
+}
+
+impl fmt::Display for EopParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EopParseError::InvalidField { line, field, text } => {
+                write!(f, "invalid {field} on line {line}: {text:?}")
+            }
+            EopParseError::OutOfOrder { line } => {
+                write!(f, "the date on line {line} doesn't follow the previous one")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EopParseError {}
+
+/// Daily earth orientation parameters, interpolated between the days.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EopTable {
+    records: Vec<EopRecord>,
+}
+
+impl EopTable {
+    /// Parse an IERS finals file: `finals.all` (IAU-1980) or `finals2000A.all`, or
+    /// their daily and data-only variants, which share the fixed column format.
+    ///
+    /// The Bulletin A values are read: the polar motion, UT1 - UTC and the length of
+    /// day, which is taken as zero where it's blank. The nutation corrections are
+    /// ignored. The days past the predictions, with blank values, are skipped.
+    pub fn parse_finals(text: &str) -> Result<EopTable, EopParseError> {
+        let mut records: Vec<EopRecord> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let field = |columns: Range<usize>, name: &'static str| {
+                let text = line.get(columns).unwrap_or("").trim();
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                text.parse::<f64>()
+                    .map(Some)
+                    .map_err(|_| EopParseError::InvalidField {
+                        line: line_number,
+                        field: name,
+                        text: text.to_string(),
+                    })
+            };
+            let Some(mjd) = field(7..15, "MJD")? else {
+                continue;
+            };
+            let (Some(xp), Some(yp), Some(ut1_utc)) = (
+                field(18..27, "polar motion x")?,
+                field(37..46, "polar motion y")?,
+                field(58..68, "UT1-UTC")?,
+            ) else {
+                continue;
+            };
+            let lod = field(79..86, "length of day")?.unwrap_or(0.0);
+            if records.last().is_some_and(|last| last.mjd >= mjd) {
+                return Err(EopParseError::OutOfOrder { line: line_number });
+            }
+            records.push(EopRecord {
+                mjd,
+                eop: Eop {
+                    ut1_utc,
+                    xp: xp * ARCSEC2RAD,
+                    yp: yp * ARCSEC2RAD,
+                    lod: lod * 1e-3,
+                },
+                predicted: line.get(57..58) == Some("P"),
+            });
+        }
+        Ok(EopTable { records })
+    }
+
+    /// The records, sorted by date
+    pub fn records(&self) -> &[EopRecord] {
+        &self.records
+    }
+
+    /// The parameters at `jd_utc`, interpolated linearly between the days, or `None`
+    /// outside the days of the table.
+    ///
+    /// UT1 - UTC jumps by a second at leap seconds; the jump is left at midnight
+    /// rather than spread over the day before.
+    pub fn at(&self, jd_utc: f64) -> Option<Eop> {
+        self.records.eop(jd_utc)
+    }
+}
+
+impl EopProvider for EopTable {
+    fn eop(&self, jd_utc: f64) -> Option<Eop> {
+        self.at(jd_utc)
+    }
+}
+
+impl EopProvider for [EopRecord] {
+    /// The parameters of records sorted by date, interpolated as in `EopTable::at`.
+    fn eop(&self, jd_utc: f64) -> Option<Eop> {
+        let mjd = jd_to_mjd(jd_utc, 0.0);
+        let next = self.partition_point(|record| record.mjd <= mjd);
+        if next == 0 {
+            return None;
+        }
+        let before = &self[next - 1];
+        if mjd == before.mjd {
+            return Some(before.eop);
+        }
+        let after = self.get(next)?;
+        let (a, b) = (before.eop, after.eop);
+        let fraction = (mjd - before.mjd) / (after.mjd - before.mjd);
+        let interpolate = |a: f64, b: f64| a + fraction * (b - a);
+        let leap_second = (b.ut1_utc - a.ut1_utc).round();
+        Some(Eop {
+            ut1_utc: interpolate(a.ut1_utc, b.ut1_utc - leap_second),
+            xp: interpolate(a.xp, b.xp),
+            yp: interpolate(a.yp, b.yp),
+            lod: interpolate(a.lod, b.lod),
+        })
+    }
+}
+
+/// Errors of reading an IERS finals file.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum EopFileError {
+    /// The file couldn't be read
+    Io(io::Error),
+    Parse(EopParseError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for EopFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EopFileError::Io(error) => write!(f, "couldn't read the EOP file: {error}"),
+            EopFileError::Parse(error) => write!(f, "invalid EOP file: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EopFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EopFileError::Io(error) => Some(error),
+            EopFileError::Parse(error) => Some(error),
+        }
+    }
+}
+
+/// An IERS finals file, parsed when opened and again by `reload` when it has been
+/// updated, e.g. by a daily download.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct EopFile {
+    path: PathBuf,
+    table: RwLock<(Option<SystemTime>, EopTable)>,
+}
+
+#[cfg(feature = "std")]
+impl EopFile {
+    /// Read and parse the finals file at `path`, see `EopTable::parse_finals`.
+    pub fn open(path: impl AsRef<Path>) -> Result<EopFile, EopFileError> {
+        let path = path.as_ref().to_path_buf();
+        let table = EopFile::read(&path)?;
+        Ok(EopFile {
+            path,
+            table: RwLock::new(table),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Parse the file again if it has been modified since it was last read. Returns
+    /// whether it was; on errors the previous parameters are kept.
+    pub fn reload(&self) -> Result<bool, EopFileError> {
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let last = self.table.read().unwrap_or_else(|e| e.into_inner()).0;
+        if modified.is_some() && modified == last {
+            return Ok(false);
+        }
+        let table = EopFile::read(&self.path)?;
+        *self.table.write().unwrap_or_else(|e| e.into_inner()) = table;
+        Ok(true)
+    }
+
+    fn read(path: &Path) -> Result<(Option<SystemTime>, EopTable), EopFileError> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let text = fs::read_to_string(path).map_err(EopFileError::Io)?;
+        let table = EopTable::parse_finals(&text).map_err(EopFileError::Parse)?;
+        Ok((modified, table))
+    }
+}
+
+#[cfg(feature = "std")]
+impl EopProvider for EopFile {
+    fn eop(&self, jd_utc: f64) -> Option<Eop> {
+        self.table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .1
+            .at(jd_utc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::jday;
+
+    const FINALS: &str = "\
+161231 57753.00 I  0.050000 0.000091  0.300000 0.000091  I-0.4087000 0.0000124  1.0000 0.0081  I     0.297    0.215    -0.113    0.207
+17 1 1 57754.00 I  0.051000 0.000091  0.301000 0.000091  I 0.5923000 0.0000124  1.0000 0.0081  I     0.297    0.215    -0.113    0.207
+24 1 1 60310.00 I  0.062958 0.000091  0.293549 0.000091  I 0.0069963 0.0000124  0.1839 0.0081  I     0.297    0.215    -0.113    0.207
+24 1 2 60311.00 I  0.061477 0.000091  0.293237 0.000091  I 0.0068201 0.0000124  0.1691 0.0081  I     0.297    0.215    -0.113    0.207
+24 6 1 60462.00 P  0.100000 0.000091  0.400000 0.000091  P-0.0123000 0.0000124                 I     0.297    0.215    -0.113    0.207
+24 6 2 60463.00
+";
+
+    #[test]
+    fn test_parse_finals() {
+        let table = EopTable::parse_finals(FINALS).unwrap();
+        let records = table.records();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[2].mjd, 60310.0);
+        assert!((records[2].eop.xp - 0.062958 * ARCSEC2RAD).abs() < 1e-18);
+        assert_eq!(records[2].eop.ut1_utc, 0.0069963);
+        assert!((records[2].eop.lod - 0.1839e-3).abs() < 1e-15);
+        assert!(!records[2].predicted);
+        assert!(records[4].predicted);
+        assert_eq!(records[4].eop.lod, 0.0);
+
+        // noon of 2024-01-01, halfway between the days
+        let (jd, jdfrac) = jday(2024, (1, 1, 12, 0, 0.0));
+        let eop = table.at(jd + jdfrac).unwrap();
+        assert!((eop.ut1_utc - 0.0069082).abs() < 1e-12);
+        assert!((eop.yp - 0.293393 * ARCSEC2RAD).abs() < 1e-15);
+        // the leap second at the end of 2016 is kept at midnight
+        let (jd, jdfrac) = jday(2016, (12, 31, 18, 0, 0.0));
+        let ut1_utc = table.at(jd + jdfrac).unwrap().ut1_utc;
+        assert!((ut1_utc - (-0.4087 + 0.75 * 0.001)).abs() < 1e-9);
+        assert!(table.at(JD_TO_MJD_OFFSET + 57752.5).is_none());
+        assert!(table.at(JD_TO_MJD_OFFSET + 60462.5).is_none());
+
+        // the records alone give the same parameters
+        let jd = JD_TO_MJD_OFFSET + 60310.25;
+        assert_eq!(records.eop(jd), table.eop(jd));
+
+        let error = EopTable::parse_finals(&FINALS.replace("0.061477", "0.06x477")).unwrap_err();
+        assert!(matches!(error, EopParseError::InvalidField { line: 4, .. }));
+        let swapped: String = FINALS
+            .lines()
+            .rev()
+            .map(|line| line.to_string() + "\n")
+            .collect();
+        assert_eq!(
+            EopTable::parse_finals(&swapped),
+            Err(EopParseError::OutOfOrder { line: 3 })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_eop_file() {
+        let path = std::env::temp_dir().join(format!("sgp4-finals-{}.all", std::process::id()));
+        fs::write(&path, &FINALS[..FINALS.find("24 1 2").unwrap()]).unwrap();
+        let file = EopFile::open(&path).unwrap();
+        let jd = JD_TO_MJD_OFFSET + 60310.5;
+        assert_eq!(file.eop(jd), None);
+        assert!(!file.reload().unwrap());
+
+        // a new day of data
+        fs::write(&path, FINALS).unwrap();
+        let future = SystemTime::now() + core::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(future))
+            .unwrap();
+        assert!(file.reload().unwrap());
+        let table = EopTable::parse_finals(FINALS).unwrap();
+        assert_eq!(file.eop(jd), table.eop(jd));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(file.reload(), Err(EopFileError::Io(_))));
+        assert_eq!(file.eop(jd), table.eop(jd));
+    }
+}
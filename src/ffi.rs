@@ -0,0 +1,221 @@
+//! C interface to the propagator, for embedding in C and C++ ground software.
+//!
+//! Build the shared library with `cargo rustc --release --features cdylib --crate-type
+//! cdylib`; the declarations are in `include/sgp4.h`, generated by cbindgen with the
+//! command in its first lines.
+
+use core::ffi::{CStr, c_char, c_int};
+use core::ptr;
+
+use crate::sgp4::{OperationMode, SGP4, Sgp4Error};
+use crate::tle::TLE;
+use crate::utils::WGSModel;
+
+/// No error
+pub const SGP4_OK: c_int = 0;
+/// Mean elements out of range: eccentricity >= 1 or < -0.001
+pub const SGP4_ERROR_INVALID_ELEMENTS: c_int = 1;
+/// Mean motion less than or equal to zero
+pub const SGP4_ERROR_NEGATIVE_MEAN_MOTION: c_int = 2;
+/// Perturbed eccentricity out of range
+pub const SGP4_ERROR_ECCENTRICITY_OUT_OF_RANGE: c_int = 3;
+/// Semi-latus rectum less than zero
+pub const SGP4_ERROR_NEGATIVE_SEMILATUS_RECTUM: c_int = 4;
+/// Satellite has decayed
+pub const SGP4_ERROR_ORBITAL_DECAY: c_int = 6;
+/// The TLE lines could not be parsed
+pub const SGP4_ERROR_PARSE: c_int = 10;
+/// A pointer argument is null, a string is not UTF-8 or the gravity model is unknown
+pub const SGP4_ERROR_INVALID_ARGUMENT: c_int = -1;
+
+/// WGS-72 constants with the low precision mu and xke of the original code
+pub const SGP4_WGS72_LOW_PRECISION: c_int = 0;
+/// WGS-72 constants, the ones the TLEs are generated with
+pub const SGP4_WGS72: c_int = 1;
+/// WGS-84 constants
+pub const SGP4_WGS84: c_int = 2;
+
+/// Opaque handle to an initialized satellite.
+pub struct Sgp4Satellite {
+    sgp4: SGP4,
+    error: c_int,
+}
+
+/// Parse the lines and initialize the propagator, returning an error code on failure.
+unsafe fn init(
+    line1: *const c_char,
+    line2: *const c_char,
+    model: c_int,
+    afspc_mode: bool,
+) -> Result<SGP4, c_int> {
+    let model = match model {
+        SGP4_WGS72_LOW_PRECISION => WGSModel::WGS_72_LOW_PRECISION,
+        SGP4_WGS72 => WGSModel::WGS_72,
+        SGP4_WGS84 => WGSModel::WGS_84,
+        _ => return Err(SGP4_ERROR_INVALID_ARGUMENT),
+    };
+    if line1.is_null() || line2.is_null() {
+        return Err(SGP4_ERROR_INVALID_ARGUMENT);
+    }
+    // SAFETY: the caller passes nul-terminated strings
+    let (line1, line2) = unsafe { (CStr::from_ptr(line1), CStr::from_ptr(line2)) };
+    let (Ok(line1), Ok(line2)) = (line1.to_str(), line2.to_str()) else {
+        return Err(SGP4_ERROR_INVALID_ARGUMENT);
+    };
+    let tle = TLE::new(line1.into(), line2.into()).map_err(|_| SGP4_ERROR_PARSE)?;
+    let operation_mode = if afspc_mode {
+        OperationMode::Afspc
+    } else {
+        OperationMode::Improved
+    };
+    SGP4::from_tle(model, operation_mode, tle).map_err(|error| match error {
+        Sgp4Error::Parse(_) => SGP4_ERROR_PARSE,
+        Sgp4Error::Propagation(error) => error.code(),
+        Sgp4Error::InvalidSchedule => SGP4_ERROR_INVALID_ARGUMENT,
+    })
+}
+
+/// Initialize a satellite from the two lines of a TLE.
+///
+/// `model` is one of `SGP4_WGS72_LOW_PRECISION`, `SGP4_WGS72` or `SGP4_WGS84`.
+/// Returns null on failure and stores the error code in `error` if it isn't null.
+/// Release the satellite with `sgp4_free`.
+///
+/// # Safety
+///
+/// `line1` and `line2` must be null or valid nul-terminated strings, and `error` must
+/// be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sgp4_init(
+    line1: *const c_char,
+    line2: *const c_char,
+    model: c_int,
+    afspc_mode: bool,
+    error: *mut c_int,
+) -> *mut Sgp4Satellite {
+    // SAFETY: forwarded from the caller
+    let result = unsafe { init(line1, line2, model, afspc_mode) };
+    let code = result.as_ref().err().copied().unwrap_or(SGP4_OK);
+    if !error.is_null() {
+        // SAFETY: the caller passes a pointer valid for writes
+        unsafe { *error = code };
+    }
+    match result {
+        Ok(sgp4) => Box::into_raw(Box::new(Sgp4Satellite {
+            sgp4,
+            error: SGP4_OK,
+        })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Propagate a satellite to `tsince_min` minutes since epoch, writing the TEME position
+/// in km to `r` and velocity in km/s to `v`. Returns the error code, also available
+/// from `sgp4_error` until the next call.
+///
+/// # Safety
+///
+/// `satellite` must be null or returned by `sgp4_init` and not yet freed. `r` and `v`
+/// must be null or valid for writes of three doubles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sgp4_propagate(
+    satellite: *mut Sgp4Satellite,
+    tsince_min: f64,
+    r: *mut f64,
+    v: *mut f64,
+) -> c_int {
+    if satellite.is_null() || r.is_null() || v.is_null() {
+        return SGP4_ERROR_INVALID_ARGUMENT;
+    }
+    // SAFETY: the caller passes a live satellite and arrays of three doubles
+    let (satellite, r, v) = unsafe {
+        (
+            &mut *satellite,
+            &mut *r.cast::<[f64; 3]>(),
+            &mut *v.cast::<[f64; 3]>(),
+        )
+    };
+    satellite.error = match satellite.sgp4.propagate(tsince_min) {
+        Ok(state) => {
+            (*r, *v) = (state.position_km, state.velocity_km_s);
+            SGP4_OK
+        }
+        Err(error) => error.code(),
+    };
+    satellite.error
+}
+
+/// Error code of the last propagation of a satellite.
+///
+/// # Safety
+///
+/// `satellite` must be null or returned by `sgp4_init` and not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sgp4_error(satellite: *const Sgp4Satellite) -> c_int {
+    // SAFETY: the caller passes a live satellite
+    match unsafe { satellite.as_ref() } {
+        Some(satellite) => satellite.error,
+        None => SGP4_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Release a satellite returned by `sgp4_init`. Null is ignored.
+///
+/// # Safety
+///
+/// `satellite` must be null or returned by `sgp4_init` and not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sgp4_free(satellite: *mut Sgp4Satellite) {
+    if !satellite.is_null() {
+        // SAFETY: the satellite was allocated by `sgp4_init`
+        drop(unsafe { Box::from_raw(satellite) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{VANGUARD_LINE1, VANGUARD_LINE2};
+    use std::ffi::CString;
+
+    #[test]
+    fn test_ffi() {
+        let line1 = CString::new(VANGUARD_LINE1).unwrap();
+        let line2 = CString::new(VANGUARD_LINE2).unwrap();
+        let mut error = -2;
+        let (mut r, mut v) = ([0.0; 3], [0.0; 3]);
+        unsafe {
+            let satellite = sgp4_init(
+                line1.as_ptr(),
+                line2.as_ptr(),
+                SGP4_WGS72,
+                false,
+                &mut error,
+            );
+            assert!(!satellite.is_null());
+            assert_eq!(error, SGP4_OK);
+            let code = sgp4_propagate(satellite, 360.0, r.as_mut_ptr(), v.as_mut_ptr());
+            assert_eq!(code, SGP4_OK);
+            assert_eq!(sgp4_error(satellite), SGP4_OK);
+            sgp4_free(satellite);
+
+            let satellite = sgp4_init(
+                line2.as_ptr(),
+                line1.as_ptr(),
+                SGP4_WGS72,
+                false,
+                &mut error,
+            );
+            assert!(satellite.is_null());
+            assert_eq!(error, SGP4_ERROR_PARSE);
+            let satellite = sgp4_init(line1.as_ptr(), line2.as_ptr(), 7, false, &mut error);
+            assert!(satellite.is_null());
+            assert_eq!(error, SGP4_ERROR_INVALID_ARGUMENT);
+            assert_eq!(sgp4_error(ptr::null()), SGP4_ERROR_INVALID_ARGUMENT);
+        }
+        let r_expected = [-7154.03120202, -3783.17682504, -3536.19412294];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-7);
+        }
+    }
+}
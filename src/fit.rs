@@ -0,0 +1,389 @@
+use alloc::vec::Vec;
+use core::array;
+use core::fmt;
+
+use crate::coe::rv2coe;
+use crate::constants::*;
+use crate::elements::{Elements, brouwer_to_kozai};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::sgp4::{OperationMode, PropagationError, SGP4};
+use crate::state::State;
+use crate::stm::{STEPS, perturb};
+use crate::time::{Epoch, mdh2days};
+use crate::utils::{WGSModel, get_grav_c};
+
+/// Step of the central difference in B*, in inverse earth radii
+const BSTAR_STEP: f64 = 1e-6;
+
+/// Velocity residuals in km/s are weighted by this many seconds to add them to the
+/// position residuals in km
+const VELOCITY_WEIGHT_S: f64 = 100.0;
+
+/// Corrections are halved while they make the fit worse, down to this fraction
+const MIN_STEP_SCALE: f64 = 1.0 / 64.0;
+
+/// Pivots of the scaled normal equations below this make them singular
+const SINGULAR: f64 = 1e-14;
+
+/// Configuration of `fit_elements`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitOptions {
+    /// Gravity model the elements are fitted for, as they will be propagated with it
+    pub model: WGSModel,
+    pub operation_mode: OperationMode,
+    /// Fit B* along with the elements, otherwise it is kept at `bstar`
+    pub fit_bstar: bool,
+    /// B* in inverse earth radii, or its initial value if it is fitted
+    pub bstar: f64,
+    pub max_iterations: usize,
+    /// The fit has converged when an iteration improves the RMS residual by less than
+    /// this fraction
+    pub tolerance: f64,
+}
+
+impl Default for FitOptions {
+    /// WGS-72 in improved mode without drag, up to 25 iterations.
+    fn default() -> Self {
+        FitOptions {
+            model: WGSModel::WGS_72,
+            operation_mode: OperationMode::Improved,
+            fit_bstar: false,
+            bstar: 0.0,
+            max_iterations: 25,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// Mean elements fitted to an ephemeris by `fit_elements`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    /// Elements at the requested epoch, without the catalog data of a TLE (number,
+    /// designator, name) which are left to be filled in
+    pub elements: Elements,
+    /// Root mean square of the residuals, with velocities weighted by 100 s, in km
+    pub rms_km: f64,
+    pub iterations: usize,
+}
+
+/// Errors of `fit_elements`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitError {
+    /// Fewer than two samples, which can't determine the elements
+    TooFewSamples,
+    /// The samples don't determine the elements, e.g. of an equatorial orbit, which
+    /// has no node, or aren't on an elliptical orbit
+    Singular,
+    /// The initial elements couldn't be initialized or propagated to the samples
+    Propagation(PropagationError),
+    /// The fit didn't converge within `FitOptions::max_iterations`
+    NotConverged { rms_km: f64 },
+}
+
+impl From<PropagationError> for FitError {
+    fn from(error: PropagationError) -> Self {
+        FitError::Propagation(error)
+    }
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitError::TooFewSamples => write!(f, "at least two samples are needed for a fit"),
+            FitError::Singular => write!(f, "the samples don't determine the elements"),
+            FitError::Propagation(error) => write!(f, "{error}"),
+            FitError::NotConverged { rms_km } => {
+                write!(
+                    f,
+                    "the fit didn't converge, with residuals of {rms_km} km RMS"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FitError::Propagation(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Fit SGP4 mean elements at `epoch` to TEME states, e.g. samples of a numerical
+/// ephemeris, by differential correction, so the elements can be published as a TLE
+/// (see `From<&Elements> for TLE`).
+///
+/// The fit starts from the osculating elements of the sample nearest to `epoch` and
+/// improves them by Gauss-Newton iterations on the position and velocity residuals of
+/// all samples, with the partials found by central differences in the same
+/// nonsingular elements as `stm::state_transition_matrix`. The samples should span at
+/// least a revolution, and not so long that SGP4 can't follow the ephemeris.
+pub fn fit_elements(
+    samples: &[State],
+    epoch: Epoch,
+    options: &FitOptions,
+) -> Result<Fit, FitError> {
+    if samples.len() < 2 {
+        return Err(FitError::TooFewSamples);
+    }
+    let parameters = if options.fit_bstar { 7 } else { 6 };
+    let mut elements = initial_elements(samples, epoch, options).ok_or(FitError::Singular)?;
+    let mut residuals = residuals_of(&elements, samples, options)?;
+    let mut rms_km = rms(&residuals);
+    for iteration in 1..=options.max_iterations {
+        let correction = correction(&elements, &residuals, samples, options, parameters)?;
+        // halve the correction while it makes the fit worse
+        let mut scale = 1.0;
+        let improved = loop {
+            let mut candidate = elements.clone();
+            for (j, delta) in correction[..parameters].iter().enumerate() {
+                candidate = perturb_fit(&candidate, j, scale * delta);
+            }
+            if let Ok(candidate_residuals) = residuals_of(&candidate, samples, options) {
+                let candidate_rms = rms(&candidate_residuals);
+                if candidate_rms <= rms_km {
+                    break Some((candidate, candidate_residuals, candidate_rms));
+                }
+            }
+            scale *= 0.5;
+            if scale < MIN_STEP_SCALE {
+                break None;
+            }
+        };
+        // without an improvement the fit is at the minimum already
+        let Some((candidate, candidate_residuals, candidate_rms)) = improved else {
+            return Ok(Fit {
+                elements,
+                rms_km,
+                iterations: iteration,
+            });
+        };
+        let converged = rms_km - candidate_rms <= options.tolerance * rms_km;
+        (elements, residuals, rms_km) = (candidate, candidate_residuals, candidate_rms);
+        if converged {
+            return Ok(Fit {
+                elements,
+                rms_km,
+                iterations: iteration,
+            });
+        }
+    }
+    Err(FitError::NotConverged { rms_km })
+}
+
+/// Osculating elements of the sample nearest to `epoch`, with the mean anomaly moved
+/// to `epoch` on a Keplerian orbit.
+fn initial_elements(samples: &[State], epoch: Epoch, options: &FitOptions) -> Option<Elements> {
+    let grav_const = get_grav_c(&options.model);
+    let nearest = samples
+        .iter()
+        .min_by(|a, b| (a.epoch - epoch).abs().total_cmp(&(b.epoch - epoch).abs()))?;
+    let coe = rv2coe(nearest.position_km, nearest.velocity_km_s, grav_const.mu)?;
+    if coe.eccentricity >= 1.0 {
+        return None;
+    }
+    // take the osculating mean motion as the Brouwer one SGP4 propagates with
+    let no_unkozai = (grav_const.mu / coe.semimajor_axis.powi(3)).sqrt() * 60.0;
+    let no_kozai = brouwer_to_kozai(
+        no_unkozai,
+        coe.eccentricity,
+        coe.inclination,
+        &options.model,
+    );
+    let mo = coe.mean_anomaly? + no_kozai * epoch.minutes_since(nearest.epoch);
+    let (year, mdhms) = epoch.to_calendar();
+    Some(Elements {
+        epochyr: year,
+        epochdays: mdh2days(year, mdhms),
+        epoch,
+        bstar: options.bstar,
+        inclo: coe.inclination,
+        nodeo: coe.raan.unwrap_or(0.0),
+        ecco: coe.eccentricity,
+        argpo: coe
+            .arg_of_perigee
+            .or(coe.longitude_of_periapsis)
+            .unwrap_or(0.0),
+        mo: mo.rem_euclid(TWOPI),
+        no_kozai,
+        ..Elements::default()
+    })
+}
+
+/// Differences of the samples from the states propagated with `elements`, three
+/// position and three weighted velocity components per sample.
+fn residuals_of(
+    elements: &Elements,
+    samples: &[State],
+    options: &FitOptions,
+) -> Result<Vec<f64>, PropagationError> {
+    let sgp4 = SGP4::from_elements(options.model, options.operation_mode, elements)?;
+    let mut residuals = Vec::with_capacity(6 * samples.len());
+    for sample in samples {
+        let state = sgp4
+            .propagate(sample.epoch.minutes_since(elements.epoch))
+            .map_err(|failure| failure.error)?;
+        for i in 0..3 {
+            residuals.push(sample.position_km[i] - state.position_km[i]);
+        }
+        for i in 0..3 {
+            residuals.push((sample.velocity_km_s[i] - state.velocity_km_s[i]) * VELOCITY_WEIGHT_S);
+        }
+    }
+    Ok(residuals)
+}
+
+fn rms(residuals: &[f64]) -> f64 {
+    (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt()
+}
+
+/// Gauss-Newton correction of the first `parameters` fitted elements, solving the
+/// normal equations of the partials of the propagated states.
+fn correction(
+    elements: &Elements,
+    residuals: &[f64],
+    samples: &[State],
+    options: &FitOptions,
+    parameters: usize,
+) -> Result<[f64; 7], FitError> {
+    let steps: [f64; 7] = array::from_fn(|j| if j == 6 { BSTAR_STEP } else { STEPS[j] });
+    let mut partials = Vec::with_capacity(parameters);
+    for (j, &step) in steps[..parameters].iter().enumerate() {
+        let plus = residuals_of(&perturb_fit(elements, j, step), samples, options)?;
+        let minus = residuals_of(&perturb_fit(elements, j, -step), samples, options)?;
+        // the residuals are the samples less the states, so their change is negated
+        let column: Vec<f64> = minus
+            .iter()
+            .zip(&plus)
+            .map(|(minus, plus)| (minus - plus) / (2.0 * step))
+            .collect();
+        partials.push(column);
+    }
+
+    let mut normal = [[0.0; 7]; 7];
+    let mut rhs = [0.0; 7];
+    for (i, column) in partials.iter().enumerate() {
+        for (j, other) in partials.iter().enumerate() {
+            normal[i][j] = column.iter().zip(other).map(|(a, b)| a * b).sum();
+        }
+        rhs[i] = column.iter().zip(residuals).map(|(a, r)| a * r).sum();
+    }
+    solve(normal, rhs, parameters).ok_or(FitError::Singular)
+}
+
+/// Elements with the fitted element `j` changed by `delta`: the nonsingular elements of
+/// `stm::perturb`, then B*.
+fn perturb_fit(elements: &Elements, j: usize, delta: f64) -> Elements {
+    if j == 6 {
+        let mut elements = elements.clone();
+        elements.bstar += delta;
+        elements
+    } else {
+        perturb(elements, j, delta)
+    }
+}
+
+/// Solve the first `n` normal equations by Gaussian elimination with partial pivoting,
+/// or `None` if they are singular. The equations are scaled to a unit diagonal first,
+/// as the elements differ by orders of magnitude in their effect.
+fn solve(mut a: [[f64; 7]; 7], mut b: [f64; 7], n: usize) -> Option<[f64; 7]> {
+    let mut scale = [0.0; 7];
+    for (i, scale) in scale[..n].iter_mut().enumerate() {
+        if a[i][i] <= 0.0 {
+            return None;
+        }
+        *scale = 1.0 / a[i][i].sqrt();
+    }
+    for i in 0..n {
+        for j in 0..n {
+            a[i][j] *= scale[i] * scale[j];
+        }
+        b[i] *= scale[i];
+    }
+    for column in 0..n {
+        let pivot =
+            (column..n).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < SINGULAR {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let pivot_row = a[column];
+        for row in column + 1..n {
+            let factor = a[row][column] / pivot_row[column];
+            for (value, pivot) in a[row][column..n].iter_mut().zip(&pivot_row[column..n]) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+    let mut x = [0.0; 7];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    for (x, scale) in x.iter_mut().zip(scale) {
+        *x *= scale;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::tle::TLE;
+
+    #[test]
+    fn test_fit_elements() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let expected = sgp4.elements();
+        let samples: Vec<State> = sgp4
+            .iter(-360.0, 720.0, 10.0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        // the osculating elements are refined to the mean elements that made the states
+        let options = FitOptions {
+            bstar: expected.bstar,
+            ..FitOptions::default()
+        };
+        let fit = fit_elements(&samples, sgp4.epoch(), &options).unwrap();
+        assert!(fit.rms_km < 1e-4, "{fit:?}");
+        assert!((fit.elements.no_kozai / expected.no_kozai - 1.0).abs() < 1e-9);
+        assert!((fit.elements.inclo - expected.inclo).abs() < 1e-8);
+        assert!((fit.elements.nodeo - expected.nodeo).abs() < 1e-8);
+        assert!((fit.elements.ecco - expected.ecco).abs() < 1e-8);
+        assert_eq!((fit.elements.epochyr, fit.elements.epochdays), (2024, 1.5));
+
+        // B* too
+        let options = FitOptions {
+            fit_bstar: true,
+            bstar: 0.0,
+            ..FitOptions::default()
+        };
+        let fit = fit_elements(&samples, sgp4.epoch(), &options).unwrap();
+        assert!(fit.rms_km < 1e-3, "{fit:?}");
+        assert!((fit.elements.bstar / expected.bstar - 1.0).abs() < 1e-3);
+
+        // a TLE of the fit reproduces the ephemeris to its rounding
+        let (line1, line2) = TLE::try_from(&fit.elements).unwrap().to_lines().unwrap();
+        let tle = TLE::new(line1, line2).unwrap();
+        let refit = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        let state = refit.propagate(720.0).unwrap();
+        let sample = samples.last().unwrap();
+        for i in 0..3 {
+            assert!((state.position_km[i] - sample.position_km[i]).abs() < 0.5);
+        }
+
+        assert_eq!(
+            fit_elements(&samples[..1], sgp4.epoch(), &options),
+            Err(FitError::TooFewSamples)
+        );
+    }
+}
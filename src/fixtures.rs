@@ -0,0 +1,40 @@
+//! Element sets shared by the tests.
+
+use crate::sgp4::{OperationMode, SGP4};
+use crate::tle::TLE;
+use crate::utils::WGSModel;
+
+/// First line of an element set of the ISS, a near earth satellite
+pub(crate) const ISS_LINE1: &str =
+    "1 25544U 98067A   24001.50000000  .00016717  00000-0  30292-3 0  9993";
+/// Second line of the ISS element set
+pub(crate) const ISS_LINE2: &str =
+    "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.50074720432548";
+
+/// First line of an element set of Vanguard 1, the first satellite of the verification
+/// cases of the reference implementation
+pub(crate) const VANGUARD_LINE1: &str =
+    "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+/// Second line of the Vanguard 1 element set
+pub(crate) const VANGUARD_LINE2: &str =
+    "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+/// The ISS element set, parsed
+pub(crate) fn iss_tle() -> TLE {
+    TLE::new(ISS_LINE1.into(), ISS_LINE2.into()).unwrap()
+}
+
+/// The ISS initialized with the constants of `model` in improved mode
+pub(crate) fn iss(model: WGSModel) -> SGP4 {
+    SGP4::from_tle(model, OperationMode::Improved, iss_tle()).unwrap()
+}
+
+/// The Vanguard 1 element set, parsed
+pub(crate) fn vanguard_tle() -> TLE {
+    TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap()
+}
+
+/// Vanguard 1 initialized with the constants of `model` in improved mode
+pub(crate) fn vanguard(model: WGSModel) -> SGP4 {
+    SGP4::from_tle(model, OperationMode::Improved, vanguard_tle()).unwrap()
+}
@@ -0,0 +1,654 @@
+use crate::constants::*;
+use crate::eop::{Eop, EopProvider};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::gstime;
+use crate::utils::radians;
+
+type Matrix3 = [[f64; 3]; 3];
+
+/// Number of terms of the full IAU-1980 nutation series.
+pub const NUTATION_TERMS: usize = 106;
+
+/// IAU-1980 nutation series: multipliers of the fundamental arguments (l, l', F, D,
+/// Omega), then the longitude sine coefficient and its rate, and the obliquity cosine
+/// coefficient and its rate, in units of 0.1 mas (per Julian century).
+#[rustfmt::skip]
+const NUT80: [([i8; 5], [f64; 4]); NUTATION_TERMS] = [
+    ([0, 0, 0, 0, 1], [-171996.0, -174.2, 92025.0, 8.9]),
+    ([0, 0, 0, 0, 2], [2062.0, 0.2, -895.0, 0.5]),
+    ([-2, 0, 2, 0, 1], [46.0, 0.0, -24.0, 0.0]),
+    ([2, 0, -2, 0, 0], [11.0, 0.0, 0.0, 0.0]),
+    ([-2, 0, 2, 0, 2], [-3.0, 0.0, 1.0, 0.0]),
+    ([1, -1, 0, -1, 0], [-3.0, 0.0, 0.0, 0.0]),
+    ([0, -2, 2, -2, 1], [-2.0, 0.0, 1.0, 0.0]),
+    ([2, 0, -2, 0, 1], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 2, -2, 2], [-13187.0, -1.6, 5736.0, -3.1]),
+    ([0, 1, 0, 0, 0], [1426.0, -3.4, 54.0, -0.1]),
+    ([0, 1, 2, -2, 2], [-517.0, 1.2, 224.0, -0.6]),
+    ([0, -1, 2, -2, 2], [217.0, -0.5, -95.0, 0.3]),
+    ([0, 0, 2, -2, 1], [129.0, 0.1, -70.0, 0.0]),
+    ([2, 0, 0, -2, 0], [48.0, 0.0, 1.0, 0.0]),
+    ([0, 0, 2, -2, 0], [-22.0, 0.0, 0.0, 0.0]),
+    ([0, 2, 0, 0, 0], [17.0, -0.1, 0.0, 0.0]),
+    ([0, 1, 0, 0, 1], [-15.0, 0.0, 9.0, 0.0]),
+    ([0, 2, 2, -2, 2], [-16.0, 0.1, 7.0, 0.0]),
+    ([0, -1, 0, 0, 1], [-12.0, 0.0, 6.0, 0.0]),
+    ([-2, 0, 0, 2, 1], [-6.0, 0.0, 3.0, 0.0]),
+    ([0, -1, 2, -2, 1], [-5.0, 0.0, 3.0, 0.0]),
+    ([2, 0, 0, -2, 1], [4.0, 0.0, -2.0, 0.0]),
+    ([0, 1, 2, -2, 1], [4.0, 0.0, -2.0, 0.0]),
+    ([1, 0, 0, -1, 0], [-4.0, 0.0, 0.0, 0.0]),
+    ([2, 1, 0, -2, 0], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, -2, 2, 1], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, -2, 2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 0, 0, 2], [1.0, 0.0, 0.0, 0.0]),
+    ([-1, 0, 0, 1, 1], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 2, -2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 2, 0, 2], [-2274.0, -0.2, 977.0, -0.5]),
+    ([1, 0, 0, 0, 0], [712.0, 0.1, -7.0, 0.0]),
+    ([0, 0, 2, 0, 1], [-386.0, -0.4, 200.0, 0.0]),
+    ([1, 0, 2, 0, 2], [-301.0, 0.0, 129.0, -0.1]),
+    ([1, 0, 0, -2, 0], [-158.0, 0.0, -1.0, 0.0]),
+    ([-1, 0, 2, 0, 2], [123.0, 0.0, -53.0, 0.0]),
+    ([0, 0, 0, 2, 0], [63.0, 0.0, -2.0, 0.0]),
+    ([1, 0, 0, 0, 1], [63.0, 0.1, -33.0, 0.0]),
+    ([-1, 0, 0, 0, 1], [-58.0, -0.1, 32.0, 0.0]),
+    ([-1, 0, 2, 2, 2], [-59.0, 0.0, 26.0, 0.0]),
+    ([1, 0, 2, 0, 1], [-51.0, 0.0, 27.0, 0.0]),
+    ([0, 0, 2, 2, 2], [-38.0, 0.0, 16.0, 0.0]),
+    ([2, 0, 0, 0, 0], [29.0, 0.0, -1.0, 0.0]),
+    ([1, 0, 2, -2, 2], [29.0, 0.0, -12.0, 0.0]),
+    ([2, 0, 2, 0, 2], [-31.0, 0.0, 13.0, 0.0]),
+    ([0, 0, 2, 0, 0], [26.0, 0.0, -1.0, 0.0]),
+    ([-1, 0, 2, 0, 1], [21.0, 0.0, -10.0, 0.0]),
+    ([-1, 0, 0, 2, 1], [16.0, 0.0, -8.0, 0.0]),
+    ([1, 0, 0, -2, 1], [-13.0, 0.0, 7.0, 0.0]),
+    ([-1, 0, 2, 2, 1], [-10.0, 0.0, 5.0, 0.0]),
+    ([1, 1, 0, -2, 0], [-7.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 2, 0, 2], [7.0, 0.0, -3.0, 0.0]),
+    ([0, -1, 2, 0, 2], [-7.0, 0.0, 3.0, 0.0]),
+    ([1, 0, 2, 2, 2], [-8.0, 0.0, 3.0, 0.0]),
+    ([1, 0, 0, 2, 0], [6.0, 0.0, 0.0, 0.0]),
+    ([2, 0, 2, -2, 2], [6.0, 0.0, -3.0, 0.0]),
+    ([0, 0, 0, 2, 1], [-6.0, 0.0, 3.0, 0.0]),
+    ([0, 0, 2, 2, 1], [-7.0, 0.0, 3.0, 0.0]),
+    ([1, 0, 2, -2, 1], [6.0, 0.0, -3.0, 0.0]),
+    ([0, 0, 0, -2, 1], [-5.0, 0.0, 3.0, 0.0]),
+    ([1, -1, 0, 0, 0], [5.0, 0.0, 0.0, 0.0]),
+    ([2, 0, 2, 0, 1], [-5.0, 0.0, 3.0, 0.0]),
+    ([0, 1, 0, -2, 0], [-4.0, 0.0, 0.0, 0.0]),
+    ([1, 0, -2, 0, 0], [4.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 0, 1, 0], [-4.0, 0.0, 0.0, 0.0]),
+    ([1, 1, 0, 0, 0], [-3.0, 0.0, 0.0, 0.0]),
+    ([1, 0, 2, 0, 0], [3.0, 0.0, 0.0, 0.0]),
+    ([1, -1, 2, 0, 2], [-3.0, 0.0, 1.0, 0.0]),
+    ([-1, -1, 2, 2, 2], [-3.0, 0.0, 1.0, 0.0]),
+    ([-2, 0, 0, 0, 1], [-2.0, 0.0, 1.0, 0.0]),
+    ([3, 0, 2, 0, 2], [-3.0, 0.0, 1.0, 0.0]),
+    ([0, -1, 2, 2, 2], [-3.0, 0.0, 1.0, 0.0]),
+    ([1, 1, 2, 0, 2], [2.0, 0.0, -1.0, 0.0]),
+    ([-1, 0, 2, -2, 1], [-2.0, 0.0, 1.0, 0.0]),
+    ([2, 0, 0, 0, 1], [2.0, 0.0, -1.0, 0.0]),
+    ([1, 0, 0, 0, 2], [-2.0, 0.0, 1.0, 0.0]),
+    ([3, 0, 0, 0, 0], [2.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 2, 1, 2], [2.0, 0.0, -1.0, 0.0]),
+    ([-1, 0, 0, 0, 2], [1.0, 0.0, -1.0, 0.0]),
+    ([1, 0, 0, -4, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([-2, 0, 2, 2, 2], [1.0, 0.0, -1.0, 0.0]),
+    ([-1, 0, 2, 4, 2], [-2.0, 0.0, 1.0, 0.0]),
+    ([2, 0, 0, -4, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([1, 1, 2, -2, 2], [1.0, 0.0, -1.0, 0.0]),
+    ([1, 0, 2, 2, 1], [-1.0, 0.0, 1.0, 0.0]),
+    ([-2, 0, 2, 4, 2], [-1.0, 0.0, 1.0, 0.0]),
+    ([-1, 0, 4, 0, 2], [1.0, 0.0, 0.0, 0.0]),
+    ([1, -1, 0, -2, 0], [1.0, 0.0, 0.0, 0.0]),
+    ([2, 0, 2, -2, 1], [1.0, 0.0, -1.0, 0.0]),
+    ([2, 0, 2, 2, 2], [-1.0, 0.0, 0.0, 0.0]),
+    ([1, 0, 0, 2, 1], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 4, -2, 2], [1.0, 0.0, 0.0, 0.0]),
+    ([3, 0, 2, -2, 2], [1.0, 0.0, 0.0, 0.0]),
+    ([1, 0, 2, -2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 2, 0, 1], [1.0, 0.0, 0.0, 0.0]),
+    ([-1, -1, 0, 2, 1], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, -2, 0, 1], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 2, -1, 2], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 0, 2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([1, 0, -2, -2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, -1, 2, 0, 1], [-1.0, 0.0, 0.0, 0.0]),
+    ([1, 1, 0, -2, 1], [-1.0, 0.0, 0.0, 0.0]),
+    ([1, 0, -2, 2, 0], [-1.0, 0.0, 0.0, 0.0]),
+    ([2, 0, 0, 2, 0], [1.0, 0.0, 0.0, 0.0]),
+    ([0, 0, 2, 4, 2], [-1.0, 0.0, 0.0, 0.0]),
+    ([0, 1, 0, 1, 0], [1.0, 0.0, 0.0, 0.0]),
+];
+
+/// Indices of the terms of `NUT80` by decreasing amplitude, so the series can be
+/// truncated to its largest terms.
+const NUT80_ORDER: [u8; NUTATION_TERMS] = nut80_order();
+
+/// Nutation of the earth's axis at a time, from `nutation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nutation {
+    /// Nutation in longitude in radians
+    pub longitude: f64,
+    /// Nutation in obliquity in radians
+    pub obliquity: f64,
+    /// Mean obliquity of the ecliptic (IAU-76) in radians
+    pub mean_obliquity: f64,
+}
+
+impl Nutation {
+    /// True obliquity of the ecliptic in radians
+    pub fn true_obliquity(&self) -> f64 {
+        self.mean_obliquity + self.obliquity
+    }
+
+    /// Equation of the equinoxes in radians, without the kinematic terms as in the
+    /// TEME definition: the angle from the mean to the true equinox along the equator.
+    pub fn equation_of_equinoxes(&self) -> f64 {
+        (self.longitude * self.mean_obliquity.cos()) % TWOPI
+    }
+
+    /// Rotation matrix from the true equator and equinox of date to the mean equator
+    /// and equinox of date.
+    pub fn matrix(&self) -> [[f64; 3]; 3] {
+        mat_mul(
+            &mat_mul(&rot1(-self.mean_obliquity), &rot3(self.longitude)),
+            &rot1(self.true_obliquity()),
+        )
+    }
+
+    /// `matrix` as a nalgebra rotation.
+    #[cfg(feature = "nalgebra")]
+    pub fn rotation(&self) -> nalgebra::Rotation3<f64> {
+        to_rotation(&self.matrix())
+    }
+}
+
+/// The rotation between TEME and J2000 at a time, with the precession and nutation
+/// computed once for all the states converted at that time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrecessionNutation {
+    matrix: Matrix3,
+}
+
+impl PrecessionNutation {
+    /// Rotation at `jd`, the Julian date in TT, with the largest `terms` terms of the
+    /// nutation series (at most `NUTATION_TERMS`).
+    ///
+    /// In low earth orbit, the 4 largest terms are within a meter of the full series
+    /// and a few tens of terms within a decimeter: both well below the errors of SGP4
+    /// states, and of the IAU-1980 model itself without the EOP corrections.
+    pub fn new(jd: f64, terms: usize) -> PrecessionNutation {
+        // julian centuries of TT from J2000.0
+        let ttt = (jd - J2000) / CENT2DAY;
+        let nutation = nutation(ttt, terms);
+
+        // TEME to true of date through the equation of the equinoxes, then to mean of
+        // date and J2000
+        let matrix = mat_mul(
+            &mat_mul(&precession(ttt), &nutation.matrix()),
+            &rot3(-nutation.equation_of_equinoxes()),
+        );
+        PrecessionNutation { matrix }
+    }
+
+    /// Rotation matrix from TEME to J2000
+    pub fn matrix(&self) -> [[f64; 3]; 3] {
+        self.matrix
+    }
+
+    /// Rotation from TEME to J2000 as a nalgebra rotation; its inverse rotates J2000 to
+    /// TEME.
+    #[cfg(feature = "nalgebra")]
+    pub fn rotation(&self) -> nalgebra::Rotation3<f64> {
+        to_rotation(&self.matrix)
+    }
+
+    /// Rotate a TEME position and velocity to J2000, see `teme_to_j2000`.
+    pub fn teme_to_j2000(&self, r: [f64; 3], v: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+        (mat_vec(&self.matrix, r), mat_vec(&self.matrix, v))
+    }
+
+    /// Rotate a J2000 position and velocity to TEME, see `j2000_to_teme`.
+    pub fn j2000_to_teme(&self, r: [f64; 3], v: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+        let rotation = transpose(&self.matrix);
+        (mat_vec(&rotation, r), mat_vec(&rotation, v))
+    }
+}
+
+/// Rotate a position and velocity from the True Equator Mean Equinox (TEME) frame used
+/// by SGP4 to the J2000 (mean equator and equinox of J2000.0, FK5) frame.
+///
+/// The rotation follows the IAU-76/FK5 reduction: the equation of the equinoxes (without
+/// the kinematic terms, as in the TEME definition), the IAU-1980 nutation and the
+/// IAU-76 precession, with the full nutation series. `jd` is the Julian date of the
+/// state in TT, though UTC is close enough for SGP4 accuracy. The frame bias between
+/// J2000 and GCRF (about 20 mas) is neglected. To truncate the nutation series or
+/// convert many states at the same time, use `PrecessionNutation`.
+pub fn teme_to_j2000(r: [f64; 3], v: [f64; 3], jd: f64) -> ([f64; 3], [f64; 3]) {
+    PrecessionNutation::new(jd, NUTATION_TERMS).teme_to_j2000(r, v)
+}
+
+/// Rotate a position and velocity from the J2000 frame to TEME, the inverse of
+/// `teme_to_j2000`.
+pub fn j2000_to_teme(r: [f64; 3], v: [f64; 3], jd: f64) -> ([f64; 3], [f64; 3]) {
+    PrecessionNutation::new(jd, NUTATION_TERMS).j2000_to_teme(r, v)
+}
+
+/// Geodetic coordinates on the WGS-84 ellipsoid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodetic {
+    /// Geodetic latitude in radians
+    pub latitude: f64,
+    /// East longitude in radians, in (-pi, pi]
+    pub longitude: f64,
+    /// Height above the ellipsoid in km
+    pub altitude: f64,
+}
+
+/// Rotate a position and velocity from TEME to the earth fixed frame by the Greenwich
+/// mean sidereal time at `jd_ut1`, the Julian date in UT1 (UTC is adequate).
+///
+/// Polar motion is neglected, so the result is the pseudo earth fixed (PEF) frame,
+/// within about 10 m of ITRF; `teme_to_itrf` takes it into account. To convert many
+/// states at the same time, compute the rotation once with `EarthRotation`.
+pub fn teme_to_ecef(r: [f64; 3], v: [f64; 3], jd_ut1: f64) -> ([f64; 3], [f64; 3]) {
+    EarthRotation::new(jd_ut1).teme_to_ecef(r, v)
+}
+
+/// Rotate a position and velocity from TEME to ITRF at `jd_utc`, the Julian date in
+/// UTC, with the earth orientation parameters of `eop` at that time: UT1 - UTC for the
+/// sidereal time, the polar motion and the length of day for the rotation rate.
+///
+/// Without a provider, or where it has no data, the parameters are taken as zero and the
+/// result is the pseudo earth fixed frame of `teme_to_ecef`.
+pub fn teme_to_itrf(
+    r: [f64; 3],
+    v: [f64; 3],
+    jd_utc: f64,
+    eop: Option<&dyn EopProvider>,
+) -> ([f64; 3], [f64; 3]) {
+    let eop = eop
+        .and_then(|provider| provider.eop(jd_utc))
+        .unwrap_or_default();
+    EarthRotation::with_eop(jd_utc, &eop).teme_to_ecef(r, v)
+}
+
+/// The rotation from TEME to the earth fixed frame at a time, with the sidereal time
+/// and its sine and cosine computed once for all the states converted at that time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EarthRotation {
+    gmst: f64,
+    matrix: Matrix3,
+    polar_motion: Matrix3,
+    rate: f64,
+}
+
+impl EarthRotation {
+    /// Rotation at `jd_ut1`, the Julian date in UT1 (UTC is adequate), to the pseudo
+    /// earth fixed frame
+    pub fn new(jd_ut1: f64) -> EarthRotation {
+        EarthRotation::with_eop(jd_ut1, &Eop::default())
+    }
+
+    /// Rotation at `jd_utc`, the Julian date in UTC, to ITRF with the earth orientation
+    /// parameters at that time (see `teme_to_itrf`)
+    pub fn with_eop(jd_utc: f64, eop: &Eop) -> EarthRotation {
+        let gmst = gstime(jd_utc + eop.ut1_utc / DAY2SEC);
+        EarthRotation {
+            gmst,
+            matrix: rot3(gmst),
+            polar_motion: mat_mul(&rot2(-eop.xp), &rot1(-eop.yp)),
+            rate: EARTHROT * (1.0 - eop.lod / DAY2SEC),
+        }
+    }
+
+    /// Greenwich mean sidereal time in radians
+    pub fn gmst(&self) -> f64 {
+        self.gmst
+    }
+
+    /// Rotation of positions from TEME to the earth fixed frame as a nalgebra rotation,
+    /// including the polar motion. Velocities also need the rotation of the frame
+    /// removed, as `teme_to_ecef` does.
+    #[cfg(feature = "nalgebra")]
+    pub fn rotation(&self) -> nalgebra::Rotation3<f64> {
+        to_rotation(&mat_mul(&self.polar_motion, &self.matrix))
+    }
+
+    /// Rotate a TEME position and velocity to the earth fixed frame, see `teme_to_ecef`.
+    pub fn teme_to_ecef(&self, r: [f64; 3], v: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+        let r_ecef = mat_vec(&self.matrix, r);
+        let v_ecef = mat_vec(&self.matrix, v);
+
+        // remove the velocity of the rotating frame
+        let v_ecef = [
+            v_ecef[0] + self.rate * r_ecef[1],
+            v_ecef[1] - self.rate * r_ecef[0],
+            v_ecef[2],
+        ];
+        (
+            mat_vec(&self.polar_motion, r_ecef),
+            mat_vec(&self.polar_motion, v_ecef),
+        )
+    }
+}
+
+/// Convert an earth fixed position in km to WGS-84 geodetic coordinates.
+///
+/// The latitude is found by iteration, converging to well below a millimeter in a few
+/// steps.
+pub fn ecef_to_geodetic(r: [f64; 3]) -> Geodetic {
+    let eccsq = WGS84_FLAT * (2.0 - WGS84_FLAT);
+    let rxy = r[0].hypot(r[1]);
+    let longitude = r[1].atan2(r[0]);
+
+    // start from the geocentric latitude
+    let mut latitude = r[2].atan2(rxy);
+    let mut c = 0.0;
+    for _ in 0..10 {
+        let sinlat = latitude.sin();
+        c = WGS84_RE / (1.0 - eccsq * sinlat * sinlat).sqrt();
+        let previous = latitude;
+        latitude = (r[2] + c * eccsq * sinlat).atan2(rxy);
+        if (latitude - previous).abs() < SMALL {
+            break;
+        }
+    }
+
+    // avoid dividing by the cosine close to the poles
+    let altitude = if HALFPI - latitude.abs() > radians(1.0) {
+        rxy / latitude.cos() - c
+    } else {
+        r[2] / latitude.sin() - c * (1.0 - eccsq)
+    };
+
+    Geodetic {
+        latitude,
+        longitude,
+        altitude,
+    }
+}
+
+/// Convert WGS-84 geodetic coordinates to an earth fixed position in km.
+pub fn geodetic_to_ecef(geodetic: &Geodetic) -> [f64; 3] {
+    let eccsq = WGS84_FLAT * (2.0 - WGS84_FLAT);
+    let (sinlat, coslat) = geodetic.latitude.sin_cos();
+    let (sinlon, coslon) = geodetic.longitude.sin_cos();
+
+    // radius of curvature in the prime vertical
+    let c = WGS84_RE / (1.0 - eccsq * sinlat * sinlat).sqrt();
+    let rxy = (c + geodetic.altitude) * coslat;
+    [
+        rxy * coslon,
+        rxy * sinlon,
+        (c * (1.0 - eccsq) + geodetic.altitude) * sinlat,
+    ]
+}
+
+/// Geodetic coordinates of a TEME position at `jd_ut1`, see `teme_to_ecef`.
+pub fn teme_to_geodetic(r: [f64; 3], jd_ut1: f64) -> Geodetic {
+    let (r_ecef, _) = teme_to_ecef(r, [0.0; 3], jd_ut1);
+    ecef_to_geodetic(r_ecef)
+}
+
+/// IAU-76 precession matrix from the mean equator and equinox of date to J2000, at
+/// `ttt` Julian centuries of TT from J2000.0.
+pub fn precession(ttt: f64) -> [[f64; 3]; 3] {
+    let zeta = ((0.017998 * ttt + 0.30188) * ttt + 2306.2181) * ttt * ARCSEC2RAD;
+    let theta = ((-0.041833 * ttt - 0.42665) * ttt + 2004.3109) * ttt * ARCSEC2RAD;
+    let z = ((0.018203 * ttt + 1.09468) * ttt + 2306.2181) * ttt * ARCSEC2RAD;
+
+    mat_mul(&mat_mul(&rot3(zeta), &rot2(-theta)), &rot3(z))
+}
+
+/// IAU-1980 nutation at `ttt` Julian centuries of TT from J2000.0, from the largest
+/// `terms` terms of the series (at most `NUTATION_TERMS`).
+pub fn nutation(ttt: f64, terms: usize) -> Nutation {
+    let meaneps = (((0.001813 * ttt - 0.00059) * ttt - 46.8150) * ttt + 84381.448) * ARCSEC2RAD;
+
+    // fundamental arguments: arcseconds and whole revolutions per century
+    let argument = |coefs: [f64; 4], revs: f64| {
+        let [c0, c1, c2, c3] = coefs;
+        let arcsec = ((c3 * ttt + c2) * ttt + c1) * ttt + c0;
+        (arcsec * ARCSEC2RAD + (revs * ttt % 1.0) * TWOPI).rem_euclid(TWOPI)
+    };
+    let l = argument([485866.733, 715922.633, 31.310, 0.064], 1325.0);
+    let lp = argument([1287099.804, 1292581.224, -0.577, -0.012], 99.0);
+    let f = argument([335778.877, 295263.137, -13.257, 0.011], 1342.0);
+    let d = argument([1072261.307, 1105601.328, -6.891, 0.019], 1236.0);
+    let omega = argument([450160.280, -482890.539, 7.455, 0.008], -5.0);
+
+    // sum the series from the smallest terms
+    let (mut deltapsi, mut deltaeps) = (0.0, 0.0);
+    for &index in NUT80_ORDER[..terms.min(NUTATION_TERMS)].iter().rev() {
+        let (multipliers, [sp, spt, ce, cet]) = &NUT80[usize::from(index)];
+        let [ml, mlp, mf, md, momega] = multipliers.map(f64::from);
+        let arg = ml * l + mlp * lp + mf * f + md * d + momega * omega;
+        deltapsi += (sp + spt * ttt) * arg.sin();
+        deltaeps += (ce + cet * ttt) * arg.cos();
+    }
+
+    Nutation {
+        longitude: deltapsi * 1e-4 * ARCSEC2RAD,
+        obliquity: deltaeps * 1e-4 * ARCSEC2RAD,
+        mean_obliquity: meaneps,
+    }
+}
+
+const fn nut80_order() -> [u8; NUTATION_TERMS] {
+    let mut order = [0; NUTATION_TERMS];
+    let mut i = 0;
+    while i < NUTATION_TERMS {
+        order[i] = i as u8;
+        i += 1;
+    }
+    // insertion sort, keeping the order of the table for equal amplitudes
+    let mut i = 1;
+    while i < NUTATION_TERMS {
+        let mut j = i;
+        while j > 0 && amplitude(order[j - 1]) < amplitude(order[j]) {
+            (order[j - 1], order[j]) = (order[j], order[j - 1]);
+            j -= 1;
+        }
+        i += 1;
+    }
+    order
+}
+
+/// Amplitude of a term of `NUT80` in longitude and obliquity, in units of 0.1 mas.
+const fn amplitude(index: u8) -> f64 {
+    let [sp, _, ce, _] = NUT80[index as usize].1;
+    sp.abs() + ce.abs()
+}
+
+fn rot1(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+fn rot2(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+fn rot3(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn mat_mul(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut m = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i][j] = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn mat_vec(m: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+#[cfg(feature = "nalgebra")]
+fn to_rotation(m: &Matrix3) -> nalgebra::Rotation3<f64> {
+    nalgebra::Rotation3::from_matrix_unchecked(nalgebra::Matrix3::from_row_slice(m.as_flattened()))
+}
+
+fn transpose(m: &Matrix3) -> Matrix3 {
+    let mut t = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            t[i][j] = m[j][i];
+        }
+    }
+    t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eop::EopTable;
+    use crate::time::jday;
+    use crate::utils::degrees;
+
+    #[test]
+    fn test_teme_to_j2000() {
+        // Vallado, Fundamentals of Astrodynamics and Applications, example 3-15. The
+        // reference includes the EOP nutation corrections, worth about a meter here.
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 52, 32.570009));
+        let r_teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let v_teme = [-4.746131487, 0.785818041, 5.531931288];
+
+        let (r, v) = teme_to_j2000(r_teme, v_teme, jd + jdfrac);
+        let r_expected = [5102.50895790, 6123.01140070, 6378.13692820];
+        let v_expected = [-4.743220157, 0.790536497, 5.533755273];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 2e-3);
+            assert!((v[i] - v_expected[i]).abs() < 2e-6);
+        }
+
+        let (r, v) = j2000_to_teme(r, v, jd + jdfrac);
+        for i in 0..3 {
+            assert!((r[i] - r_teme[i]).abs() < 1e-8);
+            assert!((v[i] - v_teme[i]).abs() < 1e-11);
+        }
+    }
+
+    #[test]
+    fn test_nutation() {
+        // Vallado, example 3-15
+        let nutation = nutation(0.0426236319, NUTATION_TERMS);
+        assert!((degrees(nutation.longitude) + 0.0034108).abs() < 1e-7);
+        assert!((degrees(nutation.obliquity) - 0.0020316).abs() < 1e-7);
+        assert!((degrees(nutation.mean_obliquity) - 23.4387368).abs() < 1e-7);
+        assert!(nutation.true_obliquity() > nutation.mean_obliquity);
+
+        // the terms are sorted by amplitude, the 18.6 year term first
+        assert_eq!(NUT80_ORDER[0], 0);
+        let amplitudes = NUT80_ORDER.map(amplitude);
+        assert!(amplitudes.windows(2).all(|w| w[0] >= w[1]));
+
+        // truncating the series
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 52, 32.570009));
+        let r_teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let (full, _) = teme_to_j2000(r_teme, [0.0; 3], jd + jdfrac);
+        let error = |terms: usize| {
+            let (r, _) =
+                PrecessionNutation::new(jd + jdfrac, terms).teme_to_j2000(r_teme, [0.0; 3]);
+            (0..3).map(|i| (r[i] - full[i]).powi(2)).sum::<f64>().sqrt()
+        };
+        assert_eq!(error(NUTATION_TERMS), 0.0);
+        assert_eq!(error(1000), 0.0);
+        assert!(error(4) < 1e-3);
+        assert!(error(40) < 1e-4);
+        assert!(error(4) > error(40));
+    }
+
+    #[test]
+    fn test_teme_to_ecef() {
+        // Vallado, example 3-15. The position is in the pseudo earth fixed frame, the
+        // velocity in ITRF, which differs by about 1e-5 km/s of polar motion.
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 27.946047));
+        let r_teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let v_teme = [-4.746131487, 0.785818041, 5.531931288];
+
+        let (r, v) = teme_to_ecef(r_teme, v_teme, jd + jdfrac);
+        let r_expected = [-1033.47503130, 7901.30558560, 6380.34453270];
+        let v_expected = [-3.225636520, -2.872451450, 5.531924446];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-5);
+            assert!((v[i] - v_expected[i]).abs() < 2e-5);
+        }
+    }
+
+    #[test]
+    fn test_teme_to_itrf() {
+        // Vallado, example 3-15, with its earth orientation parameters
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        let r_teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let v_teme = [-4.746131487, 0.785818041, 5.531931288];
+        let finals = "\
+04 4 6 53101.00 I -0.140682 0.000091  0.333309 0.000091  I-0.4399619 0.0000124  1.5563 0.0081
+04 4 7 53102.00 I -0.140682 0.000091  0.333309 0.000091  I-0.4399619 0.0000124  1.5563 0.0081
+";
+        let table = EopTable::parse_finals(finals).unwrap();
+
+        let (r, v) = teme_to_itrf(r_teme, v_teme, jd + jdfrac, Some(&table));
+        let r_expected = [-1033.4793830, 7901.2952754, 6380.3565958];
+        let v_expected = [-3.225636520, -2.872451450, 5.531924446];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-5);
+            assert!((v[i] - v_expected[i]).abs() < 2e-8);
+        }
+
+        // without the parameters, the pseudo earth fixed frame at UTC
+        let (r, _) = teme_to_itrf(r_teme, v_teme, jd + jdfrac, None);
+        assert_eq!(r, teme_to_ecef(r_teme, v_teme, jd + jdfrac).0);
+    }
+
+    #[test]
+    #[cfg(feature = "nalgebra")]
+    fn test_nalgebra_rotations() {
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        let r_teme = [5094.18016210, 6127.64465950, 6380.34453270];
+        let vector = nalgebra::Vector3::from(r_teme);
+
+        let precession_nutation = PrecessionNutation::new(jd + jdfrac, NUTATION_TERMS);
+        let (r, _) = precession_nutation.teme_to_j2000(r_teme, [0.0; 3]);
+        let r_j2000 = precession_nutation.rotation() * vector;
+        assert!((r_j2000 - nalgebra::Vector3::from(r)).norm() < 1e-9);
+        let r_back = precession_nutation.rotation().inverse() * r_j2000;
+        assert!((r_back - vector).norm() < 1e-9);
+
+        let eop = Eop {
+            xp: 1e-6,
+            yp: 2e-6,
+            ..Eop::default()
+        };
+        let rotation = EarthRotation::with_eop(jd + jdfrac, &eop);
+        let (r, _) = rotation.teme_to_ecef(r_teme, [0.0; 3]);
+        assert!((rotation.rotation() * vector - nalgebra::Vector3::from(r)).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_ecef_to_geodetic() {
+        // Vallado, example 3-3
+        let geodetic = ecef_to_geodetic([6524.834, 6862.875, 6448.296]);
+        assert!((degrees(geodetic.latitude) - 34.352496).abs() < 1e-5);
+        assert!((degrees(geodetic.longitude) - 46.4464).abs() < 1e-4);
+        assert!((geodetic.altitude - 5085.22).abs() < 1e-2);
+
+        let r = geodetic_to_ecef(&geodetic);
+        assert!((r[0] - 6524.834).abs() < 1e-8);
+        assert!((r[1] - 6862.875).abs() < 1e-8);
+        assert!((r[2] - 6448.296).abs() < 1e-8);
+
+        let pole = ecef_to_geodetic([0.0, 0.0, 7000.0]);
+        assert!((pole.latitude - HALFPI).abs() < 1e-12);
+        assert!((pole.altitude - (7000.0 - WGS84_RE * (1.0 - WGS84_FLAT))).abs() < 1e-8);
+    }
+}
@@ -0,0 +1,120 @@
+//! GeoJSON export of ground tracks and passes, for web maps and GIS tools.
+//!
+//! Features follow RFC 7946: coordinates are longitude and latitude in degrees, and
+//! lines crossing the antimeridian are split there. Times in the properties are ISO
+//! 8601 in UTC.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde_json::{Value, json};
+
+use crate::constants::*;
+use crate::frames::teme_to_geodetic;
+use crate::groundtrack::{GroundTrackPoint, split_at_antimeridian};
+use crate::passes::Pass;
+use crate::sgp4::{PropagationError, SGP4};
+use crate::utils::degrees;
+
+/// LineString features of the ground track of a satellite (see `SGP4::ground_track`),
+/// one per segment between antimeridian crossings, with the satellite number and the
+/// start and stop times of the segment as properties.
+pub fn ground_track_features(sgp4: &SGP4, points: &[GroundTrackPoint]) -> Vec<Value> {
+    split_at_antimeridian(points)
+        .iter()
+        .map(|segment| {
+            let coordinates: Vec<[f64; 2]> = segment.iter().map(coordinates).collect();
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+                "properties": {
+                    "satnum": sgp4.satnum(),
+                    "name": sgp4.name(),
+                    "start": time(sgp4, segment[0].t),
+                    "stop": time(sgp4, segment[segment.len() - 1].t),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Point features at the sub-satellite points of the AOS and LOS of a pass (see
+/// `passes::find_passes`), with the event, its time and the maximum elevation of the
+/// pass in degrees as properties.
+pub fn pass_features(sgp4: &SGP4, pass: &Pass) -> Result<[Value; 2], PropagationError> {
+    let feature = |event: &str, t: f64| -> Result<Value, PropagationError> {
+        let r = sgp4.propagate(t)?.position_km;
+        let geodetic = teme_to_geodetic(r, sgp4.jdepoch() + t / DAY2MIN);
+        let point = [degrees(geodetic.longitude), degrees(geodetic.latitude)];
+        Ok(json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": point },
+            "properties": {
+                "satnum": sgp4.satnum(),
+                "name": sgp4.name(),
+                "event": event,
+                "time": time(sgp4, t),
+                "max_elevation_deg": degrees(pass.max_elevation),
+            },
+        }))
+    };
+    Ok([feature("AOS", pass.aos)?, feature("LOS", pass.los)?])
+}
+
+/// A FeatureCollection of features, e.g. from `ground_track_features` and
+/// `pass_features`. Serialize it with `serde_json::to_string`.
+pub fn feature_collection(features: impl IntoIterator<Item = Value>) -> Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": features.into_iter().collect::<Vec<_>>(),
+    })
+}
+
+fn coordinates(point: &GroundTrackPoint) -> [f64; 2] {
+    [degrees(point.longitude), degrees(point.latitude)]
+}
+
+fn time(sgp4: &SGP4, t: f64) -> String {
+    (sgp4.epoch() + t / DAY2MIN).to_iso8601()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::observer::Observer;
+    use crate::passes::find_passes;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_feature_collection() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let track = sgp4.ground_track(0.0, 190.0, 1.0).unwrap();
+        let observer = Observer::from_degrees(51.4779, -0.0015, 0.05);
+        let pass = find_passes(&sgp4, &observer, 0.0, 1440.0).unwrap()[0];
+
+        let lines = ground_track_features(&sgp4, &track);
+        assert_eq!(lines.len(), split_at_antimeridian(&track).len());
+        assert!(lines.len() >= 2);
+        for line in &lines {
+            let coordinates = line["geometry"]["coordinates"].as_array().unwrap();
+            // no segment jumps across the map
+            for pair in coordinates.windows(2) {
+                let delta = pair[1][0].as_f64().unwrap() - pair[0][0].as_f64().unwrap();
+                assert!(delta.abs() < 180.0);
+            }
+        }
+        assert_eq!(lines[0]["properties"]["start"], "2024-01-01T12:00:00.000Z");
+        assert_eq!(lines[0]["properties"]["satnum"], 25544);
+
+        let [aos, los] = pass_features(&sgp4, &pass).unwrap();
+        assert_eq!(aos["properties"]["event"], "AOS");
+        assert_eq!(los["properties"]["event"], "LOS");
+        assert_eq!(aos["geometry"]["type"], "Point");
+
+        let collection = feature_collection(lines.into_iter().chain([aos, los]));
+        assert_eq!(collection["type"], "FeatureCollection");
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), split_at_antimeridian(&track).len() + 2);
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// A point of a ground track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundTrackPoint {
+    /// Time since epoch in minutes
+    pub t: f64,
+    /// Geodetic latitude in radians
+    pub latitude: f64,
+    /// East longitude in radians, in [-pi, pi]
+    pub longitude: f64,
+    /// Height above the WGS-84 ellipsoid in km
+    pub altitude: f64,
+}
+
+/// Split a ground track into segments that don't cross the antimeridian, so each one
+/// can be drawn as a polyline on a map.
+///
+/// At each crossing the point on the antimeridian is interpolated and added to both
+/// segments, at a longitude of pi on one side and -pi on the other, so the track reaches
+/// the map edges without gaps.
+pub fn split_at_antimeridian(points: &[GroundTrackPoint]) -> Vec<Vec<GroundTrackPoint>> {
+    let mut segments = Vec::new();
+    let mut segment: Vec<GroundTrackPoint> = Vec::new();
+    for point in points {
+        if let Some(previous) = segment.last().copied() {
+            let delta = point.longitude - previous.longitude;
+            if delta.abs() > PI {
+                // eastward crossings go from pi to -pi, westward ones from -pi to pi
+                let edge = if delta < 0.0 { PI } else { -PI };
+                let unwrapped = point.longitude + 2.0 * edge;
+                let fraction = (edge - previous.longitude) / (unwrapped - previous.longitude);
+                let interpolate = |a: f64, b: f64| a + fraction * (b - a);
+                let crossing = GroundTrackPoint {
+                    t: interpolate(previous.t, point.t),
+                    latitude: interpolate(previous.latitude, point.latitude),
+                    longitude: edge,
+                    altitude: interpolate(previous.altitude, point.altitude),
+                };
+                segment.push(crossing);
+                segments.push(segment);
+                segment = vec![GroundTrackPoint {
+                    longitude: -edge,
+                    ..crossing
+                }];
+            }
+        }
+        segment.push(*point);
+    }
+    if !segment.is_empty() {
+        segments.push(segment);
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::radians;
+
+    fn point(t: f64, longitude: f64) -> GroundTrackPoint {
+        GroundTrackPoint {
+            t,
+            latitude: 0.0,
+            longitude: radians(longitude),
+            altitude: 400.0,
+        }
+    }
+
+    #[test]
+    fn test_split_at_antimeridian() {
+        // eastward
+        let segments =
+            split_at_antimeridian(&[point(0.0, 170.0), point(1.0, 178.0), point(2.0, -174.0)]);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].len(), 3);
+        assert_eq!(segments[0][2].longitude, PI);
+        assert!((segments[0][2].t - 1.25).abs() < 1e-12);
+        assert_eq!(segments[1][0].longitude, -PI);
+        assert_eq!(segments[1][1], point(2.0, -174.0));
+
+        // westward
+        let segments = split_at_antimeridian(&[point(0.0, -179.0), point(1.0, 177.0)]);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0][1].longitude, -PI);
+        assert!((segments[0][1].t - 0.25).abs() < 1e-12);
+        assert_eq!(segments[1][0].longitude, PI);
+
+        assert_eq!(
+            split_at_antimeridian(&[point(0.0, 0.0), point(1.0, 10.0)]).len(),
+            1
+        );
+        assert!(split_at_antimeridian(&[]).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use std::io;
+
+/// A response to an HTTP request.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Value of the first header named `name`, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// HTTP transport of the clients for online element sources.
+///
+/// The crate doesn't depend on an HTTP library, so the application can use the one
+/// (and TLS stack) it already has by implementing this trait. Responses with error
+/// statuses are returned as responses; errors are for requests that got no response.
+///
+/// ```ignore
+/// struct Ureq(ureq::Agent);
+///
+/// impl HttpClient for Ureq {
+///     fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> io::Result<HttpResponse> {
+///         let mut request = self.0.get(url);
+///         for (name, value) in headers {
+///             request = request.header(*name, *value);
+///         }
+///         let mut response = request.call().map_err(io::Error::other)?;
+///         ...
+///     }
+///     ...
+/// }
+/// ```
+pub trait HttpClient {
+    /// Send a GET request with the given headers.
+    fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> io::Result<HttpResponse>;
+
+    /// Send a POST request with the given headers and body.
+    fn post(&mut self, url: &str, headers: &[(&str, &str)], body: &str)
+    -> io::Result<HttpResponse>;
+}
+
+/// Percent-encode text for a URL path segment or a form value, keeping only the
+/// unreserved characters of RFC 3986.
+pub(crate) fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// An `HttpClient` answering with canned responses, for the tests of the clients
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+
+    /// A GP record in JSON, as served by Space-Track and Celestrak
+    pub(crate) const GP: &str = r#"[{
+        "OBJECT_NAME": "VANGUARD 1",
+        "OBJECT_ID": "1958-002B",
+        "EPOCH": "2000-06-27T18:50:19.733568",
+        "MEAN_MOTION": 10.82419157,
+        "ECCENTRICITY": 0.1859667,
+        "INCLINATION": 34.2682,
+        "RA_OF_ASC_NODE": 348.7242,
+        "ARG_OF_PERICENTER": 331.7664,
+        "MEAN_ANOMALY": 19.3264,
+        "NORAD_CAT_ID": 5,
+        "ELEMENT_SET_NO": 475,
+        "REV_AT_EPOCH": 41366,
+        "BSTAR": 2.8098e-5,
+        "MEAN_MOTION_DOT": 2.3e-7,
+        "MEAN_MOTION_DDOT": 0
+    }]"#;
+
+    #[derive(Debug)]
+    pub(crate) struct Request {
+        pub(crate) url: String,
+        pub(crate) headers: Vec<(String, String)>,
+        pub(crate) body: Option<String>,
+    }
+
+    /// Answers the requests with `responses` in order, recording them, and fails like
+    /// an offline client once the responses run out
+    #[derive(Debug, Default)]
+    pub(crate) struct MockHttp {
+        pub(crate) responses: Vec<HttpResponse>,
+        pub(crate) requests: Vec<Request>,
+    }
+
+    impl MockHttp {
+        fn respond(
+            &mut self,
+            url: &str,
+            headers: &[(&str, &str)],
+            body: Option<&str>,
+        ) -> io::Result<HttpResponse> {
+            let headers = headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            self.requests.push(Request {
+                url: url.into(),
+                headers,
+                body: body.map(String::from),
+            });
+            if self.responses.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::NotConnected, "offline"));
+            }
+            Ok(self.responses.remove(0))
+        }
+    }
+
+    impl HttpClient for MockHttp {
+        fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> io::Result<HttpResponse> {
+            self.respond(url, headers, None)
+        }
+
+        fn post(
+            &mut self,
+            url: &str,
+            headers: &[(&str, &str)],
+            body: &str,
+        ) -> io::Result<HttpResponse> {
+            self.respond(url, headers, Some(body))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("user@example.com"), "user%40example.com");
+        assert_eq!(percent_encode("p&ss w=rd"), "p%26ss%20w%3Drd");
+        assert_eq!(percent_encode("é"), "%C3%A9");
+    }
+}
@@ -0,0 +1,199 @@
+use core::f64::consts::PI;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::utils::radians;
+
+/// Eccentricities within this of 0 and 1 are taken as circular and parabolic
+const TOLERANCE: f64 = 1.0e-8;
+
+/// Convergence of the Newton iterations in radians
+const NEWTON_TOLERANCE: f64 = 1.0e-12;
+
+const NEWTON_MAX_ITERATIONS: usize = 50;
+
+/// Eccentric anomaly of a true anomaly on an elliptical orbit, in (-pi, pi].
+pub fn true_to_eccentric(ecc: f64, nu: f64) -> f64 {
+    let (sinnu, cosnu) = nu.sin_cos();
+    let sine = ((1.0 - ecc * ecc).sqrt() * sinnu) / (1.0 + ecc * cosnu);
+    let cose = (ecc + cosnu) / (1.0 + ecc * cosnu);
+    sine.atan2(cose)
+}
+
+/// True anomaly of an eccentric anomaly on an elliptical orbit, in (-pi, pi].
+pub fn eccentric_to_true(ecc: f64, e0: f64) -> f64 {
+    let (sine, cose) = e0.sin_cos();
+    let sinnu = ((1.0 - ecc * ecc).sqrt() * sine) / (1.0 - ecc * cose);
+    let cosnu = (cose - ecc) / (1.0 - ecc * cose);
+    sinnu.atan2(cosnu)
+}
+
+/// Mean anomaly of an eccentric anomaly, by Kepler's equation.
+pub fn eccentric_to_mean(ecc: f64, e0: f64) -> f64 {
+    e0 - ecc * e0.sin()
+}
+
+/// Eccentric anomaly of a mean anomaly, solving Kepler's equation by Newton's method.
+///
+/// The start is offset from `m` by the eccentricity towards the apoapsis and each step
+/// is limited to one radian, which converges for all elliptical eccentricities. The
+/// result is in the same revolution as `m`.
+pub fn mean_to_eccentric(ecc: f64, m: f64) -> f64 {
+    let revolutions = ((m + PI) / TWOPI).floor() * TWOPI;
+    let m = m - revolutions;
+    let mut e0 = if m < 0.0 { m - ecc } else { m + ecc };
+    for _ in 0..NEWTON_MAX_ITERATIONS {
+        let step = ((e0 - ecc * e0.sin() - m) / (1.0 - ecc * e0.cos())).clamp(-1.0, 1.0);
+        e0 -= step;
+        if step.abs() < NEWTON_TOLERANCE {
+            break;
+        }
+    }
+    e0 + revolutions
+}
+
+/// Hyperbolic anomaly of a true anomaly, or `None` outside the asymptotes.
+pub fn true_to_hyperbolic(ecc: f64, nu: f64) -> Option<f64> {
+    if nu.abs() >= PI - (1.0 / ecc).acos() {
+        return None;
+    }
+    let sinh = ((ecc * ecc - 1.0).sqrt() * nu.sin()) / (1.0 + ecc * nu.cos());
+    Some(sinh.asinh())
+}
+
+/// True anomaly of a hyperbolic anomaly.
+pub fn hyperbolic_to_true(ecc: f64, h: f64) -> f64 {
+    2.0 * (((ecc + 1.0) / (ecc - 1.0)).sqrt() * (h * 0.5).tanh()).atan()
+}
+
+/// Mean anomaly of a hyperbolic anomaly, by the hyperbolic Kepler equation.
+pub fn hyperbolic_to_mean(ecc: f64, h: f64) -> f64 {
+    ecc * h.sinh() - h
+}
+
+/// Hyperbolic anomaly of a mean anomaly, solving the hyperbolic Kepler equation by
+/// Newton's method from `asinh(m / ecc)`, with each step limited to one.
+pub fn mean_to_hyperbolic(ecc: f64, m: f64) -> f64 {
+    let mut h = (m / ecc).asinh();
+    for _ in 0..NEWTON_MAX_ITERATIONS {
+        let step = ((ecc * h.sinh() - h - m) / (ecc * h.cosh() - 1.0)).clamp(-1.0, 1.0);
+        h -= step;
+        if step.abs() < NEWTON_TOLERANCE {
+            break;
+        }
+    }
+    h
+}
+
+/// Parabolic anomaly (tan(nu / 2)) of a true anomaly.
+pub fn true_to_parabolic(nu: f64) -> f64 {
+    (nu * 0.5).tan()
+}
+
+/// True anomaly of a parabolic anomaly.
+pub fn parabolic_to_true(b: f64) -> f64 {
+    2.0 * b.atan()
+}
+
+/// Mean anomaly of a parabolic anomaly, by Barker's equation.
+pub fn parabolic_to_mean(b: f64) -> f64 {
+    b + b * b * b / 3.0
+}
+
+/// Parabolic anomaly of a mean anomaly, solving Barker's equation in closed form.
+pub fn mean_to_parabolic(m: f64) -> f64 {
+    let s = 0.5 * (HALFPI - (1.5 * m).atan());
+    let w = s.tan().cbrt().atan();
+    2.0 / (2.0 * w).tan()
+}
+
+/// Eccentric (hyperbolic or parabolic) anomaly and mean anomaly of a true anomaly on an
+/// orbit of any eccentricity, like Vallado's newtonnu. The mean anomaly of elliptical
+/// orbits is in [0, 2pi).
+///
+/// Returns `None` for true anomalies a hyperbolic orbit doesn't reach, and for
+/// parabolic ones beyond 168 degrees where Barker's equation loses precision.
+pub fn newtonnu(ecc: f64, nu: f64) -> Option<(f64, f64)> {
+    if ecc.abs() < TOLERANCE {
+        Some((nu, nu))
+    } else if ecc < 1.0 - TOLERANCE {
+        let e0 = true_to_eccentric(ecc, nu);
+        Some((e0, eccentric_to_mean(ecc, e0).rem_euclid(TWOPI)))
+    } else if ecc > 1.0 + TOLERANCE {
+        let h = true_to_hyperbolic(ecc, nu)?;
+        Some((h, hyperbolic_to_mean(ecc, h)))
+    } else if nu.abs() < radians(168.0) {
+        let b = true_to_parabolic(nu);
+        Some((b, parabolic_to_mean(b)))
+    } else {
+        None
+    }
+}
+
+/// Eccentric (hyperbolic or parabolic) anomaly and true anomaly of a mean anomaly on an
+/// orbit of any eccentricity, like Vallado's newtonm. The inverse of `newtonnu`.
+pub fn newtonm(ecc: f64, m: f64) -> (f64, f64) {
+    if ecc.abs() < TOLERANCE {
+        (m, m)
+    } else if ecc < 1.0 - TOLERANCE {
+        let e0 = mean_to_eccentric(ecc, m);
+        (e0, eccentric_to_true(ecc, e0))
+    } else if ecc > 1.0 + TOLERANCE {
+        let h = mean_to_hyperbolic(ecc, m);
+        (h, hyperbolic_to_true(ecc, h))
+    } else {
+        let b = mean_to_parabolic(m);
+        (b, parabolic_to_true(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elliptical() {
+        // Vallado, Fundamentals of Astrodynamics and Applications, example 2-1
+        let e0 = mean_to_eccentric(0.4, radians(235.4));
+        assert!((e0 - 3.8486).abs() < 1e-4);
+
+        for ecc in [0.0, 0.1, 0.7, 0.99] {
+            for m in [-4.0, -0.5, 0.0, 1e-3, 1.0, 3.1, PI, 6.0, 20.0] {
+                let e0 = mean_to_eccentric(ecc, m);
+                assert!((eccentric_to_mean(ecc, e0) - m).abs() < 1e-11);
+                // the same angle, up to whole revolutions
+                let difference = true_to_eccentric(ecc, eccentric_to_true(ecc, e0)) - e0;
+                assert!(difference.sin().abs() < 1e-10 && difference.cos() > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hyperbolic_and_parabolic() {
+        for ecc in [1.1, 2.0, 10.0] {
+            for m in [-50.0, -1.0, 0.0, 0.3, 5.0, 1000.0] {
+                let h = mean_to_hyperbolic(ecc, m);
+                assert!((hyperbolic_to_mean(ecc, h) - m).abs() < 1e-9 * m.abs().max(1.0));
+                let nu = hyperbolic_to_true(ecc, h);
+                assert!((true_to_hyperbolic(ecc, nu).unwrap() - h).abs() < 1e-8);
+            }
+        }
+        assert_eq!(true_to_hyperbolic(2.0, radians(150.0)), None);
+
+        for m in [-3.0, 0.0, 0.2, 10.0] {
+            let b = mean_to_parabolic(m);
+            assert!((parabolic_to_mean(b) - m).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_newtonm() {
+        for (ecc, m) in [(0.0, 1.0), (0.3, 2.0), (1.0, 0.5), (1.5, 0.7)] {
+            let (e0, nu) = newtonm(ecc, m);
+            let (e0_nu, m_nu) = newtonnu(ecc, nu).unwrap();
+            assert!((e0_nu - e0).abs() < 1e-10);
+            assert!((m_nu - m).abs() < 1e-10);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use num_traits::Float;
+
+use crate::sgp4::PropagationError;
+
+/// Near earth SGP4 equations with the coefficients from initialization stored in a
+/// floating point type `T`, e.g. `f32` for GPU or embedded use where throughput and
+/// memory matter more than accuracy.
+///
+/// Built with `SGP4::kernel` once the satellite is initialized; initialization itself
+/// always runs in `f64`. In `f32` the positions drift by up to a few hundred metres
+/// within a day of the epoch as the mean anomaly grows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sgp4Kernel<T> {
+    pub(crate) isimp: bool,
+    pub(crate) mo: T,
+    pub(crate) mdot: T,
+    pub(crate) argpo: T,
+    pub(crate) argpdot: T,
+    pub(crate) nodeo: T,
+    pub(crate) nodedot: T,
+    pub(crate) nodecf: T,
+    pub(crate) cc1: T,
+    pub(crate) cc4: T,
+    pub(crate) cc5: T,
+    pub(crate) bstar: T,
+    pub(crate) omgcof: T,
+    pub(crate) xmcof: T,
+    pub(crate) eta: T,
+    pub(crate) delmo: T,
+    pub(crate) sinmao: T,
+    pub(crate) d2: T,
+    pub(crate) d3: T,
+    pub(crate) d4: T,
+    pub(crate) t2cof: T,
+    pub(crate) t3cof: T,
+    pub(crate) t4cof: T,
+    pub(crate) t5cof: T,
+    pub(crate) no: T,
+    pub(crate) ecco: T,
+    pub(crate) inclo: T,
+    pub(crate) sinio: T,
+    pub(crate) cosio: T,
+    pub(crate) aycof: T,
+    pub(crate) xlcof: T,
+    pub(crate) con41: T,
+    pub(crate) x1mth2: T,
+    pub(crate) x7thm1: T,
+    /// semimajor axis from the mean motion at epoch, in earth radii
+    pub(crate) ao: T,
+    pub(crate) xke: T,
+    pub(crate) j2: T,
+    pub(crate) radiusearthkm: T,
+}
+
+impl<T: Float> Sgp4Kernel<T> {
+    /// Propagate the satellite to a time since epoch in minutes. See `SGP4::propagate`.
+    ///
+    /// Returns the position in km and velocity in km/s in the TEME frame.
+    pub fn propagate(&self, tsince_min: T) -> Result<([T; 3], [T; 3]), PropagationError> {
+        let c = |x: f64| T::from(x).unwrap();
+        let (one, two) = (T::one(), c(2.0));
+        let (half, quarter, three_halves) = (c(0.5), c(0.25), c(1.5));
+        let twopi = c(core::f64::consts::TAU);
+
+        // update for secular gravity and atmospheric drag
+        let t = tsince_min;
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = one - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((one + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe = tempe + self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ = templ + self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let am = self.ao * tempa * tempa;
+        let nm = self.xke / (am * am.sqrt());
+        let mut em = self.ecco - tempe;
+        if em < c(-0.001) || em >= one {
+            return Err(PropagationError::InvalidElements);
+        }
+        em = em.max(c(1.0e-6));
+        mm = mm + self.no * templ;
+        let xlm = (mm + argpm + nodem) % twopi;
+        nodem = nodem % twopi;
+        argpm = argpm % twopi;
+        mm = (xlm - argpm - nodem) % twopi;
+
+        let (sinip, cosip) = (self.sinio, self.cosio);
+
+        // long period periodics
+        let axnl = em * argpm.cos();
+        let temp = one / (am * (one - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // solve kepler's equation
+        let u = (xl - nodem) % twopi;
+        let mut eo1 = u;
+        let mut tem5 = c(9999.9);
+        let mut ktr = 1;
+        let (mut sineo1, mut coseo1) = (T::zero(), T::zero());
+        // stop at the precision of T rather than at 1e-12, which f32 can't reach
+        let tolerance = c(1.0e-12).max(T::epsilon() * two);
+        while tem5.abs() >= tolerance && ktr <= 10 {
+            (sineo1, coseo1) = eo1.sin_cos();
+            tem5 = one - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            tem5 = tem5.max(c(-0.95)).min(c(0.95));
+            eo1 = eo1 + tem5;
+            ktr += 1;
+        }
+
+        // short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (one - el2);
+        if pl < T::zero() {
+            return Err(PropagationError::NegativeSemilatusRectum);
+        }
+
+        let rl = am * (one - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (one - el2).sqrt();
+        let temp = esine / (one + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = one - two * sinu * sinu;
+        let temp = one / pl;
+        let temp1 = half * self.j2 * temp;
+        let temp2 = temp1 * temp;
+
+        // update for short period periodics
+        let mrt = rl * (one - three_halves * temp2 * betal * self.con41)
+            + half * temp1 * self.x1mth2 * cos2u;
+        su = su - quarter * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + three_halves * temp2 * cosip * sin2u;
+        let xinc = self.inclo + three_halves * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / self.xke;
+        let rvdot =
+            rvdotl + nm * temp1 * (self.x1mth2 * cos2u + three_halves * self.con41) / self.xke;
+
+        // orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        // decaying satellites
+        if mrt < one {
+            return Err(PropagationError::OrbitalDecay);
+        }
+
+        // position and velocity (in km and km/sec)
+        let mr = mrt * self.radiusearthkm;
+        let vkmpersec = self.radiusearthkm * self.xke / c(60.0);
+        let r = [mr * ux, mr * uy, mr * uz];
+        let v = [
+            (mvt * ux + rvdot * vx) * vkmpersec,
+            (mvt * uy + rvdot * vy) * vkmpersec,
+            (mvt * uz + rvdot * vz) * vkmpersec,
+        ];
+        Ok((r, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::iss;
+    use crate::sgp4::{OperationMode, SGP4};
+    use crate::tle::TLE;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_kernel_f32() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let kernel64 = sgp4.kernel::<f64>().unwrap();
+        let kernel32 = sgp4.kernel::<f32>().unwrap();
+
+        for t in [0.0, 90.0, 720.0, 1440.0] {
+            let expected = sgp4.propagate(t).unwrap();
+            let (r_expected, v_expected) = (expected.position_km, expected.velocity_km_s);
+            let (r, v) = kernel64.propagate(t).unwrap();
+            let (r32, v32) = kernel32.propagate(t as f32).unwrap();
+            for i in 0..3 {
+                assert!((r[i] - r_expected[i]).abs() < 1e-8);
+                assert!((v[i] - v_expected[i]).abs() < 1e-11);
+                assert!((f64::from(r32[i]) - r_expected[i]).abs() < 1.0);
+                assert!((f64::from(v32[i]) - v_expected[i]).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_deep_space() {
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        assert!(sgp4.kernel::<f32>().is_none());
+    }
+}
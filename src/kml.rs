@@ -0,0 +1,199 @@
+//! KML export of ground tracks and passes, for Google Earth.
+//!
+//! A document is written piece by piece: `write_kml_header`, any number of tracks and
+//! passes, then `write_kml_footer`. Coordinates are in degrees on the WGS-84 ellipsoid
+//! and altitudes in meters, as KML expects.
+
+use alloc::string::{String, ToString};
+use core::fmt::{self, Write};
+
+use crate::constants::*;
+use crate::groundtrack::{GroundTrackPoint, split_at_antimeridian};
+use crate::observer::Observer;
+use crate::passes::Pass;
+use crate::sgp4::SGP4;
+use crate::utils::degrees;
+
+/// Write the start of a KML document named `name`, with the `gx` namespace of the
+/// time-stamped tracks.
+pub fn write_kml_header(out: &mut impl Write, name: &str) -> fmt::Result {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">"#
+    )?;
+    writeln!(out, "<Document>")?;
+    writeln!(out, "<name>{}</name>", Escaped(name))
+}
+
+/// Write the end of a KML document.
+pub fn write_kml_footer(out: &mut impl Write) -> fmt::Result {
+    writeln!(out, "</Document>")?;
+    writeln!(out, "</kml>")
+}
+
+/// Write the ground track of a satellite (see `SGP4::ground_track`) as a placemark of
+/// LineStrings on the ground, split at the antimeridian so Google Earth doesn't draw
+/// them across the globe.
+pub fn write_ground_track(
+    out: &mut impl Write,
+    sgp4: &SGP4,
+    points: &[GroundTrackPoint],
+) -> fmt::Result {
+    writeln!(out, "<Placemark>")?;
+    writeln!(out, "<name>{} ground track</name>", Escaped(&name(sgp4)))?;
+    writeln!(out, "<MultiGeometry>")?;
+    for segment in split_at_antimeridian(points) {
+        write_line_string(out, &segment)?;
+    }
+    writeln!(out, "</MultiGeometry>")?;
+    writeln!(out, "</Placemark>")
+}
+
+/// Write the positions of a satellite over time as a placemark with a `gx:Track`, at
+/// their altitudes, so the time slider of Google Earth animates the satellite.
+pub fn write_timed_track(
+    out: &mut impl Write,
+    sgp4: &SGP4,
+    points: &[GroundTrackPoint],
+) -> fmt::Result {
+    writeln!(out, "<Placemark>")?;
+    writeln!(out, "<name>{}</name>", Escaped(&name(sgp4)))?;
+    writeln!(out, "<gx:Track>")?;
+    writeln!(out, "<altitudeMode>absolute</altitudeMode>")?;
+    for point in points {
+        let when = sgp4.epoch() + point.t / DAY2MIN;
+        writeln!(out, "<when>{}</when>", when.to_iso8601())?;
+    }
+    for point in points {
+        writeln!(
+            out,
+            "<gx:coord>{:.6} {:.6} {:.1}</gx:coord>",
+            degrees(point.longitude),
+            degrees(point.latitude),
+            point.altitude * KM2M
+        )?;
+    }
+    writeln!(out, "</gx:Track>")?;
+    writeln!(out, "</Placemark>")
+}
+
+/// Write a pass of a satellite over an observer (see `passes::find_passes`) as a
+/// placemark of the ground track during the pass, `points` from AOS to LOS, and the
+/// location of the observer, over the time span of the pass.
+pub fn write_pass(
+    out: &mut impl Write,
+    sgp4: &SGP4,
+    observer: &Observer,
+    pass: &Pass,
+    points: &[GroundTrackPoint],
+) -> fmt::Result {
+    let at = |t: f64| (sgp4.epoch() + t / DAY2MIN).to_iso8601();
+    writeln!(out, "<Placemark>")?;
+    writeln!(
+        out,
+        "<name>{} pass {}</name>",
+        Escaped(&name(sgp4)),
+        at(pass.aos)
+    )?;
+    writeln!(
+        out,
+        "<description>maximum elevation {:.1} deg at {}</description>",
+        degrees(pass.max_elevation),
+        at(pass.culmination)
+    )?;
+    writeln!(
+        out,
+        "<TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>",
+        at(pass.aos),
+        at(pass.los)
+    )?;
+    writeln!(out, "<MultiGeometry>")?;
+    for segment in split_at_antimeridian(points) {
+        write_line_string(out, &segment)?;
+    }
+    let location = observer.location();
+    writeln!(
+        out,
+        "<Point><coordinates>{:.6},{:.6},0</coordinates></Point>",
+        degrees(location.longitude),
+        degrees(location.latitude)
+    )?;
+    writeln!(out, "</MultiGeometry>")?;
+    writeln!(out, "</Placemark>")
+}
+
+fn write_line_string(out: &mut impl Write, points: &[GroundTrackPoint]) -> fmt::Result {
+    write!(out, "<LineString><tessellate>1</tessellate><coordinates>")?;
+    for (i, point) in points.iter().enumerate() {
+        let separator = if i == 0 { "" } else { " " };
+        write!(
+            out,
+            "{separator}{:.6},{:.6},0",
+            degrees(point.longitude),
+            degrees(point.latitude)
+        )?;
+    }
+    writeln!(out, "</coordinates></LineString>")
+}
+
+/// Name of the satellite, or its number if it has none.
+fn name(sgp4: &SGP4) -> String {
+    sgp4.name()
+        .map_or_else(|| sgp4.satnum().to_string(), ToString::to_string)
+}
+
+/// Text with the XML special characters escaped.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::passes::find_passes;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_write_kml() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let track = sgp4.ground_track(0.0, 95.0, 1.0).unwrap();
+        let observer = Observer::from_degrees(51.4779, -0.0015, 0.05);
+        let pass = find_passes(&sgp4, &observer, 0.0, 1440.0).unwrap()[0];
+        let pass_track = sgp4.ground_track(pass.aos, pass.los, 0.5).unwrap();
+
+        let mut kml = String::new();
+        write_kml_header(&mut kml, "ISS & co").unwrap();
+        write_ground_track(&mut kml, &sgp4, &track).unwrap();
+        write_timed_track(&mut kml, &sgp4, &track).unwrap();
+        write_pass(&mut kml, &sgp4, &observer, &pass, &pass_track).unwrap();
+        write_kml_footer(&mut kml).unwrap();
+
+        assert!(kml.contains("<name>ISS &amp; co</name>"));
+        assert!(kml.trim_end().ends_with("</kml>"));
+        assert_eq!(kml.matches("<Placemark>").count(), 3);
+        assert_eq!(kml.matches("<when>").count(), track.len());
+        assert_eq!(kml.matches("<gx:coord>").count(), track.len());
+        assert!(kml.contains("<when>2024-01-01T12:00:00.000Z</when>"));
+        // a LineString per segment between antimeridian crossings
+        assert_eq!(
+            kml.matches("<LineString>").count(),
+            split_at_antimeridian(&track).len() + split_at_antimeridian(&pass_track).len()
+        );
+        assert!(kml.contains("<coordinates>-0.001500,51.477900,0</coordinates>"));
+    }
+}
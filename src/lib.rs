@@ -1,3 +1,59 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the `std` or the `libm` feature must be enabled for the math functions");
+
+extern crate alloc;
+
+pub mod bodies;
+pub mod catalog;
+#[cfg(feature = "celestrak")]
+pub mod celestrak;
+pub mod coe;
+pub mod conjunction;
 pub mod constants;
+#[cfg(feature = "json")]
+pub mod czml;
+pub mod decay;
+mod deep_space;
+pub mod elements;
+pub mod eop;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+pub mod fit;
+#[cfg(test)]
+mod fixtures;
+pub mod frames;
+#[cfg(feature = "json")]
+pub mod geojson;
+pub mod groundtrack;
+#[cfg(any(feature = "spacetrack", feature = "celestrak"))]
+pub mod http;
+pub mod kepler;
+#[cfg(feature = "generic")]
+pub mod kernel;
+#[cfg(feature = "kml")]
+pub mod kml;
+pub mod maneuver;
+// tests link std, whose float methods the imports of `math::Float` would shadow
+#[cfg(all(not(feature = "std"), not(test)))]
+mod math;
+pub mod network;
+pub mod observer;
+#[cfg(feature = "std")]
+pub mod omm;
+pub mod passes;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod relative;
+pub mod sensor;
 pub mod sgp4;
+#[cfg(feature = "spacetrack")]
+pub mod spacetrack;
+pub mod state;
+pub mod stm;
+pub mod time;
+pub mod timescale;
+pub mod tle;
 pub mod utils;
+pub mod verification;
@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::relative::to_ric;
+use crate::sgp4::{PropagationFailure, SGP4};
+use crate::time::Epoch;
+use crate::utils::{angle, cross, get_grav_c, mag, radians};
+
+/// Time step of the search for the maneuver epoch, in minutes
+const SEARCH_STEP_MIN: f64 = 1.0;
+
+/// Thresholds of `detect_maneuvers` above which a change between element sets is taken
+/// as a maneuver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManeuverThresholds {
+    /// Along-track distance of the next element set from the prediction of the
+    /// previous one at its epoch, in km
+    pub along_track_km: f64,
+    /// Change of the mean semimajor axis in km
+    pub semimajor_axis_km: f64,
+    /// Angle between the orbital planes in radians
+    pub plane_change: f64,
+}
+
+impl Default for ManeuverThresholds {
+    /// 10 km along-track, 0.5 km in semimajor axis and 0.01 degrees out of plane,
+    /// above the usual noise of element sets of low earth orbits a day or two apart.
+    fn default() -> Self {
+        ManeuverThresholds {
+            along_track_km: 10.0,
+            semimajor_axis_km: 0.5,
+            plane_change: radians(0.01),
+        }
+    }
+}
+
+/// A maneuver detected between two element sets of a satellite, from
+/// `detect_maneuvers`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Maneuver {
+    /// Index in the history of the first element set after the maneuver
+    pub index: usize,
+    /// Estimated epoch of the maneuver in UTC: when the orbits before and after come
+    /// closest, as they meet at the burn
+    pub epoch: Epoch,
+    /// Position of the element set after the maneuver relative to the prediction of
+    /// the one before, at its epoch, in km in the radial, in-track and cross-track
+    /// frame (see `relative::to_ric`)
+    pub residual_km: [f64; 3],
+    /// Change of the mean semimajor axis in km
+    pub semimajor_axis_change_km: f64,
+    /// Angle between the orbital planes at the maneuver epoch in radians
+    pub plane_change: f64,
+    /// Estimated delta-v in km/s, from the in-plane change of the semimajor axis and
+    /// the plane change combined
+    pub delta_v_km_s: f64,
+}
+
+/// Detect maneuvers in the element sets of a satellite, sorted by epoch.
+///
+/// Each element set is compared with the prediction of the one before it: a jump of
+/// the in-track position, of the mean semimajor axis or of the orbital plane over the
+/// thresholds is reported as a maneuver between their epochs. The in-track residuals
+/// of element sets far apart grow from drag errors alone, so the thresholds should
+/// follow the spacing of the history.
+///
+/// The delta-v is estimated from the Gauss equation of the semimajor axis for a
+/// tangential burn and the velocity rotation of the plane change, at the speed of the
+/// maneuver epoch. Radial burns, which change the semimajor axis little, are
+/// underestimated.
+pub fn detect_maneuvers(
+    history: &[SGP4],
+    thresholds: &ManeuverThresholds,
+) -> Result<Vec<Maneuver>, PropagationFailure> {
+    let mut maneuvers = Vec::new();
+    for (index, pair) in history.windows(2).enumerate() {
+        let (before, after) = (&pair[0], &pair[1]);
+        let gap_min = after.epoch().minutes_since(before.epoch());
+        let reference = before.propagate(gap_min)?;
+        let residual_km = to_ric(&reference, &after.propagate(0.0)?).position_km;
+        let semimajor_axis_change_km = after.semimajor_axis_km() - before.semimajor_axis_km();
+
+        // the orbits meet at the burn, so search for where they come closest
+        let steps = (gap_min / SEARCH_STEP_MIN).ceil().max(1.0) as usize;
+        let mut closest = (f64::INFINITY, 0.0);
+        for step in 0..=steps {
+            let t = gap_min * step as f64 / steps as f64;
+            let a = before.propagate(t)?.position_km;
+            let b = after.propagate(t - gap_min)?.position_km;
+            let distance = mag([0, 1, 2].map(|i| b[i] - a[i]));
+            if distance < closest.0 {
+                closest = (distance, t);
+            }
+        }
+        let t = closest.1;
+        let a = before.propagate(t)?;
+        let b = after.propagate(t - gap_min)?;
+        let plane_change = angle(
+            cross(a.position_km, a.velocity_km_s),
+            cross(b.position_km, b.velocity_km_s),
+        );
+
+        if residual_km[1].abs() <= thresholds.along_track_km
+            && semimajor_axis_change_km.abs() <= thresholds.semimajor_axis_km
+            && plane_change <= thresholds.plane_change
+        {
+            continue;
+        }
+        let mu = get_grav_c(&before.model()).mu;
+        let speed = mag(a.velocity_km_s);
+        let semimajor_axis = before.semimajor_axis_km();
+        let in_plane = mu * semimajor_axis_change_km / (2.0 * semimajor_axis.powi(2) * speed);
+        let out_of_plane = 2.0 * speed * (0.5 * plane_change).sin();
+        maneuvers.push(Maneuver {
+            index: index + 1,
+            epoch: before.epoch() + t / DAY2MIN,
+            residual_km,
+            semimajor_axis_change_km,
+            plane_change,
+            delta_v_km_s: in_plane.hypot(out_of_plane),
+        });
+    }
+    Ok(maneuvers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::Elements;
+    use crate::fit::{FitOptions, fit_elements};
+    use crate::fixtures::iss;
+    use crate::sgp4::OperationMode;
+    use crate::state::State;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_detect_maneuvers() {
+        let first = iss(WGSModel::WGS_72);
+        let options = FitOptions {
+            bstar: first.elements().bstar,
+            ..FitOptions::default()
+        };
+        let init = |elements: Elements| {
+            SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &elements).unwrap()
+        };
+
+        // an element set a day later on the same orbit
+        let samples: Vec<State> = first
+            .iter(1080.0, 1800.0, 10.0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let second = init(
+            fit_elements(&samples, first.epoch() + 1.0, &options)
+                .unwrap()
+                .elements,
+        );
+
+        // and one from a prograde burn of 10 m/s half a day after that
+        let mut burn = second.propagate(720.0).unwrap();
+        let speed = mag(burn.velocity_km_s);
+        burn.velocity_km_s = burn.velocity_km_s.map(|v| v * (1.0 + 0.01 / speed));
+        let burn_epoch = burn.epoch;
+        let third = init(
+            fit_elements(&[burn, burn], burn_epoch, &options)
+                .unwrap()
+                .elements,
+        );
+
+        let maneuvers =
+            detect_maneuvers(&[first, second, third], &ManeuverThresholds::default()).unwrap();
+        assert_eq!(maneuvers.len(), 1, "{maneuvers:?}");
+        let maneuver = maneuvers[0];
+        assert_eq!(maneuver.index, 2);
+        assert!((maneuver.epoch - burn_epoch).abs() * DAY2MIN < 2.0);
+        assert!(maneuver.semimajor_axis_change_km > 10.0);
+        assert!(maneuver.plane_change < 1e-5);
+        assert!((maneuver.delta_v_km_s - 0.01).abs() < 0.002, "{maneuver:?}");
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use crate::observer::Observer;
+use crate::passes::{Pass, find_passes};
+use crate::sgp4::{PropagationError, SGP4};
+
+/// A pass of a satellite over one station of a ground network, from `find_contacts`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Index of the station in the network
+    pub station: usize,
+    pub pass: Pass,
+}
+
+/// Find the contacts of a satellite with a network of ground stations between
+/// `start_min` and `stop_min` minutes since epoch, sorted by acquisition of signal.
+///
+/// Each station gets its passes above its own minimum elevation (see
+/// `passes::find_passes`), so contacts with stations close together overlap.
+pub fn find_contacts(
+    sgp4: &SGP4,
+    stations: &[Observer],
+    start_min: f64,
+    stop_min: f64,
+) -> Result<Vec<Contact>, PropagationError> {
+    let mut contacts = Vec::new();
+    for (station, observer) in stations.iter().enumerate() {
+        let passes = find_passes(sgp4, observer, start_min, stop_min)?;
+        contacts.extend(passes.into_iter().map(|pass| Contact { station, pass }));
+    }
+    contacts.sort_by(|a, b| a.pass.aos.total_cmp(&b.pass.aos));
+    Ok(contacts)
+}
+
+/// Merge contacts sorted by acquisition of signal into the intervals during which the
+/// satellite is in contact with at least one station, as `(start, stop)` in minutes
+/// since epoch.
+///
+/// The gaps between the intervals are the outages of the network, and the sum of their
+/// lengths its coverage of the satellite.
+pub fn coverage(contacts: &[Contact]) -> Vec<(f64, f64)> {
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    for contact in contacts {
+        let Pass { aos, los, .. } = contact.pass;
+        match intervals.last_mut() {
+            Some((_, stop)) if aos <= *stop => *stop = stop.max(los),
+            _ => intervals.push((aos, los)),
+        }
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DAY2MIN;
+    use crate::fixtures::iss;
+    use crate::utils::{WGSModel, radians};
+
+    #[test]
+    fn test_find_contacts() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let boulder = Observer::from_degrees(40.0, -105.0, 1.6);
+        let denver = Observer::from_degrees(39.74, -104.99, 1.6).with_min_elevation(radians(5.0));
+        let greenwich = Observer::from_degrees(51.4779, -0.0015, 0.05);
+        let stations = [boulder, denver, greenwich];
+
+        let contacts = find_contacts(&sgp4, &stations, 0.0, DAY2MIN).unwrap();
+        for (station, observer) in stations.iter().enumerate() {
+            let passes = find_passes(&sgp4, observer, 0.0, DAY2MIN).unwrap();
+            assert!(!passes.is_empty());
+            let attributed: Vec<Pass> = contacts
+                .iter()
+                .filter(|contact| contact.station == station)
+                .map(|contact| contact.pass)
+                .collect();
+            assert_eq!(attributed, passes);
+        }
+        assert!(contacts.windows(2).all(|w| w[0].pass.aos <= w[1].pass.aos));
+
+        // the Denver passes are inside the Boulder ones, so only Greenwich adds coverage
+        let intervals = coverage(&contacts);
+        assert!(intervals.windows(2).all(|w| w[0].1 < w[1].0));
+        let total =
+            |intervals: &[(f64, f64)]| -> f64 { intervals.iter().map(|(a, b)| b - a).sum() };
+        let without_denver: Vec<Contact> = contacts
+            .iter()
+            .filter(|contact| contact.station != 1)
+            .copied()
+            .collect();
+        let expected = total(&coverage(&without_denver));
+        assert!((total(&intervals) - expected).abs() < 0.1);
+        let boulder_only = find_contacts(&sgp4, &stations[..1], 0.0, DAY2MIN).unwrap();
+        assert!(total(&intervals) > total(&coverage(&boulder_only)));
+    }
+}
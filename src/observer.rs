@@ -0,0 +1,390 @@
+use crate::constants::*;
+use crate::frames::{Geodetic, geodetic_to_ecef, teme_to_ecef};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::sgp4::{PropagationError, SGP4};
+use crate::utils::*;
+
+/// Light time iterations stop when the light time changes by less than this, in
+/// seconds. Two or three are needed for satellites in earth orbit.
+const LIGHT_TIME_TOLERANCE_S: f64 = 1e-9;
+
+/// A ground station at a fixed geodetic location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    location: Geodetic,
+    r_ecef: [f64; 3],
+    min_elevation: f64,
+    light_time: bool,
+}
+
+/// Direction and distance of a satellite as seen from an observer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookAngles {
+    /// Azimuth in radians, clockwise from north, in [0, 2pi)
+    pub azimuth: f64,
+    /// Elevation above the local horizon in radians
+    pub elevation: f64,
+    /// Slant range in km
+    pub range: f64,
+}
+
+/// A satellite as seen from an observer, from `Observer::observe`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    pub look: LookAngles,
+    pub rates: LookRates,
+    /// Travel time of the signal from the satellite in seconds, by which its state was
+    /// propagated backward, or 0 without light time correction
+    pub light_time: f64,
+}
+
+/// Rates of change of the look angles of a satellite, for the slew rates of an antenna
+/// tracking it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookRates {
+    /// Azimuth rate in radians per second
+    pub azimuth: f64,
+    /// Elevation rate in radians per second
+    pub elevation: f64,
+    /// Range rate in km/s, positive when the satellite is receding
+    pub range: f64,
+}
+
+impl Observer {
+    /// Observer at a geodetic latitude and east longitude in radians, and a height above
+    /// the WGS-84 ellipsoid in km.
+    pub fn new(latitude: f64, longitude: f64, altitude: f64) -> Observer {
+        let location = Geodetic {
+            latitude,
+            longitude,
+            altitude,
+        };
+        Observer {
+            location,
+            r_ecef: geodetic_to_ecef(&location),
+            min_elevation: 0.0,
+            light_time: false,
+        }
+    }
+
+    /// The observer with a minimum elevation in radians below which satellites are not
+    /// visible, e.g. `radians(10.0)` for the terrain and antenna limits of a typical
+    /// ground station. The horizon, 0, by default.
+    pub fn with_min_elevation(self, min_elevation: f64) -> Observer {
+        Observer {
+            min_elevation,
+            ..self
+        }
+    }
+
+    /// Minimum elevation in radians, see `with_min_elevation`.
+    pub fn min_elevation(&self) -> f64 {
+        self.min_elevation
+    }
+
+    /// The observer with or without light time correction in `observe`, and so in the
+    /// passes found for it. Off by default.
+    ///
+    /// With the correction, a satellite is seen where it was when the signal left it,
+    /// a few milliseconds earlier: in low earth orbit this moves it by tens of meters,
+    /// which matters when simulating precise optical or radar observations.
+    pub fn with_light_time(self, light_time: bool) -> Observer {
+        Observer { light_time, ..self }
+    }
+
+    /// Whether light time correction is on, see `with_light_time`.
+    pub fn light_time(&self) -> bool {
+        self.light_time
+    }
+
+    /// Observer at a geodetic latitude and east longitude in degrees, and a height above
+    /// the WGS-84 ellipsoid in km.
+    pub fn from_degrees(latitude: f64, longitude: f64, altitude: f64) -> Observer {
+        Observer::new(radians(latitude), radians(longitude), altitude)
+    }
+
+    pub fn location(&self) -> Geodetic {
+        self.location
+    }
+
+    /// Earth fixed position of the observer in km
+    pub fn position_ecef(&self) -> [f64; 3] {
+        self.r_ecef
+    }
+
+    /// Azimuth, elevation and slant range of a satellite at TEME position `r` (km) at
+    /// `jd_ut1`, the Julian date in UT1 (UTC is adequate).
+    pub fn look_angles(&self, r: [f64; 3], jd_ut1: f64) -> LookAngles {
+        let (r_ecef, _) = teme_to_ecef(r, [0.0; 3], jd_ut1);
+        let rho = [
+            r_ecef[0] - self.r_ecef[0],
+            r_ecef[1] - self.r_ecef[1],
+            r_ecef[2] - self.r_ecef[2],
+        ];
+        let [south, east, zenith] = self.sez(rho);
+
+        let range = (south * south + east * east + zenith * zenith).sqrt();
+        let elevation = (zenith / range).asin();
+        let mut azimuth = east.atan2(-south);
+        if azimuth < 0.0 {
+            azimuth += TWOPI;
+        }
+
+        LookAngles {
+            azimuth,
+            elevation,
+            range,
+        }
+    }
+
+    /// Look angles and rates of a satellite at `t` minutes since the epoch of its
+    /// elements, with light time correction if it's on (see `with_light_time`).
+    ///
+    /// The light time is found by iteration: the satellite is propagated back by the
+    /// range over the speed of light until it converges.
+    pub fn observe(&self, sgp4: &SGP4, t: f64) -> Result<Observation, PropagationError> {
+        let jd = sgp4.jdepoch() + t / DAY2MIN;
+        let mut light_time = 0.0;
+        let mut state = sgp4.propagate(t)?;
+        if self.light_time {
+            loop {
+                let range = self.look_angles(state.position_km, jd).range;
+                let next = range / SPEED_OF_LIGHT;
+                let converged = (next - light_time).abs() < LIGHT_TIME_TOLERANCE_S;
+                light_time = next;
+                state = sgp4.propagate(t - light_time / MIN2SEC)?;
+                if converged {
+                    break;
+                }
+            }
+        }
+        Ok(Observation {
+            look: self.look_angles(state.position_km, jd),
+            rates: self.look_rates(state.position_km, state.velocity_km_s, jd),
+            light_time,
+        })
+    }
+
+    /// Whether a satellite at TEME position `r` (km) at `jd_ut1` is at or above the
+    /// minimum elevation.
+    pub fn is_visible(&self, r: [f64; 3], jd_ut1: f64) -> bool {
+        self.look_angles(r, jd_ut1).elevation >= self.min_elevation
+    }
+
+    /// Rates of change of the azimuth, elevation and slant range of a satellite at TEME
+    /// position `r` (km) and velocity `v` (km/s) at `jd_ut1`.
+    ///
+    /// The azimuth rate grows without bound as the satellite nears the zenith: a pass
+    /// whose azimuth rate exceeds the limit of an azimuth-elevation mount goes through
+    /// its keyhole, where the antenna can't keep up.
+    pub fn look_rates(&self, r: [f64; 3], v: [f64; 3], jd_ut1: f64) -> LookRates {
+        let (r_ecef, v_ecef) = teme_to_ecef(r, v, jd_ut1);
+        let [south, east, zenith] = self.sez([
+            r_ecef[0] - self.r_ecef[0],
+            r_ecef[1] - self.r_ecef[1],
+            r_ecef[2] - self.r_ecef[2],
+        ]);
+        let [south_dot, east_dot, zenith_dot] = self.sez(v_ecef);
+
+        let horizontal2 = south * south + east * east;
+        let range = (horizontal2 + zenith * zenith).sqrt();
+        let range_rate = (south * south_dot + east * east_dot + zenith * zenith_dot) / range;
+        LookRates {
+            azimuth: (east * south_dot - south * east_dot) / horizontal2,
+            elevation: (zenith_dot - range_rate * zenith / range) / horizontal2.sqrt(),
+            range: range_rate,
+        }
+    }
+
+    /// Rate of change of the slant range in km/s of a satellite at TEME position `r` (km)
+    /// and velocity `v` (km/s) at `jd_ut1`. Positive when the satellite is receding.
+    pub fn range_rate(&self, r: [f64; 3], v: [f64; 3], jd_ut1: f64) -> f64 {
+        let (r_ecef, v_ecef) = teme_to_ecef(r, v, jd_ut1);
+        let rho = [
+            r_ecef[0] - self.r_ecef[0],
+            r_ecef[1] - self.r_ecef[1],
+            r_ecef[2] - self.r_ecef[2],
+        ];
+        let range = (rho[0] * rho[0] + rho[1] * rho[1] + rho[2] * rho[2]).sqrt();
+        (rho[0] * v_ecef[0] + rho[1] * v_ecef[1] + rho[2] * v_ecef[2]) / range
+    }
+
+    /// Frequency in Hz received by the observer from a satellite transmitting at
+    /// `frequency` Hz, for the satellite state as in `range_rate`.
+    ///
+    /// Uses the first order (non-relativistic) Doppler shift, accurate to a few parts
+    /// in 1e10 at orbital speeds.
+    pub fn doppler(&self, r: [f64; 3], v: [f64; 3], jd_ut1: f64, frequency: f64) -> f64 {
+        frequency * (1.0 - self.range_rate(r, v, jd_ut1) / SPEED_OF_LIGHT)
+    }
+
+    /// Transformation of earth fixed positions in km to the observer's south, east,
+    /// zenith (SEZ) frame centered on the observer, as a nalgebra isometry. Its inverse
+    /// transforms topocentric positions back to the earth fixed frame.
+    #[cfg(feature = "nalgebra")]
+    pub fn isometry(&self) -> nalgebra::Isometry3<f64> {
+        let columns = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(|e| self.sez(e));
+        let rotation = nalgebra::Rotation3::from_matrix_unchecked(nalgebra::Matrix3::from_columns(
+            &columns.map(nalgebra::Vector3::from),
+        ));
+        let translation = -(rotation * nalgebra::Vector3::from(self.r_ecef));
+        nalgebra::Isometry3::from_parts(
+            translation.into(),
+            nalgebra::UnitQuaternion::from_rotation_matrix(&rotation),
+        )
+    }
+
+    /// Rotate an earth fixed vector to the observer's south, east, zenith (SEZ) frame.
+    fn sez(&self, rho: [f64; 3]) -> [f64; 3] {
+        let (sinlat, coslat) = self.location.latitude.sin_cos();
+        let (sinlon, coslon) = self.location.longitude.sin_cos();
+        [
+            sinlat * coslon * rho[0] + sinlat * sinlon * rho[1] - coslat * rho[2],
+            -sinlon * rho[0] + coslon * rho[1],
+            coslat * coslon * rho[0] + coslat * sinlon * rho[1] + sinlat * rho[2],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::time::gstime;
+
+    /// TEME position of an earth fixed position at `jd`.
+    fn ecef_to_teme(r: [f64; 3], jd: f64) -> [f64; 3] {
+        let (sing, cosg) = gstime(jd).sin_cos();
+        [cosg * r[0] - sing * r[1], sing * r[0] + cosg * r[1], r[2]]
+    }
+
+    /// TEME velocity of an earth fixed position and velocity at `jd`.
+    fn ecef_to_teme_velocity(r: [f64; 3], v: [f64; 3], jd: f64) -> [f64; 3] {
+        let v_inertial = [v[0] - EARTHROT * r[1], v[1] + EARTHROT * r[0], v[2]];
+        ecef_to_teme(v_inertial, jd)
+    }
+
+    #[test]
+    fn test_look_angles() {
+        let jd = 2460000.25;
+
+        // satellite overhead
+        let observer = Observer::from_degrees(0.0, 0.0, 0.0);
+        let r = ecef_to_teme([WGS84_RE + 1000.0, 0.0, 0.0], jd);
+        let look = observer.look_angles(r, jd);
+        assert!((look.elevation - HALFPI).abs() < 1e-9);
+        assert!((look.range - 1000.0).abs() < 1e-8);
+
+        // satellite on the horizon due north
+        let r = ecef_to_teme([WGS84_RE, 0.0, 1000.0], jd);
+        let look = observer.look_angles(r, jd);
+        assert!(look.elevation.abs() < 1e-9);
+        assert!(look.azimuth < 1e-9 || TWOPI - look.azimuth < 1e-9);
+        assert!((look.range - 1000.0).abs() < 1e-8);
+
+        // satellite to the east, 45 degrees up
+        let observer = Observer::from_degrees(0.0, 90.0, 0.0);
+        let r = ecef_to_teme([-1000.0, WGS84_RE + 1000.0, 0.0], jd);
+        let look = observer.look_angles(r, jd);
+        assert!((degrees(look.elevation) - 45.0).abs() < 1e-9);
+        assert!((degrees(look.azimuth) - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    #[cfg(feature = "nalgebra")]
+    fn test_isometry() {
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+        let isometry = observer.isometry();
+        let origin = isometry * nalgebra::Point3::from(observer.position_ecef());
+        assert!(origin.coords.norm() < 1e-9);
+
+        // the topocentric position gives the look angles
+        let jd = 2460000.25;
+        let r_ecef = [-1200.0, -5300.0, 4600.0];
+        let look = observer.look_angles(ecef_to_teme(r_ecef, jd), jd);
+        let sez = isometry * nalgebra::Point3::from(r_ecef);
+        assert!((sez.coords.norm() - look.range).abs() < 1e-8);
+        assert!((sez.z / look.range - look.elevation.sin()).abs() < 1e-12);
+        assert!((sez.y.atan2(-sez.x) - look.azimuth).abs() < 1e-12);
+
+        let back = isometry.inverse() * sez;
+        assert!((back.coords - nalgebra::Vector3::from(r_ecef)).norm() < 1e-8);
+    }
+
+    #[test]
+    fn test_look_rates() {
+        let jd = 2460000.25;
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+        let r_ecef = [-1200.0, -5300.0, 4600.0];
+        let v_ecef = [6.5, -1.2, 0.4];
+        let r = ecef_to_teme(r_ecef, jd);
+        let v = ecef_to_teme_velocity(r_ecef, v_ecef, jd);
+        let rates = observer.look_rates(r, v, jd);
+        assert!((rates.range - observer.range_rate(r, v, jd)).abs() < 1e-12);
+
+        // against central differences of the look angles a tenth of a second apart
+        let dt = 0.05;
+        let look = |t: f64| {
+            let moved = [0, 1, 2].map(|i| r_ecef[i] + v_ecef[i] * t);
+            observer.look_angles(ecef_to_teme(moved, jd + t / DAY2SEC), jd + t / DAY2SEC)
+        };
+        let (before, after) = (look(-dt), look(dt));
+        let azimuth_rate = (after.azimuth - before.azimuth) / (2.0 * dt);
+        let elevation_rate = (after.elevation - before.elevation) / (2.0 * dt);
+        assert!(
+            (rates.azimuth / azimuth_rate - 1.0).abs() < 1e-5,
+            "{rates:?}"
+        );
+        assert!(
+            (rates.elevation / elevation_rate - 1.0).abs() < 1e-5,
+            "{rates:?}"
+        );
+        assert!(observer.look_angles(r, jd).elevation > 0.0);
+    }
+
+    #[test]
+    fn test_observe() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+        let corrected = observer.with_light_time(true);
+        assert!(!observer.light_time() && corrected.light_time());
+
+        let t = 100.0;
+        let geometric = observer.observe(&sgp4, t).unwrap();
+        let r = sgp4.propagate(t).unwrap().position_km;
+        assert_eq!(
+            geometric.look,
+            observer.look_angles(r, sgp4.jdepoch() + t / DAY2MIN)
+        );
+        assert_eq!(geometric.light_time, 0.0);
+
+        // the light time is consistent with the range it was corrected to
+        let apparent = corrected.observe(&sgp4, t).unwrap();
+        assert!((apparent.light_time * SPEED_OF_LIGHT - apparent.look.range).abs() < 1e-6);
+        assert!(apparent.light_time > 1e-3 && apparent.light_time < 0.05);
+        // and the range changes by about the range rate over it
+        let change = apparent.look.range - geometric.look.range;
+        let expected = -geometric.rates.range * apparent.light_time;
+        assert!((change - expected).abs() < 1e-3, "{change} {expected}");
+    }
+
+    #[test]
+    fn test_doppler() {
+        let jd = 2460000.25;
+        let observer = Observer::from_degrees(0.0, 0.0, 0.0);
+        let r_ecef = [WGS84_RE + 1000.0, 0.0, 0.0];
+        let r = ecef_to_teme(r_ecef, jd);
+
+        // moving across the line of sight
+        let v = ecef_to_teme_velocity(r_ecef, [0.0, 7.0, 0.0], jd);
+        assert!(observer.range_rate(r, v, jd).abs() < 1e-9);
+        assert!((observer.doppler(r, v, jd, 437e6) - 437e6).abs() < 1e-3);
+
+        // receding at 1 km/s
+        let v = ecef_to_teme_velocity(r_ecef, [1.0, 0.0, 0.0], jd);
+        assert!((observer.range_rate(r, v, jd) - 1.0).abs() < 1e-9);
+        let shift = observer.doppler(r, v, jd, 437e6) - 437e6;
+        assert!((shift + 437e6 / SPEED_OF_LIGHT).abs() < 1e-3);
+    }
+}
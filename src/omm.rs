@@ -0,0 +1,451 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::time::parse_iso8601;
+use crate::tle::{Classification, TLE, TleFormatError};
+
+/// Errors encountered while parsing an orbit mean-elements message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OmmParseError {
+    /// A line is neither blank, a comment nor a `KEYWORD = value` pair
+    InvalidLine { line: usize, text: String },
+    /// A keyword required to build the elements is missing
+    MissingKeyword(&'static str),
+    /// A keyword does not hold a valid value
+    InvalidValue {
+        keyword: &'static str,
+        value: String,
+    },
+    /// The mean elements are not SGP4 mean elements
+    UnsupportedTheory(String),
+    /// The message is not well-formed XML
+    #[cfg(feature = "xml")]
+    InvalidXml(String),
+    /// The records are not valid JSON GP records
+    #[cfg(feature = "json")]
+    InvalidJson(String),
+}
+
+impl fmt::Display for OmmParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OmmParseError::InvalidLine { line, text } => {
+                write!(f, "invalid line {line}: {text:?}")
+            }
+            OmmParseError::MissingKeyword(keyword) => write!(f, "missing {keyword}"),
+            OmmParseError::InvalidValue { keyword, value } => {
+                write!(f, "invalid {keyword}: {value:?}")
+            }
+            OmmParseError::UnsupportedTheory(theory) => {
+                write!(f, "unsupported mean element theory {theory:?}")
+            }
+            #[cfg(feature = "xml")]
+            OmmParseError::InvalidXml(error) => write!(f, "invalid XML: {error}"),
+            #[cfg(feature = "json")]
+            OmmParseError::InvalidJson(error) => write!(f, "invalid JSON: {error}"),
+        }
+    }
+}
+
+impl Error for OmmParseError {}
+
+/// Parse a CCSDS Orbit Mean-Elements Message (OMM) in Keyword = Value Notation (KVN).
+///
+/// The elements are returned as a `TLE`, ready to be propagated with `SGP4::tle2rv`.
+///
+/// Units in square brackets after the values are ignored, as are comments and the
+/// keywords that don't contribute to the elements (originator, reference frame, ...).
+pub fn parse_kvn(text: &str) -> Result<TLE, OmmParseError> {
+    let mut keywords = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+        let (keyword, value) = line.split_once('=').ok_or(OmmParseError::InvalidLine {
+            line: i + 1,
+            text: line.to_string(),
+        })?;
+        let value = value.split_once('[').map_or(value, |(value, _units)| value);
+        keywords.insert(keyword.trim().to_string(), value.trim().to_string());
+    }
+    from_keywords(&keywords)
+}
+
+/// Parse CCSDS Orbit Mean-Elements Messages in XML form.
+///
+/// Returns the elements of every `<omm>` in the document, so both single messages and
+/// the `<ndm>` lists served by Space-Track can be read. Unit attributes are ignored.
+#[cfg(feature = "xml")]
+pub fn parse_xml(text: &str) -> Result<Vec<TLE>, OmmParseError> {
+    use quick_xml::events::Event;
+    use quick_xml::reader::Reader;
+
+    let invalid_xml = |error: quick_xml::Error| OmmParseError::InvalidXml(error.to_string());
+    let mut reader = Reader::from_str(text);
+    let mut tles = Vec::new();
+    let mut keywords = HashMap::new();
+    let mut element = None;
+    let mut value = String::new();
+    loop {
+        match reader.read_event().map_err(invalid_xml)? {
+            Event::Start(start) => {
+                element = Some(start.local_name().as_ref().to_string());
+                value.clear();
+            }
+            Event::Text(text) => value.push_str(&text.xml10_content()),
+            Event::CData(data) => value.push_str(&data.xml10_content()),
+            Event::GeneralRef(reference) => {
+                let entity = format!("&{};", &*reference);
+                let resolved = quick_xml::escape::unescape(&entity)
+                    .map_err(|error| invalid_xml(error.into()))?;
+                value.push_str(&resolved);
+            }
+            Event::End(end) => {
+                // leaf elements hold the keyword values
+                if let Some(keyword) = element.take() {
+                    keywords.insert(keyword, value.trim().to_string());
+                }
+                if end.local_name().as_ref() == "omm" {
+                    tles.push(from_keywords(&keywords)?);
+                    keywords.clear();
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(tles)
+}
+
+/// Parse general perturbations (GP) data in the JSON form served by Celestrak
+/// (`FORMAT=json`) and Space-Track.
+///
+/// The records use the OMM keywords, so each one is converted like a KVN message. Both
+/// a list of records and a single record are accepted. Pass the elements to
+/// `SGP4::from_tle` to get initialized propagators.
+#[cfg(feature = "json")]
+pub fn parse_json(text: &str) -> Result<Vec<TLE>, OmmParseError> {
+    use serde_json::Value;
+
+    let records = match serde_json::from_str(text) {
+        Ok(Value::Array(records)) => records,
+        Ok(record @ Value::Object(_)) => vec![record],
+        Ok(_) => return Err(OmmParseError::InvalidJson("expected GP records".into())),
+        Err(error) => return Err(OmmParseError::InvalidJson(error.to_string())),
+    };
+    records
+        .iter()
+        .map(|record| {
+            let Value::Object(record) = record else {
+                return Err(OmmParseError::InvalidJson("expected a GP record".into()));
+            };
+            let keywords = record
+                .iter()
+                .filter_map(|(keyword, value)| {
+                    let value = match value {
+                        Value::String(value) => value.trim().to_string(),
+                        Value::Number(value) => value.to_string(),
+                        _ => return None,
+                    };
+                    Some((keyword.clone(), value))
+                })
+                .collect();
+            from_keywords(&keywords)
+        })
+        .collect()
+}
+
+/// Build the elements from the OMM keywords and their values.
+pub(crate) fn from_keywords(keywords: &HashMap<String, String>) -> Result<TLE, OmmParseError> {
+    let get = |keyword: &'static str| keywords.get(keyword).map(String::as_str);
+    let required = |keyword| get(keyword).ok_or(OmmParseError::MissingKeyword(keyword));
+    let parse = |keyword: &'static str, value: &str| {
+        value
+            .parse::<f64>()
+            .map_err(|_| OmmParseError::InvalidValue {
+                keyword,
+                value: value.to_string(),
+            })
+    };
+    let number = |keyword| parse(keyword, required(keyword)?);
+    let optional = |keyword| get(keyword).map_or(Ok(0.0), |value| parse(keyword, value));
+    let integer = |keyword: &'static str| {
+        get(keyword).map_or(Ok(0), |value| {
+            value
+                .parse::<u64>()
+                .map_err(|_| OmmParseError::InvalidValue {
+                    keyword,
+                    value: value.to_string(),
+                })
+        })
+    };
+
+    if let Some(theory) = get("MEAN_ELEMENT_THEORY")
+        && !matches!(theory, "SGP4" | "SGP/SGP4")
+    {
+        return Err(OmmParseError::UnsupportedTheory(theory.to_string()));
+    }
+
+    let epoch = required("EPOCH")?;
+    let (year, epochdays) = parse_iso8601(epoch).ok_or_else(|| OmmParseError::InvalidValue {
+        keyword: "EPOCH",
+        value: epoch.to_string(),
+    })?;
+    let satnum = required("NORAD_CAT_ID")?;
+    let satnum = satnum.parse().map_err(|_| OmmParseError::InvalidValue {
+        keyword: "NORAD_CAT_ID",
+        value: satnum.to_string(),
+    })?;
+    let classification = get("CLASSIFICATION_TYPE")
+        .and_then(|c| c.chars().next())
+        .map_or(Some(Classification::Unclassified), Classification::from);
+
+    let mut tle = TLE {
+        name: get("OBJECT_NAME").map(String::from),
+        line1: String::new(),
+        line2: String::new(),
+        satnum,
+        classification,
+        intldesg: get("OBJECT_ID").map_or_else(String::new, intldesg),
+        epochyr: year,
+        epochdays,
+        ndot: optional("MEAN_MOTION_DOT")?,
+        nddot: optional("MEAN_MOTION_DDOT")?,
+        bstar: optional("BSTAR")?,
+        elnum: integer("ELEMENT_SET_NO")?,
+        inclo: number("INCLINATION")?,
+        nodeo: number("RA_OF_ASC_NODE")?,
+        ecco: number("ECCENTRICITY")?,
+        argpo: number("ARG_OF_PERICENTER")?,
+        mo: number("MEAN_ANOMALY")?,
+        no_kozai: number("MEAN_MOTION")?,
+        revnum: integer("REV_AT_EPOCH")?,
+        warnings: Vec::new(),
+    };
+    (tle.line1, tle.line2) = tle.to_lines().map_err(|error| {
+        let keyword = match error {
+            TleFormatError::SatnumOutOfRange(_) => "NORAD_CAT_ID",
+            TleFormatError::InvalidValue { field, .. } => match field {
+                "epoch day" => "EPOCH",
+                "first derivative of mean motion" => "MEAN_MOTION_DOT",
+                "second derivative of mean motion" => "MEAN_MOTION_DDOT",
+                "bstar" => "BSTAR",
+                "inclination" => "INCLINATION",
+                "right ascension" => "RA_OF_ASC_NODE",
+                "eccentricity" => "ECCENTRICITY",
+                "argument of perigee" => "ARG_OF_PERICENTER",
+                "mean anomaly" => "MEAN_ANOMALY",
+                _ => "MEAN_MOTION",
+            },
+        };
+        OmmParseError::InvalidValue {
+            keyword,
+            value: get(keyword).unwrap_or_default().to_string(),
+        }
+    })?;
+    Ok(tle)
+}
+
+/// Convert an OMM object id (e.g. "1998-067A") to a TLE international designator
+/// ("98067A").
+fn intldesg(object_id: &str) -> String {
+    match (object_id.get(2..4), object_id.get(4..5), object_id.get(5..)) {
+        (Some(year), Some("-"), Some(piece)) => format!("{year}{piece}"),
+        _ => object_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{VANGUARD_LINE1, VANGUARD_LINE2};
+
+    const KVN: &str = "CCSDS_OMM_VERS = 2.0
+COMMENT  GENERATED VIA SPACE-TRACK.ORG API
+CREATION_DATE = 2000-06-28T00:00:00
+ORIGINATOR = 18 SPCS
+
+OBJECT_NAME = VANGUARD 1
+OBJECT_ID = 1958-002B
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+
+EPOCH = 2000-06-27T18:50:19.733568
+MEAN_MOTION = 10.82419157 [rev/day]
+ECCENTRICITY = .1859667
+INCLINATION = 34.2682 [deg]
+RA_OF_ASC_NODE = 348.7242 [deg]
+ARG_OF_PERICENTER = 331.7664 [deg]
+MEAN_ANOMALY = 19.3264 [deg]
+
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 5
+ELEMENT_SET_NO = 475
+REV_AT_EPOCH = 41366
+BSTAR = 0.28098E-4 [1/ER]
+MEAN_MOTION_DOT = 2.3E-7 [rev/day**2]
+MEAN_MOTION_DDOT = 0.0 [rev/day**3]
+";
+
+    #[test]
+    fn test_parse_kvn() {
+        let tle = parse_kvn(KVN).unwrap();
+        assert_eq!(tle.name(), Some("VANGUARD 1"));
+        assert_eq!(tle.line1(), VANGUARD_LINE1);
+        assert_eq!(tle.line2(), VANGUARD_LINE2);
+
+        // four digit years aren't subject to the two digit year pivot
+        for year in [1950, 2060] {
+            let tle = parse_kvn(&KVN.replace("2000-06-27", &format!("{year}-06-27"))).unwrap();
+            assert_eq!(tle.epoch_year(), year);
+            assert_eq!(tle.epoch_year_with_pivot(0), year);
+            assert_eq!(tle.line1()[18..20], format!("{}", year % 100));
+
+            // and survive a round trip through the elements
+            let elements =
+                crate::elements::Elements::from_parsed_tle(tle, crate::tle::DEFAULT_YEAR_PIVOT);
+            assert_eq!(elements.epochyr, year);
+            let tle = TLE::try_from(&elements).unwrap();
+            assert_eq!((tle.epoch_year(), tle.epoch()), (year, elements.epoch));
+        }
+    }
+
+    #[test]
+    fn test_parse_kvn_errors() {
+        assert_eq!(
+            parse_kvn(&KVN.replace("EPOCH = 2000-06-27T18:50:19.733568\n", "")).unwrap_err(),
+            OmmParseError::MissingKeyword("EPOCH")
+        );
+        assert_eq!(
+            parse_kvn(&KVN.replace("= SGP4", "= DSST")).unwrap_err(),
+            OmmParseError::UnsupportedTheory("DSST".into())
+        );
+        assert_eq!(
+            parse_kvn(&KVN.replace("= .1859667", "= x")).unwrap_err(),
+            OmmParseError::InvalidValue {
+                keyword: "ECCENTRICITY",
+                value: "x".into()
+            }
+        );
+        assert_eq!(
+            parse_kvn(&KVN.replace("ORIGINATOR = 18 SPCS", "ORIGINATOR")).unwrap_err(),
+            OmmParseError::InvalidLine {
+                line: 4,
+                text: "ORIGINATOR".into()
+            }
+        );
+
+        // values the TLE columns can't hold
+        for (keyword, from, to) in [
+            ("BSTAR", "0.28098E-4", "NaN"),
+            ("BSTAR", "0.28098E-4", "1e-12"),
+            ("NORAD_CAT_ID", "= 5\n", "= 400000\n"),
+            ("ECCENTRICITY", ".1859667", "1.2"),
+        ] {
+            let value = to.trim_start_matches("= ").trim_end();
+            assert_eq!(
+                parse_kvn(&KVN.replace(from, to)).unwrap_err(),
+                OmmParseError::InvalidValue {
+                    keyword,
+                    value: value.into()
+                }
+            );
+        }
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_parse_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ndm xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE>2000-06-28T00:00:00</CREATION_DATE><ORIGINATOR>18 SPCS</ORIGINATOR></header>
+<body>
+<segment>
+<metadata>
+<OBJECT_NAME>VANGUARD 1</OBJECT_NAME>
+<OBJECT_ID>1958-002B</OBJECT_ID>
+<CENTER_NAME>EARTH</CENTER_NAME>
+<REF_FRAME>TEME</REF_FRAME>
+<TIME_SYSTEM>UTC</TIME_SYSTEM>
+<MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY>
+</metadata>
+<data>
+<meanElements>
+<EPOCH>2000-06-27T18:50:19.733568</EPOCH>
+<MEAN_MOTION units="rev/day">10.82419157</MEAN_MOTION>
+<ECCENTRICITY>.1859667</ECCENTRICITY>
+<INCLINATION units="deg">34.2682</INCLINATION>
+<RA_OF_ASC_NODE units="deg">348.7242</RA_OF_ASC_NODE>
+<ARG_OF_PERICENTER units="deg">331.7664</ARG_OF_PERICENTER>
+<MEAN_ANOMALY units="deg">19.3264</MEAN_ANOMALY>
+</meanElements>
+<tleParameters>
+<EPHEMERIS_TYPE>0</EPHEMERIS_TYPE>
+<CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE>
+<NORAD_CAT_ID>5</NORAD_CAT_ID>
+<ELEMENT_SET_NO>475</ELEMENT_SET_NO>
+<REV_AT_EPOCH>41366</REV_AT_EPOCH>
+<BSTAR>.28098E-4</BSTAR>
+<MEAN_MOTION_DOT>2.3E-7</MEAN_MOTION_DOT>
+<MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT>
+</tleParameters>
+</data>
+</segment>
+</body>
+</omm>
+</ndm>
+"#;
+        let tles = parse_xml(xml).unwrap();
+        assert_eq!(tles.len(), 1);
+        let kvn = parse_kvn(KVN).unwrap();
+        assert_eq!(tles[0].name(), kvn.name());
+        assert_eq!(tles[0].line1(), kvn.line1());
+        assert_eq!(tles[0].line2(), kvn.line2());
+
+        assert!(matches!(
+            parse_xml("<omm><EPOCH>2000</omm>"),
+            Err(OmmParseError::InvalidXml(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_parse_json() {
+        let json = r#"[{
+            "OBJECT_NAME": "VANGUARD 1",
+            "OBJECT_ID": "1958-002B",
+            "EPOCH": "2000-06-27T18:50:19.733568",
+            "MEAN_MOTION": 10.82419157,
+            "ECCENTRICITY": 0.1859667,
+            "INCLINATION": 34.2682,
+            "RA_OF_ASC_NODE": 348.7242,
+            "ARG_OF_PERICENTER": 331.7664,
+            "MEAN_ANOMALY": 19.3264,
+            "EPHEMERIS_TYPE": 0,
+            "CLASSIFICATION_TYPE": "U",
+            "NORAD_CAT_ID": 5,
+            "ELEMENT_SET_NO": 475,
+            "REV_AT_EPOCH": 41366,
+            "BSTAR": 2.8098e-5,
+            "MEAN_MOTION_DOT": 2.3e-7,
+            "MEAN_MOTION_DDOT": 0
+        }]"#;
+        let tles = parse_json(json).unwrap();
+        assert_eq!(tles.len(), 1);
+        let kvn = parse_kvn(KVN).unwrap();
+        assert_eq!(tles[0].name(), kvn.name());
+        assert_eq!(tles[0].line1(), kvn.line1());
+        assert_eq!(tles[0].line2(), kvn.line2());
+
+        assert!(matches!(
+            parse_json("[1, 2]"),
+            Err(OmmParseError::InvalidJson(_))
+        ));
+    }
+}
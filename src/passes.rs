@@ -0,0 +1,242 @@
+use alloc::vec::Vec;
+
+use crate::bodies::{Illumination, illumination, sun_position};
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::observer::Observer;
+use crate::sgp4::{PropagationError, SGP4};
+use crate::utils::radians;
+
+/// Time step of the coarse scan for horizon crossings, in minutes.
+///
+/// Passes shorter than this can be missed; for low earth orbits the shortest passes
+/// worth tracking last several minutes.
+const SCAN_STEP_MIN: f64 = 1.0;
+
+/// Sun elevation below which the sky is dark enough to see satellites (civil twilight).
+const TWILIGHT_ELEVATION_DEG: f64 = -6.0;
+
+/// A visibility window of a satellite above an observer's minimum elevation (see
+/// `Observer::with_min_elevation`), by default the horizon.
+///
+/// Times are in minutes since the epoch of the elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pass {
+    /// Acquisition of signal, when the satellite rises above the minimum elevation
+    pub aos: f64,
+    /// Time of the highest elevation
+    pub culmination: f64,
+    /// Loss of signal, when the satellite sets below the minimum elevation
+    pub los: f64,
+    /// Highest elevation in radians
+    pub max_elevation: f64,
+}
+
+/// Find the passes of a satellite over an observer between `start_min` and `stop_min`
+/// minutes since epoch.
+///
+/// The elevation is scanned at a coarse step, then the crossings of the minimum
+/// elevation of the observer are refined by bisection and the culmination by a golden
+/// section search, with light time correction if the observer has it on (see
+/// `Observer::with_light_time`). Passes in progress at `start_min` or `stop_min` are
+/// cut at those times. Passes that don't reach the minimum elevation aren't returned.
+pub fn find_passes(
+    sgp4: &SGP4,
+    observer: &Observer,
+    start_min: f64,
+    stop_min: f64,
+) -> Result<Vec<Pass>, PropagationError> {
+    // elevation above the minimum, so the crossings are its zeros
+    let min_elevation = observer.min_elevation();
+    let mut elevation = |t: f64| -> Result<f64, PropagationError> {
+        Ok(observer.observe(sgp4, t)?.look.elevation - min_elevation)
+    };
+
+    let mut passes = Vec::new();
+    let mut t = start_min;
+    let mut el = elevation(t)?;
+    let mut aos = (el > 0.0).then_some(start_min);
+    while t < stop_min {
+        let t_next = (t + SCAN_STEP_MIN).min(stop_min);
+        let el_next = elevation(t_next)?;
+        if (el > 0.0) != (el_next > 0.0) {
+            let crossing = bisect(&mut elevation, t, t_next)?;
+            match aos.take() {
+                Some(aos) => passes.push(culminate(&mut elevation, aos, crossing)?),
+                None => aos = Some(crossing),
+            }
+        }
+        (t, el) = (t_next, el_next);
+    }
+    if let Some(aos) = aos {
+        passes.push(culminate(&mut elevation, aos, stop_min)?);
+    }
+    for pass in &mut passes {
+        pass.max_elevation += min_elevation;
+    }
+    Ok(passes)
+}
+
+/// Whether a pass can be seen with the naked eye or binoculars: at some point during
+/// the pass the satellite is sunlit while the observer's sky is dark.
+///
+/// The pass is sampled every 30 seconds.
+pub fn is_visual(sgp4: &SGP4, observer: &Observer, pass: &Pass) -> Result<bool, PropagationError> {
+    let jdepoch = sgp4.jdepoch();
+    let steps = ((pass.los - pass.aos) * 2.0).ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        let t = pass.aos + (pass.los - pass.aos) * i as f64 / steps as f64;
+        let jd = jdepoch + t / DAY2MIN;
+        let sun = observer.look_angles(sun_position(jd), jd);
+        if sun.elevation > radians(TWILIGHT_ELEVATION_DEG) {
+            continue;
+        }
+        let r = sgp4.propagate(t)?.position_km;
+        if illumination(r, jd) == Illumination::Sunlit {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Refine the zero crossing of `elevation` between `t0` and `t1` to about a millisecond.
+pub(crate) fn bisect(
+    elevation: &mut impl FnMut(f64) -> Result<f64, PropagationError>,
+    mut t0: f64,
+    mut t1: f64,
+) -> Result<f64, PropagationError> {
+    let above0 = elevation(t0)? > 0.0;
+    while t1 - t0 > 1e-5 {
+        let mid = 0.5 * (t0 + t1);
+        if (elevation(mid)? > 0.0) == above0 {
+            t0 = mid;
+        } else {
+            t1 = mid;
+        }
+    }
+    Ok(0.5 * (t0 + t1))
+}
+
+/// Find the culmination of the pass between `aos` and `los` by golden section search.
+fn culminate(
+    elevation: &mut impl FnMut(f64) -> Result<f64, PropagationError>,
+    aos: f64,
+    los: f64,
+) -> Result<Pass, PropagationError> {
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (aos, los);
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut el_c, mut el_d) = (elevation(c)?, elevation(d)?);
+    while b - a > 1e-5 {
+        if el_c > el_d {
+            (b, d, el_d) = (d, c, el_c);
+            c = b - ratio * (b - a);
+            el_c = elevation(c)?;
+        } else {
+            (a, c, el_c) = (c, d, el_d);
+            d = a + ratio * (b - a);
+            el_d = elevation(d)?;
+        }
+    }
+    let culmination = 0.5 * (a + b);
+    Ok(Pass {
+        aos,
+        culmination,
+        los,
+        max_elevation: elevation(culmination)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::utils::WGSModel;
+
+    #[test]
+    fn test_find_passes() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+        let jdepoch = sgp4.jdepoch();
+
+        let passes = find_passes(&sgp4, &observer, 0.0, DAY2MIN).unwrap();
+        assert!(passes.len() >= 4);
+
+        // compare with a brute force scan every 10 seconds
+        let mut visible = 0;
+        let mut previous = false;
+        for i in 0..=(DAY2MIN * 6.0) as usize {
+            let t = i as f64 / 6.0;
+            let r = sgp4.propagate(t).unwrap().position_km;
+            let above = observer.look_angles(r, jdepoch + t / DAY2MIN).elevation > 0.0;
+            if above && !previous {
+                visible += 1;
+            }
+            previous = above;
+        }
+        assert_eq!(passes.len(), visible);
+
+        for pass in &passes {
+            assert!(pass.aos < pass.culmination && pass.culmination < pass.los);
+            assert!(pass.max_elevation > 0.0);
+            for t in [pass.aos, pass.los] {
+                let r = sgp4.propagate(t).unwrap().position_km;
+                let look = observer.look_angles(r, jdepoch + t / DAY2MIN);
+                assert!(look.elevation.abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_elevation() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let horizon = Observer::from_degrees(40.0, -105.0, 1.6);
+        let observer = horizon.with_min_elevation(radians(10.0));
+        let jdepoch = sgp4.jdepoch();
+
+        let all = find_passes(&sgp4, &horizon, 0.0, DAY2MIN).unwrap();
+        let passes = find_passes(&sgp4, &observer, 0.0, DAY2MIN).unwrap();
+        let high = all.iter().filter(|pass| pass.max_elevation > radians(10.0));
+        assert_eq!(passes.len(), high.clone().count());
+        assert!(passes.len() < all.len());
+
+        for (pass, full) in passes.iter().zip(high) {
+            // the same culmination, in a shorter window
+            assert!((pass.max_elevation - full.max_elevation).abs() < 1e-8);
+            assert!(pass.aos > full.aos && pass.los < full.los);
+            for t in [pass.aos, pass.los] {
+                let r = sgp4.propagate(t).unwrap().position_km;
+                let look = observer.look_angles(r, jdepoch + t / DAY2MIN);
+                assert!((look.elevation - radians(10.0)).abs() < 1e-4);
+            }
+            let r = sgp4.propagate(pass.culmination).unwrap().position_km;
+            assert!(observer.is_visible(r, jdepoch + pass.culmination / DAY2MIN));
+        }
+    }
+
+    #[test]
+    fn test_is_visual() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+        let jdepoch = sgp4.jdepoch();
+
+        let passes = find_passes(&sgp4, &observer, 0.0, 3.0 * DAY2MIN).unwrap();
+        let sun_elevation = |t: f64| {
+            let jd = jdepoch + t / DAY2MIN;
+            observer.look_angles(sun_position(jd), jd).elevation
+        };
+        for pass in &passes {
+            // daylight passes can't be seen
+            if sun_elevation(pass.aos) > 0.0 && sun_elevation(pass.los) > 0.0 {
+                assert!(!is_visual(&sgp4, &observer, pass).unwrap());
+            }
+        }
+        assert!(
+            passes
+                .iter()
+                .any(|pass| is_visual(&sgp4, &observer, pass).unwrap())
+        );
+    }
+}
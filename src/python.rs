@@ -0,0 +1,317 @@
+//! Python bindings mirroring the `Satrec` class of python-sgp4's `sgp4.api`, so that
+//! code written against python-sgp4 runs with few changes:
+//!
+//! ```python
+//! from sgp4_rust import Satrec, WGS72
+//!
+//! satellite = Satrec.twoline2rv(line1, line2, WGS72)
+//! e, r, v = satellite.sgp4(2458827, 0.362605)
+//! ```
+//!
+//! Build the extension module with maturin, or with `cargo rustc --release --features
+//! python --crate-type cdylib` and rename the library to `sgp4_rust.so`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::constants::*;
+use crate::sgp4::{OperationMode, PropagationResult, SGP4};
+use crate::time::{days2mdh, jday as jday_split};
+use crate::tle::TLE;
+use crate::utils::WGSModel;
+
+/// Gravity model constants, as in `sgp4.api`
+const WGS72OLD: u8 = 0;
+const WGS72: u8 = 1;
+const WGS84: u8 = 2;
+
+/// Result of a propagation as returned by python-sgp4: the error code, then the TEME
+/// position in km and velocity in km/s, which are NaN after an error.
+type Sgp4Output = (i32, (f64, f64, f64), (f64, f64, f64));
+
+/// A satellite initialized from a TLE, like `sgp4.api.Satrec`.
+#[pyclass(name = "Satrec", module = "sgp4_rust")]
+pub struct Satrec {
+    sgp4: SGP4,
+    /// Time and error code of the last propagation
+    t: f64,
+    error: i32,
+}
+
+impl Satrec {
+    fn output(&mut self, result: PropagationResult) -> Sgp4Output {
+        let ((r, v), error) = match result {
+            Ok(state) => {
+                self.t = state.epoch.minutes_since(self.sgp4.epoch());
+                ((state.position_km, state.velocity_km_s), 0)
+            }
+            Err(failure) => {
+                self.t = failure.tsince_min;
+                (([f64::NAN; 3], [f64::NAN; 3]), failure.code())
+            }
+        };
+        self.error = error;
+        (error, (r[0], r[1], r[2]), (v[0], v[1], v[2]))
+    }
+}
+
+#[pymethods]
+impl Satrec {
+    /// Initialize a satellite from the two lines of a TLE with the gravity model
+    /// `whichconst` (`WGS72OLD`, `WGS72` or `WGS84`) and operation mode `opsmode`
+    /// ('i' for improved or 'a' for AFSPC).
+    #[staticmethod]
+    #[pyo3(signature = (line1, line2, whichconst = WGS72, opsmode = 'i'))]
+    pub fn twoline2rv(line1: &str, line2: &str, whichconst: u8, opsmode: char) -> PyResult<Satrec> {
+        let model = match whichconst {
+            WGS72OLD => WGSModel::WGS_72_LOW_PRECISION,
+            WGS72 => WGSModel::WGS_72,
+            WGS84 => WGSModel::WGS_84,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "whichconst must be WGS72OLD, WGS72 or WGS84",
+                ));
+            }
+        };
+        let operation_mode = match opsmode {
+            'a' => OperationMode::Afspc,
+            'i' => OperationMode::Improved,
+            _ => return Err(PyValueError::new_err("opsmode must be 'a' or 'i'")),
+        };
+        let tle = TLE::new(line1.into(), line2.into())
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+        let sgp4 = SGP4::from_tle(model, operation_mode, tle)
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+        Ok(Satrec {
+            sgp4,
+            t: 0.0,
+            error: 0,
+        })
+    }
+
+    /// Propagate to the Julian date `jd + fr` in UTC.
+    pub fn sgp4(&mut self, jd: f64, fr: f64) -> Sgp4Output {
+        let result = self.sgp4.propagate_jd(jd, fr);
+        self.output(result)
+    }
+
+    /// Propagate to `tsince` minutes since epoch.
+    pub fn sgp4_tsince(&mut self, tsince: f64) -> Sgp4Output {
+        let result = self.sgp4.propagate(tsince);
+        self.output(result)
+    }
+
+    /// Propagate to each Julian date `jd[i] + fr[i]`, returning lists of error codes,
+    /// positions and velocities.
+    #[allow(clippy::type_complexity)]
+    pub fn sgp4_array(
+        &mut self,
+        jd: Vec<f64>,
+        fr: Vec<f64>,
+    ) -> PyResult<(Vec<i32>, Vec<(f64, f64, f64)>, Vec<(f64, f64, f64)>)> {
+        if jd.len() != fr.len() {
+            return Err(PyValueError::new_err("jd and fr must have the same length"));
+        }
+        let mut output = (Vec::new(), Vec::new(), Vec::new());
+        for (jd, fr) in jd.into_iter().zip(fr) {
+            let (e, r, v) = self.sgp4(jd, fr);
+            output.0.push(e);
+            output.1.push(r);
+            output.2.push(v);
+        }
+        Ok(output)
+    }
+
+    #[getter]
+    fn satnum(&self) -> u64 {
+        self.sgp4.satrec().satnum
+    }
+
+    #[getter]
+    fn classification(&self) -> char {
+        match self.sgp4.satrec().classification {
+            Some(crate::tle::Classification::Classified) => 'C',
+            _ => 'U',
+        }
+    }
+
+    #[getter]
+    fn intldesg(&self) -> String {
+        self.sgp4.satrec().intldesg.trim().to_string()
+    }
+
+    #[getter]
+    fn epochyr(&self) -> u64 {
+        self.sgp4.satrec().epochyr % 100
+    }
+
+    #[getter]
+    fn epochdays(&self) -> f64 {
+        self.sgp4.satrec().epochdays
+    }
+
+    #[getter]
+    fn jdsatepoch(&self) -> f64 {
+        self.sgp4.satrec().epoch.jd()
+    }
+
+    #[getter(jdsatepochF)]
+    fn jdsatepoch_f(&self) -> f64 {
+        self.sgp4.satrec().epoch.fraction()
+    }
+
+    #[getter]
+    fn elnum(&self) -> u64 {
+        self.sgp4.satrec().elnum
+    }
+
+    #[getter]
+    fn revnum(&self) -> u64 {
+        self.sgp4.satrec().revnum
+    }
+
+    #[getter]
+    fn ndot(&self) -> f64 {
+        self.sgp4.satrec().ndot
+    }
+
+    #[getter]
+    fn nddot(&self) -> f64 {
+        self.sgp4.satrec().nddot
+    }
+
+    #[getter]
+    fn bstar(&self) -> f64 {
+        self.sgp4.satrec().bstar
+    }
+
+    #[getter]
+    fn inclo(&self) -> f64 {
+        self.sgp4.satrec().inclo
+    }
+
+    #[getter]
+    fn nodeo(&self) -> f64 {
+        self.sgp4.satrec().nodeo
+    }
+
+    #[getter]
+    fn ecco(&self) -> f64 {
+        self.sgp4.satrec().ecco
+    }
+
+    #[getter]
+    fn argpo(&self) -> f64 {
+        self.sgp4.satrec().argpo
+    }
+
+    #[getter]
+    fn mo(&self) -> f64 {
+        self.sgp4.satrec().mo
+    }
+
+    #[getter]
+    fn no_kozai(&self) -> f64 {
+        self.sgp4.satrec().no_kozai
+    }
+
+    #[getter]
+    fn no_unkozai(&self) -> f64 {
+        self.sgp4.satrec().no
+    }
+
+    #[getter]
+    fn a(&self) -> f64 {
+        self.sgp4.satrec().a
+    }
+
+    #[getter]
+    fn alta(&self) -> f64 {
+        self.sgp4.satrec().alta
+    }
+
+    #[getter]
+    fn altp(&self) -> f64 {
+        self.sgp4.satrec().altp
+    }
+
+    #[getter]
+    fn t(&self) -> f64 {
+        self.t
+    }
+
+    #[getter]
+    fn error(&self) -> i32 {
+        self.error
+    }
+
+    #[getter]
+    fn method(&self) -> char {
+        // deep space for periods of 225 minutes or more
+        if TWOPI / self.sgp4.satrec().no >= 225.0 {
+            'd'
+        } else {
+            'n'
+        }
+    }
+
+    #[getter]
+    fn operationmode(&self) -> char {
+        match self.sgp4.operation_mode() {
+            OperationMode::Afspc => 'a',
+            OperationMode::Improved => 'i',
+        }
+    }
+}
+
+/// Julian date of a UTC date, split into the date at midnight and the day fraction.
+#[pyfunction]
+pub fn jday(year: u64, mon: u64, day: u64, hr: u64, minute: u64, sec: f64) -> (f64, f64) {
+    jday_split(year, (mon, day, hr, minute, sec))
+}
+
+/// (month, day, hour, minute, second) of a fractional day of the year.
+#[pyfunction]
+pub fn days2mdhms(year: u64, days: f64) -> (u64, u64, u64, u64, f64) {
+    days2mdh(year, days)
+}
+
+#[pymodule]
+fn sgp4_rust(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Satrec>()?;
+    m.add_function(wrap_pyfunction!(jday, m)?)?;
+    m.add_function(wrap_pyfunction!(days2mdhms, m)?)?;
+    m.add("WGS72OLD", WGS72OLD)?;
+    m.add("WGS72", WGS72)?;
+    m.add("WGS84", WGS84)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ISS_LINE1, ISS_LINE2, VANGUARD_LINE1, VANGUARD_LINE2};
+
+    #[test]
+    fn test_satrec() {
+        let mut satellite = Satrec::twoline2rv(VANGUARD_LINE1, VANGUARD_LINE2, WGS72, 'i').unwrap();
+        assert_eq!(satellite.satnum(), 5);
+        assert_eq!(satellite.epochyr(), 0);
+        assert_eq!(satellite.method(), 'n');
+
+        let (e, r, _) = satellite.sgp4_tsince(360.0);
+        assert_eq!(e, 0);
+        assert!((r.0 + 7154.03120202).abs() < 1e-7);
+        let (jd, fr) = (satellite.jdsatepoch(), satellite.jdsatepoch_f());
+        let (e, r_jd, _) = satellite.sgp4(jd, fr + 360.0 / DAY2MIN);
+        assert_eq!(e, 0);
+        assert!((r_jd.0 - r.0).abs() < 1e-6);
+
+        let mut satellite = Satrec::twoline2rv(ISS_LINE1, ISS_LINE2, WGS72, 'i').unwrap();
+        let (e, r, _) = satellite.sgp4_tsince(1e7);
+        assert_ne!(e, 0);
+        assert!(r.0.is_nan());
+        assert_eq!(satellite.error(), e);
+        assert!(Satrec::twoline2rv("1 00005U", "2 00005", WGS72, 'i').is_err());
+    }
+}
@@ -0,0 +1,289 @@
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::state::State;
+use crate::stm::Matrix6;
+use crate::utils::{cross, dot, mag};
+
+/// Determinants of the Clohessy-Wiltshire position partials below this make a transfer
+/// time a whole number of revolutions, where the in-plane motion can't be targeted
+const SINGULAR: f64 = 1e-12;
+
+/// Relative position and velocity in the radial, in-track and cross-track (RIC, also
+/// RTN or RSW) frame of a reference satellite.
+///
+/// Radial points away from the center of the earth, cross-track along the angular
+/// momentum of the reference, and in-track completes the frame, along the velocity for
+/// circular orbits. The velocity is measured in the rotating frame, as seen from the
+/// reference.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ric {
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+}
+
+#[cfg(feature = "glam")]
+impl Ric {
+    /// Relative position in km as a glam vector
+    pub fn position_dvec3(&self) -> glam::DVec3 {
+        self.position_km.into()
+    }
+
+    /// Relative velocity in km/s as a glam vector
+    pub fn velocity_dvec3(&self) -> glam::DVec3 {
+        self.velocity_km_s.into()
+    }
+}
+
+/// Rotation from the frame of a state to its RIC frame: the rows are the radial,
+/// in-track and cross-track unit vectors.
+pub fn ric_rotation(r: [f64; 3], v: [f64; 3]) -> [[f64; 3]; 3] {
+    let radial = unit(r);
+    let cross_track = unit(cross(r, v));
+    [radial, cross(cross_track, radial), cross_track]
+}
+
+/// Express `other` relative to `reference` in the RIC frame of `reference`. Both states
+/// are to be at the same epoch and in the same inertial frame, e.g. TEME.
+pub fn to_ric(reference: &State, other: &State) -> Ric {
+    let (r, v) = (reference.position_km, reference.velocity_km_s);
+    let rotation = ric_rotation(r, v);
+    let dr = [0, 1, 2].map(|i| other.position_km[i] - r[i]);
+    let dv = [0, 1, 2].map(|i| other.velocity_km_s[i] - v[i]);
+    // remove the rotation of the frame, at the angular velocity of the reference
+    let omega = cross(r, v).map(|h| h / dot(r, r));
+    let omega_dr = cross(omega, dr);
+    let dv = [0, 1, 2].map(|i| dv[i] - omega_dr[i]);
+    Ric {
+        position_km: rotation.map(|axis| dot(axis, dr)),
+        velocity_km_s: rotation.map(|axis| dot(axis, dv)),
+    }
+}
+
+/// The inertial position and velocity of a state given relative to `reference` in its
+/// RIC frame. The inverse of `to_ric`.
+pub fn from_ric(reference: &State, ric: &Ric) -> ([f64; 3], [f64; 3]) {
+    let (r, v) = (reference.position_km, reference.velocity_km_s);
+    let rotation = ric_rotation(r, v);
+    let inertial = |x: [f64; 3]| [0, 1, 2].map(|i| (0..3).map(|k| rotation[k][i] * x[k]).sum());
+    let dr: [f64; 3] = inertial(ric.position_km);
+    let dv: [f64; 3] = inertial(ric.velocity_km_s);
+    let omega = cross(r, v).map(|h| h / dot(r, r));
+    let omega_dr = cross(omega, dr);
+    (
+        [0, 1, 2].map(|i| r[i] + dr[i]),
+        [0, 1, 2].map(|i| v[i] + dv[i] + omega_dr[i]),
+    )
+}
+
+/// Mean motion in rad/s of the circular orbit the Clohessy-Wiltshire equations assume
+/// for a chief state: its angular velocity, |r x v| / |r|^2.
+pub fn cw_mean_motion(chief: &State) -> f64 {
+    let (r, v) = (chief.position_km, chief.velocity_km_s);
+    mag(cross(r, v)) / dot(r, r)
+}
+
+/// State transition matrix of the Clohessy-Wiltshire (Hill) equations over `t_s`
+/// seconds, for a chief on a circular orbit with mean motion `n` in rad/s.
+///
+/// The states are RIC positions in km and velocities in km/s, ordered as in `Ric`.
+/// The equations linearize the relative motion, so they hold for deputies close to
+/// the chief (compared to its radius) and for chief orbits near circular.
+pub fn cw_stm(n: f64, t_s: f64) -> Matrix6 {
+    let (s, c) = (n * t_s).sin_cos();
+    let nt = n * t_s;
+    [
+        [4.0 - 3.0 * c, 0.0, 0.0, s / n, 2.0 * (1.0 - c) / n, 0.0],
+        [
+            6.0 * (s - nt),
+            1.0,
+            0.0,
+            -2.0 * (1.0 - c) / n,
+            (4.0 * s - 3.0 * nt) / n,
+            0.0,
+        ],
+        [0.0, 0.0, c, 0.0, 0.0, s / n],
+        [3.0 * n * s, 0.0, 0.0, c, 2.0 * s, 0.0],
+        [-6.0 * n * (1.0 - c), 0.0, 0.0, -2.0 * s, 4.0 * c - 3.0, 0.0],
+        [0.0, 0.0, -n * s, 0.0, 0.0, c],
+    ]
+}
+
+/// Propagate a relative state `t_s` seconds with the Clohessy-Wiltshire equations. See
+/// `cw_stm`.
+pub fn cw_propagate(n: f64, ric: &Ric, t_s: f64) -> Ric {
+    let stm = cw_stm(n, t_s);
+    let x = [
+        ric.position_km[0],
+        ric.position_km[1],
+        ric.position_km[2],
+        ric.velocity_km_s[0],
+        ric.velocity_km_s[1],
+        ric.velocity_km_s[2],
+    ];
+    let row = |i: usize| (0..6).map(|j| stm[i][j] * x[j]).sum();
+    Ric {
+        position_km: [row(0), row(1), row(2)],
+        velocity_km_s: [row(3), row(4), row(5)],
+    }
+}
+
+/// The two impulses of a Clohessy-Wiltshire transfer, from `cw_transfer`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CwTransfer {
+    /// Change of velocity at departure in km/s, in the RIC frame
+    pub departure_delta_v_km_s: [f64; 3],
+    /// Change of velocity at arrival in km/s, to match the velocity of the target
+    pub arrival_delta_v_km_s: [f64; 3],
+}
+
+/// Impulses that take a deputy from the relative state `initial` to the relative
+/// position of `target` in `t_s` seconds and then match its velocity, with the
+/// Clohessy-Wiltshire equations for a chief mean motion `n` in rad/s.
+///
+/// Returns `None` for transfer times of a whole number of revolutions, when every
+/// departure velocity returns to the same in-plane position, and for half revolutions
+/// out of plane.
+pub fn cw_transfer(n: f64, initial: &Ric, target: &Ric, t_s: f64) -> Option<CwTransfer> {
+    let stm = cw_stm(n, t_s);
+    let block =
+        |row: usize, column: usize| [0, 1, 2].map(|i| [0, 1, 2].map(|j| stm[row + i][column + j]));
+    let (phi_rr, phi_rv, phi_vr, phi_vv) = (block(0, 0), block(0, 3), block(3, 0), block(3, 3));
+    let apply = |m: [[f64; 3]; 3], x: [f64; 3]| m.map(|row| dot(row, x));
+
+    // the departure velocity that reaches the target position
+    let free = apply(phi_rr, initial.position_km);
+    let miss = [0, 1, 2].map(|i| target.position_km[i] - free[i]);
+    let velocity = apply(invert3(phi_rv)?, miss);
+
+    let arrival = apply(phi_vr, initial.position_km);
+    let arrival_from_velocity = apply(phi_vv, velocity);
+    Some(CwTransfer {
+        departure_delta_v_km_s: [0, 1, 2].map(|i| velocity[i] - initial.velocity_km_s[i]),
+        arrival_delta_v_km_s: [0, 1, 2]
+            .map(|i| target.velocity_km_s[i] - arrival[i] - arrival_from_velocity[i]),
+    })
+}
+
+/// Invert a 3x3 matrix by its adjugate, or `None` if it is singular.
+fn invert3(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let determinant: f64 = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum();
+    let scale = m.iter().flatten().fold(0.0_f64, |max, x| max.max(x.abs()));
+    if determinant.abs() <= SINGULAR * scale.powi(3) {
+        return None;
+    }
+    // the inverse is the transposed cofactor matrix over the determinant
+    Some([0, 1, 2].map(|i| [0, 1, 2].map(|j| cofactor(j, i) / determinant)))
+}
+
+fn unit(v: [f64; 3]) -> [f64; 3] {
+    let magnitude = mag(v);
+    v.map(|x| x / magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::PI;
+
+    use super::*;
+    use crate::constants::TWOPI;
+    use crate::state::Frame;
+    use crate::time::Epoch;
+
+    fn state(position_km: [f64; 3], velocity_km_s: [f64; 3]) -> State {
+        State {
+            epoch: Epoch::default(),
+            position_km,
+            velocity_km_s,
+            frame: Frame::Teme,
+        }
+    }
+
+    #[test]
+    fn test_ric() {
+        // circular equatorial orbit at 7000 km, seen at x = 7000 km
+        let speed = (398600.4418f64 / 7000.0).sqrt();
+        let reference = state([7000.0, 0.0, 0.0], [0.0, speed, 0.0]);
+
+        let other = state([7001.0, 2.0, 3.0], [0.0, speed, 0.1]);
+        let ric = to_ric(&reference, &other);
+        assert_eq!(ric.position_km, [1.0, 2.0, 3.0]);
+        // moving with the frame, apart from the offsets in its rotation
+        let omega = speed / 7000.0;
+        let expected = [2.0 * omega, -omega, 0.1];
+        for (velocity, expected) in ric.velocity_km_s.iter().zip(expected) {
+            assert!((velocity - expected).abs() < 1e-12);
+        }
+
+        let (r, v) = from_ric(&reference, &ric);
+        for i in 0..3 {
+            assert!((r[i] - other.position_km[i]).abs() < 1e-9);
+            assert!((v[i] - other.velocity_km_s[i]).abs() < 1e-12);
+        }
+
+        // a point on the same circular orbit ahead of the reference has no relative
+        // velocity in the rotating frame
+        let angle = 1e-3_f64;
+        let ahead = state(
+            [7000.0 * angle.cos(), 7000.0 * angle.sin(), 0.0],
+            [-speed * angle.sin(), speed * angle.cos(), 0.0],
+        );
+        let ric = to_ric(&reference, &ahead);
+        assert!(ric.position_km[1] > 6.99 && ric.position_km[0] < 0.0);
+        assert!(mag(ric.velocity_km_s) < 1e-5);
+    }
+
+    #[test]
+    fn test_cw() {
+        let n = 0.0011;
+        let period = TWOPI / n;
+        // a deputy offset in-track stays put, one offset radially drifts
+        let ric = Ric {
+            position_km: [0.0, 1.0, 0.0],
+            ..Ric::default()
+        };
+        assert_eq!(cw_propagate(n, &ric, 1000.0), ric);
+        let ric = Ric {
+            position_km: [1.0, 0.0, 0.0],
+            ..Ric::default()
+        };
+        let drifted = cw_propagate(n, &ric, period);
+        assert!((drifted.position_km[0] - 1.0).abs() < 1e-9);
+        assert!((drifted.position_km[1] + 12.0 * PI).abs() < 1e-9);
+
+        // a 2x1 ellipse around the chief is periodic
+        let ellipse = Ric {
+            position_km: [1.0, 0.0, 0.5],
+            velocity_km_s: [0.0, -2.0 * n, 0.0],
+        };
+        let later = cw_propagate(n, &ellipse, period);
+        for i in 0..3 {
+            assert!((later.position_km[i] - ellipse.position_km[i]).abs() < 1e-9);
+        }
+
+        // a transfer reaches the target
+        let target = Ric {
+            position_km: [0.0, -0.1, 0.0],
+            ..Ric::default()
+        };
+        let transfer = cw_transfer(n, &ellipse, &target, 0.3 * period).unwrap();
+        let departure = Ric {
+            velocity_km_s: [0, 1, 2]
+                .map(|i| ellipse.velocity_km_s[i] + transfer.departure_delta_v_km_s[i]),
+            ..ellipse
+        };
+        let arrival = cw_propagate(n, &departure, 0.3 * period);
+        for i in 0..3 {
+            assert!((arrival.position_km[i] - target.position_km[i]).abs() < 1e-9);
+            let velocity = arrival.velocity_km_s[i] + transfer.arrival_delta_v_km_s[i];
+            assert!(velocity.abs() < 1e-12);
+        }
+        assert_eq!(cw_transfer(n, &ellipse, &target, period), None);
+    }
+}
@@ -0,0 +1,257 @@
+use alloc::vec::Vec;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::observer::Observer;
+use crate::passes::bisect;
+use crate::relative::ric_rotation;
+use crate::sgp4::{PropagationError, SGP4};
+use crate::utils::{angle, dot, mag};
+
+/// Time step of the coarse scan for the edges of the field of view, in minutes.
+///
+/// Accesses shorter than this can be missed, as can those of narrow cones, which the
+/// target crosses quickly; use a wider cone to screen for them.
+const SCAN_STEP_MIN: f64 = 1.0 / 6.0;
+
+/// Where a sensor is mounted and where its boresight points.
+#[derive(Debug, Clone, Copy)]
+pub enum Mount<'a> {
+    /// At a ground site, pointing at an azimuth (clockwise from north) and elevation
+    /// in radians. Targets below the minimum elevation of the observer are hidden.
+    Ground {
+        observer: Observer,
+        azimuth: f64,
+        elevation: f64,
+    },
+    /// On a satellite, pointing along a direction in its radial, in-track and
+    /// cross-track frame (see `relative::ric_rotation`), e.g. `[-1.0, 0.0, 0.0]` for
+    /// nadir. Targets behind the earth are hidden.
+    Satellite {
+        sgp4: &'a SGP4,
+        boresight_ric: [f64; 3],
+    },
+}
+
+/// A sensor with a conical field of view: a target is in view within `half_angle`
+/// radians of the boresight.
+#[derive(Debug, Clone, Copy)]
+pub struct Sensor<'a> {
+    pub mount: Mount<'a>,
+    pub half_angle: f64,
+}
+
+/// An interval during which a target is in the field of view of a sensor, from
+/// `find_access`.
+///
+/// Times are in minutes since the epoch of the elements of the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Access {
+    /// When the target enters the field of view
+    pub start: f64,
+    /// When the target leaves the field of view
+    pub stop: f64,
+}
+
+impl Sensor<'_> {
+    /// Angle in radians between the boresight and the line of sight to a target at
+    /// TEME position `r` (km) at `jd` (UTC), and whether the line of sight is clear:
+    /// above the minimum elevation of a ground site, or not through the earth.
+    pub fn off_boresight(&self, r: [f64; 3], jd: f64) -> Result<(f64, bool), PropagationError> {
+        match self.mount {
+            Mount::Ground {
+                observer,
+                azimuth,
+                elevation,
+            } => {
+                let look = observer.look_angles(r, jd);
+                let cos_angle = elevation.sin() * look.elevation.sin()
+                    + elevation.cos() * look.elevation.cos() * (azimuth - look.azimuth).cos();
+                let clear = look.elevation >= observer.min_elevation();
+                Ok((cos_angle.clamp(-1.0, 1.0).acos(), clear))
+            }
+            Mount::Satellite {
+                sgp4,
+                boresight_ric,
+            } => {
+                let t = (jd - sgp4.jdepoch()) * DAY2MIN;
+                let state = sgp4.propagate(t)?;
+                let (position, velocity) = (state.position_km, state.velocity_km_s);
+                let rotation = ric_rotation(position, velocity);
+                let boresight = [0, 1, 2].map(|i| {
+                    (0..3)
+                        .map(|k| rotation[k][i] * boresight_ric[k])
+                        .sum::<f64>()
+                });
+                let line_of_sight = [0, 1, 2].map(|i| r[i] - position[i]);
+                let clear = !through_earth(position, line_of_sight);
+                Ok((angle(boresight, line_of_sight), clear))
+            }
+        }
+    }
+
+    /// Whether a target at TEME position `r` (km) at `jd` (UTC) is in the field of
+    /// view, with a clear line of sight.
+    pub fn in_view(&self, r: [f64; 3], jd: f64) -> Result<bool, PropagationError> {
+        let (off_boresight, clear) = self.off_boresight(r, jd)?;
+        Ok(clear && off_boresight <= self.half_angle)
+    }
+}
+
+/// Find when a target satellite is in the field of view of a sensor between
+/// `start_min` and `stop_min` minutes since the epoch of the target.
+///
+/// Like `passes::find_passes`, the geometry is scanned at a coarse step and the edges
+/// are refined by bisection. Accesses in progress at `start_min` or `stop_min` are cut
+/// at those times.
+pub fn find_access(
+    sensor: &Sensor,
+    target: &SGP4,
+    start_min: f64,
+    stop_min: f64,
+) -> Result<Vec<Access>, PropagationError> {
+    let jdepoch = target.jdepoch();
+    // positive in view: the margin inside the cone, where the line of sight is clear
+    let mut margin = |t: f64| -> Result<f64, PropagationError> {
+        let r = target.propagate(t)?.position_km;
+        let (off_boresight, clear) = sensor.off_boresight(r, jdepoch + t / DAY2MIN)?;
+        Ok(if clear {
+            sensor.half_angle - off_boresight
+        } else {
+            -1.0
+        })
+    };
+
+    let mut accesses = Vec::new();
+    let mut t = start_min;
+    let mut inside = margin(t)? > 0.0;
+    let mut start = inside.then_some(start_min);
+    while t < stop_min {
+        let t_next = (t + SCAN_STEP_MIN).min(stop_min);
+        let inside_next = margin(t_next)? > 0.0;
+        if inside != inside_next {
+            let edge = bisect(&mut margin, t, t_next)?;
+            match start.take() {
+                Some(start) => accesses.push(Access { start, stop: edge }),
+                None => start = Some(edge),
+            }
+        }
+        (t, inside) = (t_next, inside_next);
+    }
+    if let Some(start) = start {
+        accesses.push(Access {
+            start,
+            stop: stop_min,
+        });
+    }
+    Ok(accesses)
+}
+
+/// Whether the segment from `r` along `line_of_sight` passes through the earth, taken
+/// as a sphere of the equatorial radius.
+fn through_earth(r: [f64; 3], line_of_sight: [f64; 3]) -> bool {
+    let length = mag(line_of_sight);
+    // fraction of the segment closest to the center of the earth
+    let s = (-dot(r, line_of_sight) / (length * length)).clamp(0.0, 1.0);
+    mag([0, 1, 2].map(|i| r[i] + s * line_of_sight[i])) < WGS84_RE
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::PI;
+
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::passes::find_passes;
+    use crate::sgp4::OperationMode;
+    use crate::utils::{WGSModel, radians};
+
+    #[test]
+    fn test_ground_sensor() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let observer = Observer::from_degrees(40.0, -105.0, 1.6);
+
+        // a sensor looking straight up sees the whole sky
+        let zenith = Sensor {
+            mount: Mount::Ground {
+                observer,
+                azimuth: 0.0,
+                elevation: HALFPI,
+            },
+            half_angle: HALFPI,
+        };
+        let accesses = find_access(&zenith, &sgp4, 0.0, DAY2MIN).unwrap();
+        let passes = find_passes(&sgp4, &observer, 0.0, DAY2MIN).unwrap();
+        assert_eq!(accesses.len(), passes.len());
+        for (access, pass) in accesses.iter().zip(&passes) {
+            assert!((access.start - pass.aos).abs() < 1e-3);
+            assert!((access.stop - pass.los).abs() < 1e-3);
+        }
+
+        // a narrow cone at the culmination of the highest pass
+        let pass = passes
+            .iter()
+            .max_by(|a, b| a.max_elevation.total_cmp(&b.max_elevation))
+            .unwrap();
+        let jd = sgp4.jdepoch() + pass.culmination / DAY2MIN;
+        let r = sgp4.propagate(pass.culmination).unwrap().position_km;
+        let look = observer.look_angles(r, jd);
+        let narrow = Sensor {
+            mount: Mount::Ground {
+                observer,
+                azimuth: look.azimuth,
+                elevation: look.elevation,
+            },
+            half_angle: radians(5.0),
+        };
+        assert!(narrow.off_boresight(r, jd).unwrap().0 < 1e-6);
+        let accesses = find_access(&narrow, &sgp4, pass.aos, pass.los).unwrap();
+        assert_eq!(accesses.len(), 1);
+        let access = accesses[0];
+        assert!(access.start < pass.culmination && pass.culmination < access.stop);
+        assert!(access.start > pass.aos && access.stop < pass.los);
+    }
+
+    #[test]
+    fn test_satellite_sensor() {
+        let sgp4 = iss(WGSModel::WGS_84);
+        let offset = |degrees: f64| {
+            let mut elements = sgp4.elements();
+            elements.mo += radians(degrees);
+            SGP4::from_elements(WGSModel::WGS_84, OperationMode::Improved, &elements).unwrap()
+        };
+        let (ahead, behind) = (offset(0.5), offset(-0.5));
+
+        // a camera looking forward along the track
+        let forward = Sensor {
+            mount: Mount::Satellite {
+                sgp4: &sgp4,
+                boresight_ric: [0.0, 1.0, 0.0],
+            },
+            half_angle: radians(10.0),
+        };
+        let accesses = find_access(&forward, &ahead, 0.0, 180.0).unwrap();
+        assert_eq!(
+            accesses,
+            [Access {
+                start: 0.0,
+                stop: 180.0
+            }]
+        );
+        assert!(
+            find_access(&forward, &behind, 0.0, 180.0)
+                .unwrap()
+                .is_empty()
+        );
+
+        // a satellite on the other side of the earth is hidden even in a wide cone
+        let opposite = offset(180.0);
+        let wide = Sensor {
+            half_angle: PI,
+            ..forward
+        };
+        let r = opposite.propagate(0.0).unwrap().position_km;
+        assert!(!wide.in_view(r, sgp4.jdepoch()).unwrap());
+    }
+}
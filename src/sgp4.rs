@@ -1,53 +1,236 @@
-use std::error::Error;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use core::fmt;
 
+use crate::coe::{ClassicalElements, rv2coe};
 use crate::constants::*;
+use crate::deep_space::{DeepSpace, DsInitInput, RESONANCE_STEP_MIN};
+use crate::elements::{Elements, kozai_correction};
+use crate::frames::teme_to_geodetic;
+use crate::groundtrack::GroundTrackPoint;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::state::{Frame, State};
+use crate::time::{Epoch, ToJday, days2mdh, gstime, gstime_afspc};
+use crate::tle::*;
 use crate::utils::*;
 
-pub enum TypeRun {
+/// Run schedule of `SGP4::run`, one per run type of the reference driver: the start,
+/// stop and step in minutes since epoch that `twoline2rv` returns.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RunSchedule {
+    /// `CATALOG_SCHEDULE`, a day either side of the epoch in steps of 10 minutes
+    #[default]
     Catalog,
+    /// The start, stop and step following column 69 of line 2, as in SGP4-VER.TLE
     Verification,
-    FromJD,
-    Manual,
+    /// From the start to the stop date set with `SGP4::set_jd_from_ymdhms` or
+    /// `SGP4::set_jd_from_yr_doy`, in minutes since the epoch of the TLE, in steps of
+    /// `step` minutes
+    FromJD { step: f64 },
+    /// Times chosen by the caller, in minutes since epoch
+    Manual { start: f64, stop: f64, step: f64 },
 }
 
-#[derive(Default)]
-pub enum Classification {
+/// Formulation of the reference implementation's 'a'/'i' operation mode switch.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperationMode {
+    /// 'a': the sidereal time of the AFSPC code (`gstime_afspc`), and the node of
+    /// deep space satellites kept in range as AFSPC does for low inclinations
+    Afspc,
+    /// 'i': the sidereal time of the IAU-82 model (`gstime`) and the corrected node
     #[default]
-    Unclassified,
-    Classified,
-}
-
-impl Classification {
-    fn from(c: char) -> Option<Classification> {
-        match c {
-            'U' => Some(Classification::Unclassified),
-            'C' => Some(Classification::Classified),
-            _ => None,
-        }
-    }
+    Improved,
 }
 
+/// Errors of the propagator, one per error number of the reference implementation, and
+/// those of the strict resonance integration (see `ResonanceOptions::strict`), the
+/// validity window and the time steps of batches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PropagationError {
+    /// Mean eccentricity out of [-0.001, 1)
     InvalidElements,
+    /// Mean motion below zero
     NegativeMeanMotion,
+    /// Eccentricity after the lunar-solar perturbations out of [0, 1]
     EccentricityOutOfRange,
+    /// Semilatus rectum below zero
     NegativeSemilatusRectum,
+    /// The satellite is below the surface of the earth
     OrbitalDecay,
+    /// The resonance integration of a deep space orbit diverged, in strict mode only
+    ResonanceDiverged,
+    /// The time is outside of the validity window of the elements, in strict mode only
+    StaleElements,
+    /// The time step of a batch is zero, negative or not finite, or the blend window of
+    /// `Catalog::ephemeris` is negative or not finite
+    InvalidStep,
+}
+
+impl PropagationError {
+    /// Error number of `satrec.error` in the reference implementation, 1 to 6. Error 5,
+    /// sub-orbital epoch elements, is no longer raised by the reference; 7, a diverged
+    /// resonance integration, 8, stale elements, and 9, an invalid time step, are not
+    /// among its errors.
+    pub fn code(self) -> i32 {
+        match self {
+            PropagationError::InvalidElements => 1,
+            PropagationError::NegativeMeanMotion => 2,
+            PropagationError::EccentricityOutOfRange => 3,
+            PropagationError::NegativeSemilatusRectum => 4,
+            PropagationError::OrbitalDecay => 6,
+            PropagationError::ResonanceDiverged => 7,
+            PropagationError::StaleElements => 8,
+            PropagationError::InvalidStep => 9,
+        }
+    }
+
+    /// The error of an error number of the reference implementation, or `None` for 0
+    /// (no error) and unknown numbers.
+    pub fn from_code(code: i32) -> Option<PropagationError> {
+        match code {
+            1 => Some(PropagationError::InvalidElements),
+            2 => Some(PropagationError::NegativeMeanMotion),
+            3 => Some(PropagationError::EccentricityOutOfRange),
+            4 => Some(PropagationError::NegativeSemilatusRectum),
+            6 => Some(PropagationError::OrbitalDecay),
+            7 => Some(PropagationError::ResonanceDiverged),
+            8 => Some(PropagationError::StaleElements),
+            9 => Some(PropagationError::InvalidStep),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PropagationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            PropagationError::InvalidElements => "mean elements out of range",
+            PropagationError::NegativeMeanMotion => "negative mean motion",
+            PropagationError::EccentricityOutOfRange => "perturbed eccentricity out of range",
+            PropagationError::NegativeSemilatusRectum => "negative semilatus rectum",
+            PropagationError::OrbitalDecay => "satellite has decayed",
+            PropagationError::ResonanceDiverged => "resonance integration diverged",
+            PropagationError::StaleElements => "outside of the validity window of the elements",
+            PropagationError::InvalidStep => "time step must be positive and finite",
+        };
+        write!(f, "{message} (error {})", self.code())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PropagationError {}
+
+/// Mean elements of `SGP4::secular`, in earth radii and radians.
+struct Secular {
+    am: f64,
+    nm: f64,
+    em: f64,
+    inclm: f64,
+    nodem: f64,
+    argpm: f64,
+    mm: f64,
+    /// Mean argument of latitude, not wrapped to [0, 2pi)
+    arglat: f64,
+}
+
+/// A propagation error with the satellite and time it happened at, so failures in runs
+/// over many satellites can be traced back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropagationFailure {
+    pub satnum: u64,
+    /// Epoch of the elements
+    pub epoch: Epoch,
+    /// Time of the failed propagation in minutes since epoch
+    pub tsince_min: f64,
+    pub error: PropagationError,
+}
+
+impl PropagationFailure {
+    /// Error number of the reference implementation, see `PropagationError::code`.
+    pub fn code(&self) -> i32 {
+        self.error.code()
+    }
+}
+
+impl fmt::Display for PropagationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "satellite {} at {} min since epoch JD {:.8}: {}",
+            self.satnum,
+            self.tsince_min,
+            self.epoch.julian_date(),
+            self.error
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PropagationFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<PropagationFailure> for PropagationError {
+    fn from(failure: PropagationFailure) -> Self {
+        failure.error
+    }
+}
+
+/// Errors returned when initializing the propagator from a TLE.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sgp4Error {
+    Parse(TleParseError),
+    Propagation(PropagationError),
+    /// The start date of a FromJD run isn't before the stop date, or they weren't set
+    InvalidSchedule,
+}
+
+impl From<TleParseError> for Sgp4Error {
+    fn from(error: TleParseError) -> Self {
+        Sgp4Error::Parse(error)
+    }
+}
+
+impl From<PropagationError> for Sgp4Error {
+    fn from(error: PropagationError) -> Self {
+        Sgp4Error::Propagation(error)
+    }
 }
 
-#[derive(Debug)]
-pub struct TLE {
-    line1: String,
-    line2: String,
+impl fmt::Display for Sgp4Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sgp4Error::Parse(error) => write!(f, "invalid TLE: {error}"),
+            Sgp4Error::Propagation(error) => write!(f, "invalid elements: {error}"),
+            Sgp4Error::InvalidSchedule => write!(f, "run schedule must start before it stops"),
+        }
+    }
 }
 
-impl TLE {
-    pub fn new(line1: String, line2: String) -> TLE {
-        TLE { line1, line2 }
+#[cfg(feature = "std")]
+impl std::error::Error for Sgp4Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Sgp4Error::Parse(error) => Some(error),
+            Sgp4Error::Propagation(error) => Some(error),
+            Sgp4Error::InvalidSchedule => None,
+        }
     }
 }
 
-#[derive(Default)]
+/// Result of propagating to one time.
+pub type PropagationResult = Result<State, PropagationFailure>;
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct SGP4InitOutput {
     ainv: f64,
     ao: f64,
@@ -63,138 +246,2550 @@ struct SGP4InitOutput {
     sinio: f64,
     gsto: f64,
     no_unkozai: f64,
+    // the drag coefficients without B*, kept for `set_bstar`
+    cc2: f64,
+    cc3: f64,
+    coef: f64,
+    tsi: f64,
+    sfour: f64,
+    xhdot1: f64,
+}
+
+/// Mean elements of a satellite at a time since epoch, from `SGP4::mean_elements`.
+///
+/// Angles are in radians in [0, 2pi).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeanElements {
+    /// Semimajor axis in km
+    pub semimajor_axis_km: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    /// Right ascension of the ascending node
+    pub raan: f64,
+    pub arg_of_perigee: f64,
+    pub mean_anomaly: f64,
+    /// Mean motion (Brouwer, un-Kozai'd) in radians per minute
+    pub mean_motion: f64,
+}
+
+/// Drag coefficients of an initialized satellite, named as in Spacetrack Report #3,
+/// from `SGP4::drag_coefficients`.
+///
+/// C1 and D2-D4 include B* (C1 = B* C2), while C3-C5 multiply it during propagation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragCoefficients {
+    pub c1: f64,
+    pub c2: f64,
+    pub c3: f64,
+    pub c4: f64,
+    pub c5: f64,
+    /// Zero if `isimp`
+    pub d2: f64,
+    /// Zero if `isimp`
+    pub d3: f64,
+    /// Zero if `isimp`
+    pub d4: f64,
+    /// Simplified drag equations, without the D2-D4 terms, of perigees below 220 km
+    /// and deep space orbits
+    pub isimp: bool,
+}
+
+/// Long period periodic terms of one body, see `LunarSolarPeriodics`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeriodicTerms {
+    pub eccentricity: f64,
+    /// Inclination in radians
+    pub inclination: f64,
+    /// Mean anomaly in radians
+    pub mean_anomaly: f64,
+    /// Argument of perigee plus cos(i) times the node, in radians
+    pub perigee: f64,
+    /// sin(i) times the node, in radians
+    pub node: f64,
+}
+
+impl PeriodicTerms {
+    fn sum(&self, other: &PeriodicTerms) -> PeriodicTerms {
+        PeriodicTerms {
+            eccentricity: self.eccentricity + other.eccentricity,
+            inclination: self.inclination + other.inclination,
+            mean_anomaly: self.mean_anomaly + other.mean_anomaly,
+            perigee: self.perigee + other.perigee,
+            node: self.node + other.node,
+        }
+    }
+}
+
+/// The lunar-solar long period periodic terms of a deep space satellite at a time,
+/// from `SGP4::lunar_solar_periodics`.
+///
+/// `propagate` adds their total to the mean elements after the secular updates. Above
+/// an inclination of 0.2 rad the node changes by `node / sin(i)` and the argument of
+/// perigee by `perigee - cos(i) node / sin(i)`; below, the Lyddane modification
+/// applies them to the nonsingular elements instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LunarSolarPeriodics {
+    pub solar: PeriodicTerms,
+    pub lunar: PeriodicTerms,
+}
+
+impl LunarSolarPeriodics {
+    /// The solar and lunar terms together
+    pub fn total(&self) -> PeriodicTerms {
+        self.solar.sum(&self.lunar)
+    }
+}
+
+/// Geopotential resonance regime of a deep space orbit, from `SGP4::resonance`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Resonance {
+    #[default]
+    None,
+    /// 24 hour (geosynchronous) orbits
+    Synchronous,
+    /// 12 hour (Molniya, GPS) orbits
+    HalfDay,
 }
 
+/// Integration of the resonance terms of deep space orbits, set with
+/// `Sgp4Builder::resonance`.
+///
+/// The mean motion and longitude of resonant orbits are integrated from epoch in
+/// fixed steps by a second order Taylor series. The defaults are those of the
+/// reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResonanceOptions {
+    /// Integration step in minutes, 720 in the reference. Must be positive; a smaller
+    /// step is more accurate far from epoch, a larger one faster.
+    pub step_min: f64,
+    /// Integrate the resonance of 24 hour orbits, or treat them as non-resonant
+    pub synchronous: bool,
+    /// Integrate the resonance of 12 hour orbits, or treat them as non-resonant
+    pub half_day: bool,
+    /// Fail with `PropagationError::ResonanceDiverged` when the integrated mean motion
+    /// leaves the resonance band of the orbit or stops being finite, or the time isn't
+    /// finite, rather than extrapolating a meaningless state (or, for an infinite
+    /// time, never returning)
+    pub strict: bool,
+}
+
+impl Default for ResonanceOptions {
+    fn default() -> Self {
+        ResonanceOptions {
+            step_min: RESONANCE_STEP_MIN,
+            synchronous: true,
+            half_day: true,
+            strict: false,
+        }
+    }
+}
+
+/// What propagation does at times outside of the validity window of the elements, see
+/// `ValidityWindow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidityMode {
+    /// Fail with `PropagationError::StaleElements`
+    Strict,
+    /// Propagate anyway, leaving the caller to report the times `SGP4::stale` finds
+    /// outside of the window
+    Warn,
+}
+
+/// Longest time from the epoch the elements are trusted for, set with
+/// `Sgp4Builder::validity_window`.
+///
+/// Element sets are fitted to observations around their epoch, and their errors grow
+/// by a few kilometres a day in low orbits, mostly along-track from the drag model.
+/// Propagating a month-old element set still returns a state, but a meaningless one;
+/// a window of a week or two catches that. Without a window times aren't checked, as
+/// in the reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidityWindow {
+    /// Largest time from epoch in minutes, before or after it
+    pub max_tsince_min: f64,
+    pub mode: ValidityMode,
+}
+
+impl ValidityWindow {
+    /// A window of `max_days` either side of the epoch.
+    pub fn days(max_days: f64, mode: ValidityMode) -> Self {
+        ValidityWindow {
+            max_tsince_min: max_days * DAY2MIN,
+            mode,
+        }
+    }
+
+    /// Whether a time in minutes since epoch is inside the window.
+    pub fn contains(&self, tsince_min: f64) -> bool {
+        tsince_min.abs() <= self.max_tsince_min
+    }
+}
+
+/// The SGP4 propagator of one satellite.
+///
+/// Initialization (`twoline2rv`, `tle2rv` or `from_tle`) computes the coefficients of
+/// the satellite. After that propagation only reads them: the state at a time doesn't
+/// depend on earlier calls, and a satellite can be shared between threads.
+///
+/// Everything that only depends on the elements, including the semimajor axis and
+/// the trigonometric functions of the inclination at epoch, is computed once there, so
+/// `propagate` evaluates only the time-dependent terms and doesn't allocate. Deep space
+/// satellites also evaluate the lunar-solar terms and the resonance integration.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SGP4 {
     model: WGSModel,
-    use_afspc_mode: bool,
+    operation_mode: OperationMode,
     grav_const: GravitationalConstants,
     satrec: SatRec,
     use_deep_space: bool,
     x2ox3: f64,
-    jdstart_full: f64,
-    jdstop_full: f64,
+    /// start and stop of FromJD runs
+    jd_range: Option<(Epoch, Epoch)>,
     sgp4init_out: SGP4InitOutput,
-    // TODO: implement deep space
-    // ds: Option<...>
+    ds: Option<DeepSpace>,
+    parse_options: ParseOptions,
+    year_pivot: u64,
+    run_schedule: RunSchedule,
+    /// start, stop and step of `run` in minutes since epoch
+    schedule: (f64, f64, f64),
+    resonance_options: ResonanceOptions,
+    validity_window: Option<ValidityWindow>,
+    /// problems of the TLE that didn't stop parsing, see `warnings`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    warnings: Vec<TleParseError>,
 }
 
-impl Default for SGP4 {
+/// Run schedule of catalog runs in the reference implementation: a day either side of
+/// the epoch in steps of 10 minutes
+pub const CATALOG_SCHEDULE: (f64, f64, f64) = (-DAY2MIN, DAY2MIN, 10.0);
+
+/// Configuration of a propagator, so options can be added without changing the
+/// signature of `SGP4::new`.
+///
+/// ```
+/// use sgp4_rust::sgp4::{OperationMode, SGP4};
+/// use sgp4_rust::utils::WGSModel;
+///
+/// let sgp4 = SGP4::builder()
+///     .model(WGSModel::WGS_84)
+///     .operation_mode(OperationMode::Afspc)
+///     .schedule(0.0, 1440.0, 60.0)
+///     .build();
+/// assert_eq!(sgp4.model(), WGSModel::WGS_84);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sgp4Builder {
+    model: WGSModel,
+    operation_mode: OperationMode,
+    year_pivot: u64,
+    parse_options: ParseOptions,
+    run_schedule: RunSchedule,
+    resonance: ResonanceOptions,
+    validity_window: Option<ValidityWindow>,
+}
+
+impl Default for Sgp4Builder {
     fn default() -> Self {
         Self {
-            model: WGSModel::WGS_84,
-            use_afspc_mode: true,
-            grav_const: get_grav_c(&WGSModel::WGS_84),
-            satrec: SatRec::default(),
-            use_deep_space: false,
-            x2ox3: 2.0 / 3.0,
-            jdstart_full: 0.0,
-            jdstop_full: 0.0,
-            sgp4init_out: SGP4InitOutput::default(),
+            model: WGSModel::WGS_72,
+            operation_mode: OperationMode::Improved,
+            year_pivot: DEFAULT_YEAR_PIVOT,
+            parse_options: ParseOptions::default(),
+            run_schedule: RunSchedule::Catalog,
+            resonance: ResonanceOptions::default(),
+            validity_window: None,
         }
     }
 }
 
-impl SGP4 {
-    pub fn new(model: WGSModel, use_afspc_mode: bool) -> SGP4 {
+impl Sgp4Builder {
+    /// WGS-72 constants in improved mode, warning about wrong checksums.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gravity model constants.
+    pub fn model(mut self, model: WGSModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Operation mode, `OperationMode::Improved` unless reproducing AFSPC results.
+    pub fn operation_mode(mut self, operation_mode: OperationMode) -> Self {
+        self.operation_mode = operation_mode;
+        self
+    }
+
+    /// Two digit epoch years below `pivot` are in the 2000s, the others in the 1900s.
+    /// Four digit years, e.g. of elements from OMM, are used as they are.
+    pub fn year_pivot(mut self, pivot: u64) -> Self {
+        self.year_pivot = pivot;
+        self
+    }
+
+    /// How TLE checksums are verified by `twoline2rv`.
+    pub fn checksum_mode(mut self, mode: ChecksumMode) -> Self {
+        self.parse_options.checksum_mode = mode;
+        self
+    }
+
+    /// How TLE lines are parsed by `twoline2rv`, including the checksum mode.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse_options = options;
+        self
+    }
+
+    /// Times of `SGP4::run` in minutes since epoch, a `RunSchedule::Manual` schedule.
+    pub fn schedule(self, start_min: f64, stop_min: f64, step_min: f64) -> Self {
+        self.run_schedule(RunSchedule::Manual {
+            start: start_min,
+            stop: stop_min,
+            step: step_min,
+        })
+    }
+
+    /// Run schedule of `SGP4::run` and `twoline2rv`.
+    pub fn run_schedule(mut self, run_schedule: RunSchedule) -> Self {
+        self.run_schedule = run_schedule;
+        self
+    }
+
+    /// Integration of the resonance terms of deep space orbits.
+    pub fn resonance(mut self, options: ResonanceOptions) -> Self {
+        self.resonance = options;
+        self
+    }
+
+    /// Validity window of the elements, checked on propagation.
+    pub fn validity_window(mut self, window: ValidityWindow) -> Self {
+        self.validity_window = Some(window);
+        self
+    }
+
+    /// Create the propagator, to be initialized with `twoline2rv` or `tle2rv`.
+    pub fn build(self) -> SGP4 {
         SGP4 {
-            model,
-            use_afspc_mode,
-            grav_const: get_grav_c(&model),
+            model: self.model,
+            operation_mode: self.operation_mode,
+            grav_const: get_grav_c(&self.model),
+            parse_options: self.parse_options,
+            year_pivot: self.year_pivot,
+            run_schedule: self.run_schedule,
+            schedule: match self.run_schedule {
+                RunSchedule::Manual { start, stop, step } => (start, stop, step),
+                _ => CATALOG_SCHEDULE,
+            },
+            resonance_options: self.resonance,
+            validity_window: self.validity_window,
             satrec: SatRec::default(),
             use_deep_space: false,
             x2ox3: 2.0 / 3.0,
-            jdstart_full: 0.0,
-            jdstop_full: 0.0,
+            jd_range: None,
             sgp4init_out: SGP4InitOutput::default(),
+            ds: None,
+            warnings: Vec::new(),
         }
     }
 
+    /// Create a propagator initialized with the elements of a TLE, ready for
+    /// `propagate`.
+    pub fn build_with_tle(self, tle: TLE) -> Result<SGP4, Sgp4Error> {
+        let mut sgp4 = self.build();
+        sgp4.init_tle(tle)?;
+        Ok(sgp4)
+    }
+
+    /// Create a propagator initialized with mean elements, ready for `propagate`. The
+    /// year pivot and parse options don't apply, as the elements are already parsed.
+    pub fn build_with_elements(self, elements: &Elements) -> Result<SGP4, PropagationError> {
+        let mut sgp4 = self.build();
+        sgp4.init_elements(elements)?;
+        Ok(sgp4)
+    }
+}
+
+impl Default for SGP4 {
+    /// An uninitialized propagator with WGS-84 constants in AFSPC mode, as before
+    /// `Sgp4Builder`, and the builder's defaults for the other options.
+    fn default() -> Self {
+        Sgp4Builder::default()
+            .model(WGSModel::WGS_84)
+            .operation_mode(OperationMode::Afspc)
+            .build()
+    }
+}
+
+impl SGP4 {
+    /// Create a propagator with the given constants and operation mode; see `builder`
+    /// for the other options.
+    pub fn new(model: WGSModel, operation_mode: OperationMode) -> SGP4 {
+        SGP4::builder()
+            .model(model)
+            .operation_mode(operation_mode)
+            .build()
+    }
+
+    /// Configure a propagator.
+    pub fn builder() -> Sgp4Builder {
+        Sgp4Builder::new()
+    }
+
+    pub fn model(&self) -> WGSModel {
+        self.model
+    }
+
+    pub fn operation_mode(&self) -> OperationMode {
+        self.operation_mode
+    }
+
+    /// How TLE lines are parsed by `twoline2rv`.
+    pub fn parse_options(&self) -> ParseOptions {
+        self.parse_options
+    }
+
+    /// Select how TLE checksums are verified by `twoline2rv`.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.parse_options.checksum_mode = mode;
+    }
+
+    /// Select how TLE lines are parsed by `twoline2rv`.
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    /// Validity window of the elements, from `Sgp4Builder::validity_window`.
+    pub fn validity_window(&self) -> Option<ValidityWindow> {
+        self.validity_window
+    }
+
+    /// Select the validity window of the elements, or `None` not to check times.
+    pub fn set_validity_window(&mut self, window: Option<ValidityWindow>) {
+        self.validity_window = window;
+    }
+
     fn preprocess_tle(&self, tle_line1: String, tle_line2: String) -> (String, String) {
         (tle_line1, tle_line2)
     }
 
-    /// TODO: update docstring for rust
-    /// Parse TLE lines and populate SGP4 variables.
+    /// Parse the lines of a TLE and initialize SGP4, returning the state at epoch and
+    /// the run schedule: start, stop and step in minutes since epoch.
     ///
-    /// This function converts the two line element (TLE) set character string data to
-    /// variables and initializes the sgp4 variables. several intermediate variables
-    /// and quantities are determined. The Verification mode permits quick checks of any
-    /// changes to the underlying technical theory and works using a
-    /// modified tle file in which the start, stop, and delta time values are
-    /// included at the end of the second line of data. The Catalog mode simply
+    /// The run schedule (see `Sgp4Builder::run_schedule`) selects the times. The
+    /// Verification mode permits quick checks of any changes to the underlying
+    /// technical theory and works using a modified TLE file in which the start, stop,
+    /// and step are included at the end of the second line of data. The Catalog mode
     /// propagates from -1440 to 1440 min from epoch and is useful when performing
-    /// entire catalog runs.
+    /// entire catalog runs. The Manual mode propagates over the times of the caller.
     ///
     /// If using the FromJD mode, the start and stop Julian dates must be set before
-    /// calling this function (see `set_jd_from_from_ymdhms` or `set_jd_from_yr_doy`).
+    /// calling this function (see `set_jd_from_ymdhms` or `set_jd_from_yr_doy`).
+    ///
+    /// The schedule is also kept for `run`. The propagator is ready for `propagate`
+    /// afterwards, and can be initialized again with the elements of another
+    /// satellite.
+    pub fn twoline2rv(
+        &mut self,
+        tle_line1: String,
+        tle_line2: String,
+    ) -> Result<(State, (f64, f64, f64)), Sgp4Error> {
+        // pre-process the TLE lines
+        let (tle_line1, mut tle_line2) = self.preprocess_tle(tle_line1, tle_line2);
+        let verification = match self.run_schedule {
+            RunSchedule::Verification => {
+                let schedule = parse_run_schedule(&tle_line2)?;
+                tle_line2.truncate(69);
+                Some(schedule)
+            }
+            RunSchedule::FromJD { .. } if self.jd_range.is_none() => {
+                return Err(Sgp4Error::InvalidSchedule);
+            }
+            _ => None,
+        };
+        let tle = TLE::parse_with_options(tle_line1, tle_line2, self.parse_options)?;
+        let state = self.tle2rv(tle)?;
+        let schedule = match (self.run_schedule, self.jd_range) {
+            (RunSchedule::Manual { start, stop, step }, _) => (start, stop, step),
+            (RunSchedule::FromJD { step }, Some((start, stop))) => {
+                let epoch = self.epoch();
+                (start.minutes_since(epoch), stop.minutes_since(epoch), step)
+            }
+            _ => verification.unwrap_or(CATALOG_SCHEDULE),
+        };
+        self.schedule = schedule;
+        Ok((state, schedule))
+    }
+
+    /// Set the start and stop of FromJD runs from calendar dates in UTC (year, (month,
+    /// day, hour, minute, second)), see `jday`.
+    pub fn set_jd_from_ymdhms(
+        &mut self,
+        start_year: u64,
+        start_mdhms: (u64, u64, u64, u64, f64),
+        stop_year: u64,
+        stop_mdhms: (u64, u64, u64, u64, f64),
+    ) -> Result<(), Sgp4Error> {
+        self.set_jd_range(
+            Epoch::from_calendar(start_year, start_mdhms),
+            Epoch::from_calendar(stop_year, stop_mdhms),
+        )
+    }
+
+    /// Set the start and stop of FromJD runs from years and days of the year in UTC,
+    /// starting at 1.0 at midnight on January 1st as in the epoch of a TLE.
+    pub fn set_jd_from_yr_doy(
+        &mut self,
+        start_year: u64,
+        start_doy: f64,
+        stop_year: u64,
+        stop_doy: f64,
+    ) -> Result<(), Sgp4Error> {
+        self.set_jd_from_ymdhms(
+            start_year,
+            days2mdh(start_year, start_doy),
+            stop_year,
+            days2mdh(stop_year, stop_doy),
+        )
+    }
+
+    fn set_jd_range(&mut self, start: Epoch, stop: Epoch) -> Result<(), Sgp4Error> {
+        if start - stop >= 0.0 {
+            return Err(Sgp4Error::InvalidSchedule);
+        }
+        self.jd_range = Some((start, stop));
+        Ok(())
+    }
+
+    /// Initialize SGP4 from a parsed TLE, keeping the object name of three line element
+    /// sets in the satellite record.
+    ///
+    /// See `twoline2rv`.
+    pub fn tle2rv(&mut self, tle: TLE) -> Result<State, Sgp4Error> {
+        self.init_tle(tle)
+    }
+
+    /// Create a propagator initialized with mean elements, ready for `propagate`.
+    pub fn from_elements(
+        model: WGSModel,
+        operation_mode: OperationMode,
+        elements: &Elements,
+    ) -> Result<SGP4, PropagationError> {
+        SGP4::builder()
+            .model(model)
+            .operation_mode(operation_mode)
+            .build_with_elements(elements)
+    }
+
+    /// Create a propagator initialized with the elements of a TLE, ready for
+    /// `propagate`.
+    pub fn from_tle(
+        model: WGSModel,
+        operation_mode: OperationMode,
+        tle: TLE,
+    ) -> Result<SGP4, Sgp4Error> {
+        SGP4::builder()
+            .model(model)
+            .operation_mode(operation_mode)
+            .build_with_tle(tle)
+    }
+
+    /// Fill the satellite record from a TLE and initialize SGP4, returning the state at
+    /// epoch.
+    fn init_tle(&mut self, mut tle: TLE) -> Result<State, Sgp4Error> {
+        let warnings = core::mem::take(&mut tle.warnings);
+        let elements = Elements::from_parsed_tle(tle, self.year_pivot);
+        let state = self.init_elements(&elements);
+        self.warnings = warnings;
+        Ok(state?)
+    }
+
+    /// Fill the satellite record from mean elements and initialize SGP4, returning the
+    /// state at epoch.
+    fn init_elements(&mut self, elements: &Elements) -> Result<State, PropagationError> {
+        self.warnings.clear();
+        self.satrec.satnum = elements.satnum;
+        self.satrec.name = elements.name.clone();
+        self.satrec.classification = elements.classification;
+        self.satrec.intldesg = elements.intldesg.clone();
+        self.satrec.epochyr = elements.epochyr;
+        self.satrec.epochdays = elements.epochdays;
+        self.satrec.epoch = elements.epoch;
+        self.satrec.ndot = elements.ndot;
+        self.satrec.nddot = elements.nddot;
+        self.satrec.bstar = elements.bstar;
+        self.satrec.elnum = elements.elnum;
+        self.satrec.inclo = elements.inclo;
+        self.satrec.nodeo = elements.nodeo;
+        self.satrec.ecco = elements.ecco;
+        self.satrec.argpo = elements.argpo;
+        self.satrec.mo = elements.mo;
+        self.satrec.no_kozai = elements.no_kozai;
+        self.satrec.revnum = elements.revnum;
+
+        // initialize SGP4
+        let epoch = self.satrec.epoch.julian_date() - JD_EPOCH_1950;
+        self.sgp4init(epoch)
+    }
+
+    /// Problems of the TLE the propagator was initialized with that didn't stop
+    /// parsing, e.g. the checksum mismatches accepted by `ChecksumMode::Warn`. Empty
+    /// after initialization with mean elements.
+    pub fn warnings(&self) -> &[TleParseError] {
+        &self.warnings
+    }
+
+    /// Mean elements the propagator was initialized with.
+    pub fn elements(&self) -> Elements {
+        let satrec = &self.satrec;
+        Elements {
+            satnum: satrec.satnum,
+            name: satrec.name.clone(),
+            classification: satrec.classification,
+            intldesg: satrec.intldesg.clone(),
+            epochyr: satrec.epochyr,
+            epochdays: satrec.epochdays,
+            epoch: satrec.epoch,
+            ndot: satrec.ndot,
+            nddot: satrec.nddot,
+            bstar: satrec.bstar,
+            elnum: satrec.elnum,
+            inclo: satrec.inclo,
+            nodeo: satrec.nodeo,
+            ecco: satrec.ecco,
+            argpo: satrec.argpo,
+            mo: satrec.mo,
+            no_kozai: satrec.no_kozai,
+            revnum: satrec.revnum,
+        }
+    }
+
+    /// Initialize the epoch-dependent quantities shared by the near earth and deep
+    /// space initializations (un-Kozai the mean motion, sidereal time at epoch, ...).
+    ///
+    /// `epoch` is the epoch in days from 0 Jan 1950 0 hr.
+    fn initl(&mut self, epoch: f64) {
+        let xke = self.grav_const.xke;
+        let ecco = self.satrec.ecco;
+        let inclo = self.satrec.inclo;
+        let out = &mut self.sgp4init_out;
+
+        // calculate auxillary epoch quantities
+        out.eccsq = ecco * ecco;
+        out.omeosq = 1.0 - out.eccsq;
+        out.rteosq = out.omeosq.sqrt();
+        out.cosio = inclo.cos();
+        out.cosio2 = out.cosio * out.cosio;
+
+        // un-kozai the mean motion
+        let del = kozai_correction(self.satrec.no_kozai, ecco, inclo, &self.grav_const);
+        out.no_unkozai = self.satrec.no_kozai / (1.0 + del);
+
+        out.ao = (xke / out.no_unkozai).powf(self.x2ox3);
+        out.sinio = inclo.sin();
+        let po = out.ao * out.omeosq;
+        out.con42 = 1.0 - 5.0 * out.cosio2;
+        out.con41 = -out.con42 - out.cosio2 - out.cosio2;
+        out.ainv = 1.0 / out.ao;
+        out.posq = po * po;
+        out.rp = out.ao * (1.0 - ecco);
+
+        // sidereal time at epoch
+        out.gsto = match self.operation_mode {
+            OperationMode::Afspc => gstime_afspc(epoch + JD_EPOCH_1950),
+            OperationMode::Improved => gstime(epoch + JD_EPOCH_1950),
+        };
+    }
+
+    /// Initialize the SGP4 propagator from the mean elements stored in the satellite
+    /// record.
+    ///
+    /// All quantities that only depend on the epoch are computed here once so that
+    /// subsequent calls to `propagate` only need to evaluate the time-dependent terms.
+    /// Returns the state at epoch.
+    ///
+    /// `epoch` is the epoch in days from 0 Jan 1950 0 hr.
+    fn sgp4init(&mut self, epoch: f64) -> Result<State, PropagationError> {
+        let temp4 = 1.5e-12;
+        let radiusearthkm = self.grav_const.radiusearthkm;
+        let j2 = self.grav_const.j2;
+        let j3oj2 = self.grav_const.j3oj2;
+        let j4 = self.grav_const.j4;
+
+        // earth constants
+        let ss = 78.0 / radiusearthkm + 1.0;
+        let qzms2t = ((120.0 - 78.0) / radiusearthkm).powi(4);
+
+        self.satrec.init = true;
+        self.satrec.isimp = false;
+        self.use_deep_space = false;
+        self.ds = None;
+
+        self.initl(epoch);
+        let ao = self.sgp4init_out.ao;
+        let con41 = self.sgp4init_out.con41;
+        let con42 = self.sgp4init_out.con42;
+        let cosio = self.sgp4init_out.cosio;
+        let cosio2 = self.sgp4init_out.cosio2;
+        let omeosq = self.sgp4init_out.omeosq;
+        let posq = self.sgp4init_out.posq;
+        let rp = self.sgp4init_out.rp;
+        let rteosq = self.sgp4init_out.rteosq;
+        let sinio = self.sgp4init_out.sinio;
+        let eccsq = self.sgp4init_out.eccsq;
+        let gsto = self.sgp4init_out.gsto;
+        let no_unkozai = self.sgp4init_out.no_unkozai;
+
+        let satrec = &mut self.satrec;
+        satrec.no = no_unkozai;
+        satrec.a = (no_unkozai * self.grav_const.tumin).powf(-self.x2ox3);
+        satrec.alta = satrec.a * (1.0 + satrec.ecco) - 1.0;
+        satrec.altp = satrec.a * (1.0 - satrec.ecco) - 1.0;
+
+        if omeosq >= 0.0 || no_unkozai >= 0.0 {
+            satrec.isimp = rp < 220.0 / radiusearthkm + 1.0;
+
+            // for perigees below 156 km, s and qoms2t are altered
+            let mut sfour = ss;
+            let mut qzms24 = qzms2t;
+            let perige = (rp - 1.0) * radiusearthkm;
+            if perige < 156.0 {
+                sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+                qzms24 = ((120.0 - sfour) / radiusearthkm).powi(4);
+                sfour = sfour / radiusearthkm + 1.0;
+            }
+
+            let pinvsq = 1.0 / posq;
+            let tsi = 1.0 / (ao - sfour);
+            satrec.eta = ao * satrec.ecco * tsi;
+            let etasq = satrec.eta * satrec.eta;
+            let eeta = satrec.ecco * satrec.eta;
+            let psisq = (1.0 - etasq).abs();
+            let coef = qzms24 * tsi.powi(4);
+            let coef1 = coef / psisq.powf(3.5);
+            let cc2 = coef1
+                * no_unkozai
+                * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                    + 0.375 * j2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+            let cc3 = if satrec.ecco > 1.0e-4 {
+                -2.0 * coef * tsi * j3oj2 * no_unkozai * sinio / satrec.ecco
+            } else {
+                0.0
+            };
+            satrec.x1mth2 = 1.0 - cosio2;
+            satrec.cc4 = 2.0
+                * no_unkozai
+                * coef1
+                * ao
+                * omeosq
+                * (satrec.eta * (2.0 + 0.5 * etasq) + satrec.ecco * (0.5 + 2.0 * etasq)
+                    - j2 * tsi / (ao * psisq)
+                        * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                            + 0.75
+                                * satrec.x1mth2
+                                * (2.0 * etasq - eeta * (1.0 + etasq))
+                                * (2.0 * satrec.argpo).cos()));
+            satrec.cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+            let cosio4 = cosio2 * cosio2;
+            let temp1 = 1.5 * j2 * pinvsq * no_unkozai;
+            let temp2 = 0.5 * temp1 * j2 * pinvsq;
+            let temp3 = -0.46875 * j4 * pinvsq * pinvsq * no_unkozai;
+            satrec.mdot = no_unkozai
+                + 0.5 * temp1 * rteosq * con41
+                + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+            satrec.argpdot = -0.5 * temp1 * con42
+                + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+                + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+            let xhdot1 = -temp1 * cosio;
+            satrec.nodedot = xhdot1
+                + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2))
+                    * cosio;
+            // avoid a divide by zero for inclinations of 180 deg
+            let den = if (cosio + 1.0).abs() > 1.5e-12 {
+                1.0 + cosio
+            } else {
+                temp4
+            };
+            satrec.xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / den;
+            satrec.aycof = -0.5 * j3oj2 * sinio;
+            satrec.delmo = (1.0 + satrec.eta * satrec.mo.cos()).powi(3);
+            satrec.sinmao = satrec.mo.sin();
+            satrec.x7thm1 = 7.0 * cosio2 - 1.0;
+
+            // deep space initialization
+            if TWOPI / no_unkozai >= 225.0 {
+                self.use_deep_space = true;
+                satrec.isimp = true;
+                self.ds = Some(DeepSpace::new(&DsInitInput {
+                    epoch,
+                    ecco: satrec.ecco,
+                    eccsq,
+                    inclo: satrec.inclo,
+                    nodeo: satrec.nodeo,
+                    argpo: satrec.argpo,
+                    mo: satrec.mo,
+                    no: no_unkozai,
+                    mdot: satrec.mdot,
+                    nodedot: satrec.nodedot,
+                    xpidot: satrec.argpdot + satrec.nodedot,
+                    gsto,
+                    xke: self.grav_const.xke,
+                    resonance: self.resonance_options,
+                }));
+            }
+
+            let out = &mut self.sgp4init_out;
+            (out.cc2, out.cc3, out.coef, out.tsi, out.sfour, out.xhdot1) =
+                (cc2, cc3, coef, tsi, sfour, xhdot1);
+            self.init_drag();
+        }
+
+        // propagate to epoch to get the initial state, with the lunar-solar periodics
+        // applied so it is consistent with later calls to `propagate`
+        self.satrec.init = false;
+        Ok(self.propagate(0.0)?)
+    }
+
+    /// Initialize the coefficients of the drag terms, which are the only ones that
+    /// depend on B*, from the coefficients without it kept by `sgp4init`.
+    fn init_drag(&mut self) {
+        let SGP4InitOutput {
+            ao,
+            omeosq,
+            cc2,
+            cc3,
+            coef,
+            tsi,
+            sfour,
+            xhdot1,
+            ..
+        } = self.sgp4init_out;
+        let satrec = &mut self.satrec;
+        satrec.cc1 = satrec.bstar * cc2;
+        satrec.omgcof = satrec.bstar * cc3 * satrec.argpo.cos();
+        satrec.xmcof = if satrec.ecco > 1.0e-4 {
+            -self.x2ox3 * coef * satrec.bstar / (satrec.ecco * satrec.eta)
+        } else {
+            0.0
+        };
+        satrec.nodecf = 3.5 * omeosq * xhdot1 * satrec.cc1;
+        satrec.t2cof = 1.5 * satrec.cc1;
+
+        // set variables if not deep space
+        if !satrec.isimp {
+            let cc1sq = satrec.cc1 * satrec.cc1;
+            satrec.d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = satrec.d2 * tsi * satrec.cc1 / 3.0;
+            satrec.d3 = (17.0 * ao + sfour) * temp;
+            satrec.d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * satrec.cc1;
+            satrec.t3cof = satrec.d2 + 2.0 * cc1sq;
+            satrec.t4cof =
+                0.25 * (3.0 * satrec.d3 + satrec.cc1 * (12.0 * satrec.d2 + 10.0 * cc1sq));
+            satrec.t5cof = 0.2
+                * (3.0 * satrec.d4
+                    + 12.0 * satrec.cc1 * satrec.d3
+                    + 6.0 * satrec.d2 * satrec.d2
+                    + 15.0 * cc1sq * (2.0 * satrec.d2 + cc1sq));
+        }
+    }
+
+    /// Change the drag term B* (in inverse earth radii) of an initialized satellite,
+    /// e.g. for a study of the sensitivity to drag, recomputing only the coefficients
+    /// that depend on it. The satellite propagates as if it had been initialized with
+    /// the new B*; the state at epoch doesn't change.
+    pub fn set_bstar(&mut self, bstar: f64) {
+        self.satrec.bstar = bstar;
+        self.init_drag();
+    }
+
+    /// Scale the drag term B* by `factor`. See `set_bstar`.
+    pub fn scale_bstar(&mut self, factor: f64) {
+        self.set_bstar(self.satrec.bstar * factor);
+    }
+
+    /// Propagate the satellite to a time since epoch.
+    ///
+    /// Applies the secular effects of gravity and atmospheric drag and the long and
+    /// short period periodics to the mean elements, then solves Kepler's equation and
+    /// orients the result in the True Equator, Mean Equinox (TEME) frame.
     ///
     /// Args:
-    ///     tle_line1 (str): First line of the TLE set
-    ///     tle_line2 (str): Second line of the TLE set
-    ///     typerun (TypeRun): Mode of execution (default = TypeRun.Catalog)
-    ///     start (float, optional): Start time in minutes from epoch (default = None)
-    ///     stop (float, optional): Stop time in minutes from epoch (default = None)
-    ///     step (float, optional): Time step in minutes (default = None)
+    ///     tsince_min (f64): Time since epoch in minutes
     ///
     /// Returns:
-    ///     tuple (r_init, v_init, startmfe, stopmfe, deltamin)
-    ///         startmfe (float): Start time in minutes from epoch
-    ///         stopmfe (float): Stop time in minutes from epoch
-    ///         deltamin (float): Time step in minutes
-    ///         r_init (np.ndarray): Initial position vector in TEME frame in km
-    ///         v_init (np.ndarray): Initial velocity vector in TEME frame in km/s
+    ///     State: Position in km and velocity in km/s in the TEME frame
     ///
-    pub fn twoline2rv(mut self, tle_line1: String, tle_line2: String) -> ([f64; 3], [f64; 3]) {
-        let xpdotp = DAY2MIN / TWOPI;
+    /// Errors carry the satellite number and time, see `PropagationFailure`.
+    pub fn propagate(&self, tsince_min: f64) -> PropagationResult {
+        match self.propagate_rv(tsince_min) {
+            Ok((r, v)) => Ok(self.state(tsince_min, r, v)),
+            Err(error) => Err(self.failure(error, tsince_min)),
+        }
+    }
 
-        // pre-process the TLE lines
-        let (tle_line1, tle_line2) = self.preprocess_tle(tle_line1, tle_line2);
-
-        // parse the first line
-        self.satrec.satnum = tle_line1[2..7].parse().unwrap();
-        self.satrec.classification = Classification::from(tle_line1.chars().nth(7).unwrap());
-        let binding = tle_line1[9..17].to_string();
-        self.satrec.intldesg = binding.trim().to_string();
-        self.satrec.epochyr = tle_line1[18..20].parse().unwrap();
-        self.satrec.epochdays = tle_line1[20..32].parse().unwrap();
-        self.satrec.ndot = tle_line1[33..43].parse().unwrap();
-        self.satrec.nddot = f64::powi(
-            tle_line1[44..50].parse().unwrap() * 10.0,
-            tle_line1[50..52].parse().unwrap(),
-        );
-        self.satrec.bstar = f64::powi(
-            tle_line1[53..59].parse().unwrap() * 10.0,
-            tle_line1[59..61].parse().unwrap(),
-        );
-        self.satrec.elnum = tle_line1[64..68].parse().unwrap();
-
-        // parse the second line
-        self.satrec.inclo = radians(tle_line2[8..16].parse().unwrap());
-        self.satrec.nodeo = radians(tle_line2[17..25].parse().unwrap());
-        self.satrec.ecco = (String::from("0.") + &tle_line2[26..33].trim())
-            .parse()
+    /// An error at a time since epoch with the satellite it happened to.
+    pub(crate) fn failure(&self, error: PropagationError, tsince_min: f64) -> PropagationFailure {
+        PropagationFailure {
+            satnum: self.satrec.satnum,
+            epoch: self.satrec.epoch,
+            tsince_min,
+            error,
+        }
+    }
+
+    /// TEME state at a time since epoch.
+    fn state(&self, tsince_min: f64, r: [f64; 3], v: [f64; 3]) -> State {
+        State {
+            epoch: self.satrec.epoch + tsince_min / DAY2MIN,
+            position_km: r,
+            velocity_km_s: v,
+            frame: Frame::Teme,
+        }
+    }
+
+    /// Position in km and velocity in km/s of `propagate`.
+    fn propagate_rv(&self, tsince_min: f64) -> Result<([f64; 3], [f64; 3]), PropagationError> {
+        let radiusearthkm = self.grav_const.radiusearthkm;
+        let xke = self.grav_const.xke;
+        let j2 = self.grav_const.j2;
+        let j3oj2 = self.grav_const.j3oj2;
+        let vkmpersec = radiusearthkm * xke / 60.0;
+        let mut con41 = self.sgp4init_out.con41;
+
+        let satrec = &self.satrec;
+        let Secular {
+            am,
+            nm,
+            em,
+            inclm,
+            nodem,
+            argpm,
+            mm,
+            ..
+        } = self.secular(tsince_min)?;
+        let t = tsince_min;
+
+        let mut ep = em;
+        let mut xincp = inclm;
+        let mut argpp = argpm;
+        let mut nodep = nodem;
+        let mut mp = mm;
+        // the inclination only changes in deep space, where they are recomputed
+        let mut sinip = self.sgp4init_out.sinio;
+        let mut cosip = self.sgp4init_out.cosio;
+        let mut aycof = satrec.aycof;
+        let mut xlcof = satrec.xlcof;
+
+        // add lunar-solar periodics
+        if let Some(ds) = self.ds.as_ref() {
+            (ep, xincp, nodep, argpp, mp) = ds.dpper(
+                t,
+                satrec.init,
+                self.operation_mode,
+                ep,
+                xincp,
+                nodep,
+                argpp,
+                mp,
+            );
+            if xincp < 0.0 {
+                xincp = -xincp;
+                nodep += PI;
+                argpp -= PI;
+            }
+            if !(0.0..=1.0).contains(&ep) {
+                return Err(PropagationError::EccentricityOutOfRange);
+            }
+
+            sinip = xincp.sin();
+            cosip = xincp.cos();
+            aycof = -0.5 * j3oj2 * sinip;
+            // avoid a divide by zero for inclinations of 180 deg
+            let den = if (cosip + 1.0).abs() > 1.5e-12 {
+                1.0 + cosip
+            } else {
+                1.5e-12
+            };
+            xlcof = -0.25 * j3oj2 * sinip * (3.0 + 5.0 * cosip) / den;
+        }
+
+        // long period periodics
+        let axnl = ep * argpp.cos();
+        let mut temp = 1.0 / (am * (1.0 - ep * ep));
+        let aynl = ep * argpp.sin() + temp * aycof;
+        let xl = mp + argpp + nodep + temp * xlcof * axnl;
+
+        // solve kepler's equation
+        let u = (xl - nodep) % TWOPI;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut ktr = 1;
+        let mut sineo1 = 0.0;
+        let mut coseo1 = 0.0;
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            tem5 = tem5.clamp(-0.95, 0.95);
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(PropagationError::NegativeSemilatusRectum);
+        }
+
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        temp = 1.0 / pl;
+        let temp1 = 0.5 * j2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mut x1mth2 = satrec.x1mth2;
+        let mut x7thm1 = satrec.x7thm1;
+        if self.use_deep_space {
+            let cosisq = cosip * cosip;
+            con41 = 3.0 * cosisq - 1.0;
+            x1mth2 = 1.0 - cosisq;
+            x7thm1 = 7.0 * cosisq - 1.0;
+        }
+
+        // update for short period periodics
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * con41) + 0.5 * temp1 * x1mth2 * cos2u;
+        su -= 0.25 * temp2 * x7thm1 * sin2u;
+        let xnode = nodep + 1.5 * temp2 * cosip * sin2u;
+        let xinc = xincp + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (x1mth2 * cos2u + 1.5 * con41) / xke;
+
+        // orientation vectors
+        let sinsu = su.sin();
+        let cossu = su.cos();
+        let snod = xnode.sin();
+        let cnod = xnode.cos();
+        let sini = xinc.sin();
+        let cosi = xinc.cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        // position and velocity (in km and km/sec)
+        let mr = mrt * radiusearthkm;
+        let r = [mr * ux, mr * uy, mr * uz];
+        let v = [
+            (mvt * ux + rvdot * vx) * vkmpersec,
+            (mvt * uy + rvdot * vy) * vkmpersec,
+            (mvt * uz + rvdot * vz) * vkmpersec,
+        ];
+
+        // decaying satellites
+        if mrt < 1.0 {
+            return Err(PropagationError::OrbitalDecay);
+        }
+
+        Ok((r, v))
+    }
+
+    /// Mean elements at a time since epoch, updated for the secular effects of gravity
+    /// and drag and for deep space resonance.
+    fn secular(&self, tsince_min: f64) -> Result<Secular, PropagationError> {
+        self.check_validity(tsince_min)?;
+        let xke = self.grav_const.xke;
+        let gsto = self.sgp4init_out.gsto;
+        let satrec = &self.satrec;
+
+        // update for secular gravity and atmospheric drag
+        let t = tsince_min;
+        let xmdf = satrec.mo + satrec.mdot * t;
+        let argpdf = satrec.argpo + satrec.argpdot * t;
+        let nodedf = satrec.nodeo + satrec.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + satrec.nodecf * t2;
+        let mut tempa = 1.0 - satrec.cc1 * t;
+        let mut tempe = satrec.bstar * satrec.cc4 * t;
+        let mut templ = satrec.t2cof * t2;
+
+        if !satrec.isimp {
+            let delomg = satrec.omgcof * t;
+            let delm = satrec.xmcof * ((1.0 + satrec.eta * xmdf.cos()).powi(3) - satrec.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - satrec.d2 * t2 - satrec.d3 * t3 - satrec.d4 * t4;
+            tempe += satrec.bstar * satrec.cc5 * (mm.sin() - satrec.sinmao);
+            templ += satrec.t3cof * t3 + t4 * (satrec.t4cof + t * satrec.t5cof);
+        }
+
+        let mut nm = satrec.no;
+        let mut em = satrec.ecco;
+        let mut inclm = satrec.inclo;
+        if let Some(ds) = self.ds.as_ref() {
+            (em, argpm, inclm, nodem, mm, nm) = ds.dspace(
+                t,
+                gsto,
+                satrec.no,
+                satrec.argpo,
+                satrec.argpdot,
+                em,
+                argpm,
+                inclm,
+                nodem,
+                mm,
+            )?;
+        }
+
+        if nm <= 0.0 {
+            return Err(PropagationError::NegativeMeanMotion);
+        }
+
+        // the semimajor axis of the mean motion at epoch is found at initialization;
+        // only deep space resonance changes the mean motion
+        let am = if nm == satrec.no {
+            self.sgp4init_out.ao
+        } else {
+            (xke / nm).powf(self.x2ox3)
+        } * tempa
+            * tempa;
+        nm = xke / am.powf(1.5);
+        em -= tempe;
+
+        if !(-0.001..1.0).contains(&em) {
+            return Err(PropagationError::InvalidElements);
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += satrec.no * templ;
+        // before wrapping, for counting the revolutions since epoch
+        let arglat = argpm + mm;
+        let mut xlm = mm + argpm + nodem;
+
+        nodem %= TWOPI;
+        argpm %= TWOPI;
+        xlm %= TWOPI;
+        mm = (xlm - argpm - nodem) % TWOPI;
+
+        Ok(Secular {
+            am,
+            nm,
+            em,
+            inclm,
+            nodem,
+            argpm,
+            mm,
+            arglat,
+        })
+    }
+
+    /// The `PropagationError::StaleElements` failure of a time since epoch outside of
+    /// the validity window, whatever its mode, or `None` inside of it or without a
+    /// window. In `ValidityMode::Warn`, propagation succeeds at such times, and this is
+    /// the warning for the caller to report.
+    pub fn stale(&self, tsince_min: f64) -> Option<PropagationFailure> {
+        let window = self.validity_window?;
+        (!window.contains(tsince_min))
+            .then(|| self.failure(PropagationError::StaleElements, tsince_min))
+    }
+
+    /// Check a time since epoch against a strict validity window.
+    fn check_validity(&self, tsince_min: f64) -> Result<(), PropagationError> {
+        match self.validity_window {
+            Some(window) if window.mode == ValidityMode::Strict => match self.stale(tsince_min) {
+                Some(failure) => Err(failure.error),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Propagate the satellite to four times since epoch at once, evaluating the near
+    /// earth equations on SIMD lanes. See `propagate`.
+    ///
+    /// The SIMD sine and cosine approximations agree with `propagate` to well below a
+    /// millimetre. Deep space satellites are propagated one time after another, since
+    /// the resonance integrator steps sequentially.
+    #[cfg(feature = "simd")]
+    pub fn propagate_x4(&self, times: [f64; 4]) -> [PropagationResult; 4] {
+        use wide::f64x4;
+
+        // a strict validity window is checked one time after another too
+        let strict = |window: ValidityWindow| window.mode == ValidityMode::Strict;
+        let stale = times.iter().any(|&t| self.stale(t).is_some());
+        if self.use_deep_space || (self.validity_window.is_some_and(strict) && stale) {
+            return times.map(|t| self.propagate(t));
+        }
+        let radiusearthkm = self.grav_const.radiusearthkm;
+        let xke = self.grav_const.xke;
+        let j2 = self.grav_const.j2;
+        let vkmpersec = radiusearthkm * xke / 60.0;
+        let con41 = self.sgp4init_out.con41;
+        let satrec = &self.satrec;
+
+        // update for secular gravity and atmospheric drag
+        let t = f64x4::new(times);
+        let xmdf = satrec.mo + satrec.mdot * t;
+        let argpdf = satrec.argpo + satrec.argpdot * t;
+        let nodedf = satrec.nodeo + satrec.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + satrec.nodecf * t2;
+        let mut tempa = 1.0 - satrec.cc1 * t;
+        let mut tempe = satrec.bstar * satrec.cc4 * t;
+        let mut templ = satrec.t2cof * t2;
+
+        if !satrec.isimp {
+            let delomg = satrec.omgcof * t;
+            let eta = 1.0 + satrec.eta * xmdf.cos();
+            let delm = satrec.xmcof * (eta * eta * eta - satrec.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - satrec.d2 * t2 - satrec.d3 * t3 - satrec.d4 * t4;
+            tempe += satrec.bstar * satrec.cc5 * (mm.sin() - satrec.sinmao);
+            templ += satrec.t3cof * t3 + t4 * (satrec.t4cof + t * satrec.t5cof);
+        }
+
+        let nm = satrec.no;
+        if nm <= 0.0 {
+            return times.map(|t| Err(self.failure(PropagationError::NegativeMeanMotion, t)));
+        }
+
+        let am = self.sgp4init_out.ao * tempa * tempa;
+        let nm = xke / (am * am.sqrt());
+        let mut em = satrec.ecco - tempe;
+        let invalid_elements = em.simd_lt(-0.001) | em.simd_ge(1.0);
+        em = em.max(f64x4::splat(1.0e-6));
+        mm += satrec.no * templ;
+        let mut xlm = mm + argpm + nodem;
+
+        nodem %= TWOPI;
+        argpm %= TWOPI;
+        xlm %= TWOPI;
+        mm = (xlm - argpm - nodem) % TWOPI;
+
+        let (sinip, cosip) = (self.sgp4init_out.sinio, self.sgp4init_out.cosio);
+
+        // long period periodics
+        let (sinargp, cosargp) = argpm.sin_cos();
+        let axnl = em * cosargp;
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * sinargp + temp * satrec.aycof;
+        let xl = mm + argpm + nodem + temp * satrec.xlcof * axnl;
+
+        // solve kepler's equation, iterating the lanes together until all converge
+        let u = (xl - nodem) % TWOPI;
+        let mut eo1 = u;
+        let mut sineo1 = f64x4::ZERO;
+        let mut coseo1 = f64x4::ZERO;
+        let mut active = f64x4::splat(0.0).simd_eq(0.0);
+        for _ in 0..10 {
+            let (s, c) = eo1.sin_cos();
+            sineo1 = active.select(s, sineo1);
+            coseo1 = active.select(c, coseo1);
+            let tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            let tem5 = ((u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5)
+                .clamp(f64x4::splat(-0.95), f64x4::splat(0.95));
+            eo1 = active.select(eo1 + tem5, eo1);
+            active &= tem5.abs().simd_ge(1.0e-12);
+            if active.none() {
+                break;
+            }
+        }
+
+        // short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        let negative_pl = pl.simd_lt(0.0);
+
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * j2 * temp;
+        let temp2 = temp1 * temp;
+
+        // update for short period periodics
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * con41) + 0.5 * temp1 * satrec.x1mth2 * cos2u;
+        su -= 0.25 * temp2 * satrec.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = satrec.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * satrec.x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (satrec.x1mth2 * cos2u + 1.5 * con41) / xke;
+        let decayed = mrt.simd_lt(1.0);
+
+        // orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        // position and velocity (in km and km/sec)
+        let mr = mrt * radiusearthkm;
+        let r = [mr * ux, mr * uy, mr * uz].map(f64x4::to_array);
+        let v = [
+            (mvt * ux + rvdot * vx) * vkmpersec,
+            (mvt * uy + rvdot * vy) * vkmpersec,
+            (mvt * uz + rvdot * vz) * vkmpersec,
+        ]
+        .map(f64x4::to_array);
+
+        // report the first failing check of each lane, in the order of `propagate`
+        let errors = [
+            (invalid_elements, PropagationError::InvalidElements),
+            (negative_pl, PropagationError::NegativeSemilatusRectum),
+            (decayed, PropagationError::OrbitalDecay),
+        ]
+        .map(|(mask, error)| (mask.to_bitmask(), error));
+        core::array::from_fn(
+            |i| match errors.iter().find(|(mask, _)| mask & (1 << i) != 0) {
+                Some(&(_, error)) => Err(self.failure(error, times[i])),
+                None => Ok(self.state(
+                    times[i],
+                    [r[0][i], r[1][i], r[2][i]],
+                    [v[0][i], v[1][i], v[2][i]],
+                )),
+            },
+        )
+    }
+
+    /// Near earth propagation kernel with the coefficients converted to `T`, e.g.
+    /// `f32`. Returns `None` for deep space satellites, which need the resonance
+    /// integrator of `propagate`.
+    #[cfg(feature = "generic")]
+    pub fn kernel<T: num_traits::Float>(&self) -> Option<crate::kernel::Sgp4Kernel<T>> {
+        if self.use_deep_space {
+            return None;
+        }
+        let c = |x: f64| T::from(x);
+        let satrec = &self.satrec;
+        let grav_const = &self.grav_const;
+        Some(crate::kernel::Sgp4Kernel {
+            isimp: satrec.isimp,
+            mo: c(satrec.mo)?,
+            mdot: c(satrec.mdot)?,
+            argpo: c(satrec.argpo)?,
+            argpdot: c(satrec.argpdot)?,
+            nodeo: c(satrec.nodeo)?,
+            nodedot: c(satrec.nodedot)?,
+            nodecf: c(satrec.nodecf)?,
+            cc1: c(satrec.cc1)?,
+            cc4: c(satrec.cc4)?,
+            cc5: c(satrec.cc5)?,
+            bstar: c(satrec.bstar)?,
+            omgcof: c(satrec.omgcof)?,
+            xmcof: c(satrec.xmcof)?,
+            eta: c(satrec.eta)?,
+            delmo: c(satrec.delmo)?,
+            sinmao: c(satrec.sinmao)?,
+            d2: c(satrec.d2)?,
+            d3: c(satrec.d3)?,
+            d4: c(satrec.d4)?,
+            t2cof: c(satrec.t2cof)?,
+            t3cof: c(satrec.t3cof)?,
+            t4cof: c(satrec.t4cof)?,
+            t5cof: c(satrec.t5cof)?,
+            no: c(satrec.no)?,
+            ecco: c(satrec.ecco)?,
+            inclo: c(satrec.inclo)?,
+            sinio: c(self.sgp4init_out.sinio)?,
+            cosio: c(self.sgp4init_out.cosio)?,
+            aycof: c(satrec.aycof)?,
+            xlcof: c(satrec.xlcof)?,
+            con41: c(self.sgp4init_out.con41)?,
+            x1mth2: c(satrec.x1mth2)?,
+            x7thm1: c(satrec.x7thm1)?,
+            ao: c(self.sgp4init_out.ao)?,
+            xke: c(grav_const.xke)?,
+            j2: c(grav_const.j2)?,
+            radiusearthkm: c(grav_const.radiusearthkm)?,
+        })
+    }
+
+    /// The satellite record, for the bindings that mirror its fields.
+    #[cfg(feature = "python")]
+    pub(crate) fn satrec(&self) -> &SatRec {
+        &self.satrec
+    }
+
+    /// NORAD catalog number
+    pub fn satnum(&self) -> u64 {
+        self.satrec.satnum
+    }
+
+    /// Object name from the title line of a three line element set, or OMM
+    pub fn name(&self) -> Option<&str> {
+        self.satrec.name.as_deref()
+    }
+
+    /// International designator (launch year, launch number and piece, e.g. "98067A")
+    pub fn international_designator(&self) -> &str {
+        &self.satrec.intldesg
+    }
+
+    /// Epoch of the elements
+    pub fn epoch(&self) -> Epoch {
+        self.satrec.epoch
+    }
+
+    /// Semimajor axis in km, from the un-Kozai'd mean motion of the elements
+    pub fn semimajor_axis_km(&self) -> f64 {
+        self.satrec.a * self.grav_const.radiusearthkm
+    }
+
+    /// Altitude of the apogee above the equatorial radius in km
+    pub fn apogee_altitude_km(&self) -> f64 {
+        self.satrec.alta * self.grav_const.radiusearthkm
+    }
+
+    /// Altitude of the perigee above the equatorial radius in km
+    pub fn perigee_altitude_km(&self) -> f64 {
+        self.satrec.altp * self.grav_const.radiusearthkm
+    }
+
+    /// Mean motion (Brouwer, un-Kozai'd) at epoch in radians per minute, the mean motion
+    /// SGP4 propagates with (see `elements::kozai_to_brouwer`)
+    pub fn mean_motion_brouwer(&self) -> f64 {
+        self.satrec.no
+    }
+
+    /// Greenwich sidereal time at epoch in radians, of the model of the operation mode
+    pub fn gsto(&self) -> f64 {
+        self.sgp4init_out.gsto
+    }
+
+    /// Whether the satellite is propagated with the deep space (SDP4) equations, for
+    /// periods of 225 minutes or more
+    pub fn is_deep_space(&self) -> bool {
+        self.use_deep_space
+    }
+
+    /// Whether the simplified drag equations are used, for perigees below 220 km and
+    /// deep space orbits (see `DragCoefficients::isimp`)
+    pub fn is_simplified(&self) -> bool {
+        self.satrec.isimp
+    }
+
+    /// Lunar-solar long period periodic terms at a time since epoch in minutes, to study
+    /// the contributions of the sun and moon separately, or `None` for near earth
+    /// satellites, which don't have them.
+    pub fn lunar_solar_periodics(&self, tsince_min: f64) -> Option<LunarSolarPeriodics> {
+        self.ds.as_ref().map(|ds| ds.periodics(tsince_min))
+    }
+
+    /// Resonance regime the orbit is integrated in, `Resonance::None` for near earth
+    /// satellites and for regimes turned off in the `ResonanceOptions`.
+    pub fn resonance(&self) -> Resonance {
+        self.ds
+            .as_ref()
+            .map_or(Resonance::None, |ds| ds.resonance())
+    }
+
+    /// Integration of the resonance terms, from `Sgp4Builder::resonance`.
+    pub fn resonance_options(&self) -> ResonanceOptions {
+        self.resonance_options
+    }
+
+    /// Mean elements at a time since epoch, updated for the secular effects of gravity
+    /// and drag, and for deep space resonance: the elements `propagate` adds the
+    /// periodics to before solving Kepler's equation.
+    ///
+    /// The lunar-solar periodics of deep space orbits aren't included, so the mean
+    /// elements of all orbits change smoothly.
+    pub fn mean_elements(&self, tsince_min: f64) -> Result<MeanElements, PropagationFailure> {
+        let secular = self
+            .secular(tsince_min)
+            .map_err(|error| self.failure(error, tsince_min))?;
+        Ok(MeanElements {
+            semimajor_axis_km: secular.am * self.grav_const.radiusearthkm,
+            eccentricity: secular.em,
+            inclination: secular.inclm,
+            raan: secular.nodem.rem_euclid(TWOPI),
+            arg_of_perigee: secular.argpm.rem_euclid(TWOPI),
+            mean_anomaly: secular.mm.rem_euclid(TWOPI),
+            mean_motion: secular.nm,
+        })
+    }
+
+    /// Coefficients of the drag model the satellite was initialized with, e.g. to check
+    /// whether the simplified equations apply to a low perigee.
+    pub fn drag_coefficients(&self) -> DragCoefficients {
+        let satrec = &self.satrec;
+        let unless_simplified = |value: f64| if satrec.isimp { 0.0 } else { value };
+        DragCoefficients {
+            c1: satrec.cc1,
+            c2: self.sgp4init_out.cc2,
+            c3: self.sgp4init_out.cc3,
+            c4: satrec.cc4,
+            c5: satrec.cc5,
+            d2: unless_simplified(satrec.d2),
+            d3: unless_simplified(satrec.d3),
+            d4: unless_simplified(satrec.d4),
+            isimp: satrec.isimp,
+        }
+    }
+
+    /// Time from perigee to perigee in minutes, from the secular rate of the mean
+    /// anomaly. Deep space resonance and lunar-solar rates aren't included.
+    pub fn anomalistic_period_min(&self) -> f64 {
+        TWOPI / self.satrec.mdot
+    }
+
+    /// Time from ascending node to ascending node in minutes, from the secular rates of
+    /// the mean anomaly and argument of perigee.
+    pub fn nodal_period_min(&self) -> f64 {
+        TWOPI / (self.satrec.mdot + self.satrec.argpdot)
+    }
+
+    /// Revolution number at a time since epoch: the revolution number of the elements,
+    /// counted up at each ascending node after the epoch (and down before it).
+    ///
+    /// The nodes are found from the argument of latitude of the propagated state,
+    /// unwrapped by the mean argument of latitude, so a revolution starts as the
+    /// satellite crosses the equator northward. Equatorial orbits have no node and are
+    /// counted by the mean argument of latitude alone.
+    pub fn revolution_number(&self, tsince_min: f64) -> Result<u64, PropagationFailure> {
+        let revolutions = |t: f64| -> Result<f64, PropagationError> {
+            let mean = self.secular(t)?.arglat;
+            let (r, v) = self.propagate_rv(t)?;
+            let h = cross(r, v);
+            let arglat = if h[0].hypot(h[1]) > SMALL * mag(h) {
+                let arglat = (r[2] * mag(h)).atan2(h[0] * r[1] - h[1] * r[0]);
+                arglat + TWOPI * ((mean - arglat) / TWOPI).round()
+            } else {
+                mean
+            };
+            Ok((arglat / TWOPI).floor())
+        };
+        let count = revolutions(tsince_min)
+            .and_then(|revolutions_then| Ok(revolutions_then - revolutions(0.0)?))
+            .map_err(|error| self.failure(error, tsince_min))?;
+        Ok((self.satrec.revnum as f64 + count).max(0.0) as u64)
+    }
+
+    /// Julian date of the epoch
+    pub(crate) fn jdepoch(&self) -> f64 {
+        self.satrec.epoch.julian_date()
+    }
+
+    /// Minutes from the epoch to a point in time, e.g. a `chrono::DateTime<Utc>` or a
+    /// `hifitime::Epoch`, to convert run start and stop times.
+    pub fn minutes_since_epoch(&self, time: &impl ToJday) -> f64 {
+        let (jd, jdfrac) = time.to_jday();
+        Epoch::new(jd, jdfrac).minutes_since(self.satrec.epoch)
+    }
+
+    /// UTC date and time of a time in minutes since epoch, or `None` if it is not
+    /// representable (see `time::jday_to_datetime`).
+    #[cfg(feature = "chrono")]
+    pub fn datetime(&self, tsince_min: f64) -> Option<chrono::DateTime<chrono::Utc>> {
+        let time = self.satrec.epoch + tsince_min / DAY2MIN;
+        crate::time::jday_to_datetime(time.jd(), time.fraction())
+    }
+
+    /// hifitime epoch (in UTC) of a time in minutes since epoch.
+    #[cfg(feature = "hifitime")]
+    pub fn hifitime_epoch(&self, tsince_min: f64) -> hifitime::Epoch {
+        let time = self.satrec.epoch + tsince_min / DAY2MIN;
+        crate::time::jday_to_hifitime(time.jd(), time.fraction())
+    }
+
+    /// Propagate the satellite to a time since epoch, returning the osculating classical
+    /// elements of the state along with it (see `coe::rv2coe`), with the gravitational
+    /// parameter of the model. Complements `mean_elements`.
+    ///
+    /// A state without angular momentum has no orbital plane; it can only come from a
+    /// degenerate orbit, and fails with `PropagationError::NegativeSemilatusRectum`.
+    pub fn propagate_coe(
+        &self,
+        tsince_min: f64,
+    ) -> Result<(State, ClassicalElements), PropagationFailure> {
+        let state = self.propagate(tsince_min)?;
+        match rv2coe(state.position_km, state.velocity_km_s, self.grav_const.mu) {
+            Some(coe) => Ok((state, coe)),
+            None => Err(self.failure(PropagationError::NegativeSemilatusRectum, tsince_min)),
+        }
+    }
+
+    /// Propagate the satellite to a point in time, e.g. a `chrono::DateTime<Utc>` or a
+    /// `hifitime::Epoch`. See `propagate`.
+    pub fn propagate_at(&self, time: &impl ToJday) -> PropagationResult {
+        self.propagate(self.minutes_since_epoch(time))
+    }
+
+    /// Propagate the satellite to a Julian date in UTC, split into a date part and a
+    /// fraction of the day as `jday` returns it. The parts are differenced with those of
+    /// the epoch separately, so the time since epoch keeps the precision of the
+    /// fraction. See `propagate`.
+    pub fn propagate_jd(&self, jd: f64, jdfrac: f64) -> PropagationResult {
+        self.propagate_at(&(jd, jdfrac))
+    }
+
+    /// Propagate the satellite to each of `times` in minutes since epoch, reusing the
+    /// coefficients from initialization. See `propagate`.
+    ///
+    /// A failure at one time doesn't stop the batch; its error is returned in place. The
+    /// states are exactly those of `propagate`, with or without the `simd` feature; see
+    /// `propagate_x4` for the vectorized equations.
+    pub fn propagate_many(&self, times: &[f64]) -> Vec<PropagationResult> {
+        times.iter().map(|&t| self.propagate(t)).collect()
+    }
+
+    /// Lazily propagate from `start_min` to `stop_min` minutes since epoch in steps of
+    /// `step_min` minutes, yielding the state at each time.
+    ///
+    /// Each item is the result of `propagate`, so a failed step doesn't end the
+    /// iteration; use e.g. `map_while(Result::ok)` to stop at the first error. Fails
+    /// with `PropagationError::InvalidStep` at `start_min` if the step isn't positive
+    /// and finite.
+    pub fn iter(
+        &self,
+        start_min: f64,
+        stop_min: f64,
+        step_min: f64,
+    ) -> Result<Ephemeris<'_>, PropagationFailure> {
+        if !(step_min > 0.0 && step_min.is_finite()) {
+            return Err(self.failure(PropagationError::InvalidStep, start_min));
+        }
+        let steps = ((stop_min - start_min) / step_min + SMALL)
+            .floor()
+            .max(-1.0)
+            + 1.0;
+        Ok(Ephemeris {
+            sgp4: self,
+            start_min,
+            step_min,
+            next: 0,
+            len: steps as usize,
+        })
+    }
+
+    /// Run schedule the propagator was configured with.
+    pub fn run_schedule(&self) -> RunSchedule {
+        self.run_schedule
+    }
+
+    /// Start, stop and step of `run` in minutes since epoch.
+    pub fn schedule(&self) -> (f64, f64, f64) {
+        self.schedule
+    }
+
+    /// Propagate over the run schedule, with the times found by `twoline2rv` for
+    /// Verification and FromJD runs, by default `CATALOG_SCHEDULE`. See `iter`.
+    pub fn run(&self) -> Result<Ephemeris<'_>, PropagationFailure> {
+        let (start_min, stop_min, step_min) = self.schedule;
+        self.iter(start_min, stop_min, step_min)
+    }
+
+    /// Propagate from `start_min` to `stop_min` minutes since epoch in steps of
+    /// `step_min` minutes and convert the positions to geodetic coordinates.
+    ///
+    /// Use `groundtrack::split_at_antimeridian` to split the track for plotting. Fails
+    /// with `PropagationError::InvalidStep` at `start_min` if the step isn't positive
+    /// and finite.
+    pub fn ground_track(
+        &self,
+        start_min: f64,
+        stop_min: f64,
+        step_min: f64,
+    ) -> Result<Vec<GroundTrackPoint>, PropagationFailure> {
+        if !(step_min > 0.0 && step_min.is_finite()) {
+            return Err(self.failure(PropagationError::InvalidStep, start_min));
+        }
+        let jdepoch = self.jdepoch();
+        let steps = ((stop_min - start_min) / step_min + SMALL).floor() as usize;
+        (0..=steps)
+            .map(|i| {
+                let t = start_min + i as f64 * step_min;
+                let r = self.propagate(t)?.position_km;
+                let geodetic = teme_to_geodetic(r, jdepoch + t / DAY2MIN);
+                Ok(GroundTrackPoint {
+                    t,
+                    latitude: geodetic.latitude,
+                    longitude: geodetic.longitude,
+                    altitude: geodetic.altitude,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Iterator over the states of a satellite at evenly spaced times, from `SGP4::iter`.
+pub struct Ephemeris<'a> {
+    sgp4: &'a SGP4,
+    start_min: f64,
+    step_min: f64,
+    next: usize,
+    len: usize,
+}
+
+impl Iterator for Ephemeris<'_> {
+    type Item = PropagationResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.len {
+            return None;
+        }
+        // multiply rather than accumulate the step so long runs don't drift
+        let t = self.start_min + self.next as f64 * self.step_min;
+        self.next += 1;
+        Some(self.sgp4.propagate(t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Ephemeris<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{VANGUARD_LINE1, VANGUARD_LINE2, iss, iss_tle, vanguard};
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_twoline2rv_epoch_state() {
+        let mut sgp4 = SGP4::new(WGSModel::WGS_72, OperationMode::Improved);
+        let (
+            State {
+                position_km: r,
+                velocity_km_s: v,
+                ..
+            },
+            _,
+        ) = sgp4
+            .twoline2rv(VANGUARD_LINE1.into(), VANGUARD_LINE2.into())
+            .unwrap();
+
+        let r_expected = [7022.46529266, -1400.08296755, 0.03995155];
+        let v_expected = [1.893841015, 6.405893759, 4.534807250];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-7);
+            assert!((v[i] - v_expected[i]).abs() < 1e-8);
+        }
+
+        // the propagator stays usable after initialization
+        let r = sgp4.propagate(360.0).unwrap().position_km;
+        assert!((r[0] - -7154.03120202).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_twoline2rv_deep_space_epoch_state() {
+        // Molniya orbit in 12 hour resonance
+        let mut sgp4 = SGP4::new(WGSModel::WGS_72, OperationMode::Afspc);
+        let (
+            State {
+                position_km: r,
+                velocity_km_s: v,
+                ..
+            },
+            _,
+        ) = sgp4
+            .twoline2rv(
+                "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+                "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+            )
             .unwrap();
-        self.satrec.argpo = radians(tle_line2[34..42].parse().unwrap());
-        self.satrec.mo = radians(tle_line2[43..51].parse().unwrap());
-        self.satrec.no_kozai = tle_line2[53..63].parse().unwrap() / xpdotp;
-        self.satrec.revnum = tle_line2[63..68].parse().unwrap();
 
-        // convert epoch year to full year
-        let year = self.satrec.epochyr + if self.satrec.epochyr < 57 { 2000 } else { 1900 };
+        let r_expected = [2349.8948335, -14785.93811562, 0.02119378];
+        let v_expected = [2.721488096, -3.256811655, 4.498416672];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-6);
+            assert!((v[i] - v_expected[i]).abs() < 1e-8);
+        }
+    }
 
-        // adjust ndot and nddot units
-        self.satrec.ndot /= xpdotp * DAY2MIN;
-        self.satrec.nddot /= xpdotp * DAY2MIN.powi(2);
+    #[test]
+    fn test_from_tle() {
+        let sgp4 = vanguard(WGSModel::WGS_72);
 
-        // compute julian date of the epoch
-        let mdhms = days2mdh(year, self.satrec.epochdays);
-        (self.satrec.jdsatepoch, self.satrec.jdsatepochf) = jday(year, mdhms);
+        let State {
+            position_km: r,
+            velocity_km_s: v,
+            ..
+        } = sgp4.propagate(360.0).unwrap();
+        let r_expected = [-7154.03120202, -3783.17682504, -3536.19412294];
+        let v_expected = [4.741887409, -4.151817765, -2.093935425];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-7);
+            assert!((v[i] - v_expected[i]).abs() < 1e-8);
+        }
+    }
 
-        // initialize SGP4
-        epoch = self.satrec.jdsatepoch + self.satrec.jdsatepochf - JD_EPOCH_1950;
-        let (r_init, v_init) = self.sgp4init(epoch);
+    #[test]
+    fn test_orbit_accessors() {
+        let sgp4 = vanguard(WGSModel::WGS_72);
+
+        let a = sgp4.semimajor_axis_km();
+        let radius = 6378.135;
+        assert!((sgp4.apogee_altitude_km() - (a * (1.0 + 0.1859667) - radius)).abs() < 1e-8);
+        assert!((sgp4.perigee_altitude_km() - (a * (1.0 - 0.1859667) - radius)).abs() < 1e-8);
+        assert!((sgp4.perigee_altitude_km() - 651.332).abs() < 1e-3);
+        assert!((sgp4.apogee_altitude_km() - 3863.109).abs() < 1e-3);
+
+        // 10.82 revolutions a day, shifted slightly by the J2 rates
+        let period = DAY2MIN / 10.82419157;
+        assert!((sgp4.anomalistic_period_min() - period).abs() < 0.01);
+        assert!((sgp4.nodal_period_min() - period).abs() < 0.2);
+        assert!(sgp4.nodal_period_min() < sgp4.anomalistic_period_min());
+    }
+
+    #[test]
+    fn test_mean_elements() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let elements = sgp4.elements();
+
+        let at_epoch = sgp4.mean_elements(0.0).unwrap();
+        assert_eq!(at_epoch.inclination, elements.inclo);
+        assert!((at_epoch.raan - elements.nodeo).abs() < 1e-12);
+        assert!((at_epoch.arg_of_perigee - elements.argpo).abs() < 1e-12);
+        assert!((at_epoch.mean_anomaly - elements.mo).abs() < 1e-12);
+        assert_eq!(at_epoch.eccentricity, elements.ecco);
+        assert!((at_epoch.semimajor_axis_km - sgp4.semimajor_axis_km()).abs() < 1e-9);
+        assert_eq!(at_epoch.mean_motion, sgp4.satrec.no);
+
+        // a day later the node has regressed by about 5 degrees, and drag has lowered
+        // the orbit
+        let later = sgp4.mean_elements(DAY2MIN).unwrap();
+        let regression = sgp4.satrec.nodedot * DAY2MIN + sgp4.satrec.nodecf * DAY2MIN.powi(2);
+        assert!((later.raan - (elements.nodeo + regression)).abs() < 1e-9);
+        assert!((degrees(regression) + 5.0).abs() < 0.1);
+        assert!(later.semimajor_axis_km < at_epoch.semimajor_axis_km);
+        assert!(later.mean_motion > at_epoch.mean_motion);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        assert!(!sgp4.is_deep_space() && !sgp4.is_simplified());
+        let epoch = sgp4.epoch();
+        assert!((sgp4.gsto() - gstime(epoch.jd() + epoch.fraction())).abs() < 1e-9);
+        let elements = sgp4.elements();
+        assert!(sgp4.mean_motion_brouwer() < elements.no_kozai);
+        let xke = get_grav_c(&WGSModel::WGS_72).xke;
+        let a = (xke / sgp4.mean_motion_brouwer()).powf(2.0 / 3.0);
+        assert!((a * 6378.135 - sgp4.semimajor_axis_km()).abs() < 1e-9);
+
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        assert!(sgp4.is_deep_space() && sgp4.is_simplified());
+        assert!(sgp4.anomalistic_period_min() > 700.0);
+    }
+
+    #[test]
+    fn test_validity_window() {
+        let window = ValidityWindow::days(7.0, ValidityMode::Strict);
+        let mut sgp4 = SGP4::builder()
+            .validity_window(window)
+            .build_with_tle(iss_tle())
+            .unwrap();
+        assert_eq!(sgp4.validity_window(), Some(window));
+
+        assert!(sgp4.propagate(7.0 * DAY2MIN).is_ok());
+        let failure = sgp4.propagate(-8.0 * DAY2MIN).unwrap_err();
+        assert_eq!(failure.error, PropagationError::StaleElements);
+        assert_eq!(failure.tsince_min, -8.0 * DAY2MIN);
+        assert!(sgp4.mean_elements(30.0 * DAY2MIN).is_err());
+
+        assert_eq!(sgp4.stale(-8.0 * DAY2MIN), Some(failure));
+        assert_eq!(sgp4.stale(1.0), None);
+
+        // warnings and unchecked times propagate as without a window
+        sgp4.set_validity_window(Some(ValidityWindow::days(7.0, ValidityMode::Warn)));
+        let state = sgp4.propagate(8.0 * DAY2MIN).unwrap();
+        assert!(sgp4.stale(8.0 * DAY2MIN).is_some());
+        sgp4.set_validity_window(None);
+        assert_eq!(sgp4.propagate(8.0 * DAY2MIN), Ok(state));
+        assert_eq!(sgp4.stale(8.0 * DAY2MIN), None);
+    }
+
+    #[test]
+    fn test_propagate_jd() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        let (jd, jdfrac) = crate::time::jday(2024, (1, 1, 18, 0, 0.0));
+        let state = sgp4.propagate_jd(jd, jdfrac).unwrap();
+        assert_eq!(state, sgp4.propagate(360.0).unwrap());
+        assert_eq!(sgp4.propagate_jd(jd + 1.0, jdfrac - 1.0), Ok(state));
+    }
+
+    #[test]
+    fn test_propagate_coe() {
+        let sgp4 = vanguard(WGSModel::WGS_72);
+
+        let (state, coe) = sgp4.propagate_coe(360.0).unwrap();
+        assert_eq!(state, sgp4.propagate(360.0).unwrap());
+        let mu = get_grav_c(&WGSModel::WGS_72).mu;
+        assert_eq!(Some(coe), state.to_classical_elements(mu));
+
+        // the osculating elements are close to the mean ones
+        let mean = sgp4.mean_elements(360.0).unwrap();
+        assert!((coe.semimajor_axis - mean.semimajor_axis_km).abs() < 10.0);
+        assert!((coe.eccentricity - mean.eccentricity).abs() < 1e-3);
+        assert!((coe.inclination - mean.inclination).abs() < 1e-3);
+        assert!((coe.raan.unwrap() - mean.raan).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_revolution_number() {
+        let sgp4 = iss(WGSModel::WGS_72);
+        assert_eq!(sgp4.revolution_number(0.0).unwrap(), 43254);
+
+        // counted up as the satellite crosses the equator northward
+        let mut z = sgp4.propagate(-DAY2MIN).unwrap().position_km[2];
+        let mut revnum = sgp4.revolution_number(-DAY2MIN).unwrap();
+        let mut nodes = 0;
+        for minute in (1 - DAY2MIN as i64)..=DAY2MIN as i64 {
+            let t = minute as f64;
+            let z_next = sgp4.propagate(t).unwrap().position_km[2];
+            let revnum_next = sgp4.revolution_number(t).unwrap();
+            let node = z < 0.0 && z_next >= 0.0;
+            assert_eq!(revnum_next - revnum, u64::from(node), "{t}");
+            nodes += u64::from(node);
+            (z, revnum) = (z_next, revnum_next);
+        }
+        assert_eq!(nodes, 31);
+    }
+
+    #[test]
+    fn test_set_bstar() {
+        for (line1, line2) in [
+            (VANGUARD_LINE1, VANGUARD_LINE2),
+            // deep space, which only has part of the drag terms
+            (
+                "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+                "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656",
+            ),
+        ] {
+            let tle = TLE::new(line1.into(), line2.into()).unwrap();
+            let mut sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+            let original = sgp4.clone();
+            let elements = sgp4.elements();
+
+            // the same as initializing with the changed B*
+            sgp4.scale_bstar(3.0);
+            let expected = Elements {
+                bstar: 3.0 * elements.bstar,
+                ..elements.clone()
+            };
+            let expected =
+                SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &expected).unwrap();
+            assert_eq!(sgp4.elements().bstar, 3.0 * elements.bstar);
+            for t in [0.0, 1440.0, -720.0, 14400.0] {
+                assert_eq!(sgp4.propagate(t), expected.propagate(t));
+            }
+            assert_eq!(sgp4.propagate(0.0), original.propagate(0.0));
+            assert_ne!(sgp4.propagate(14400.0), original.propagate(14400.0));
+
+            sgp4.set_bstar(elements.bstar);
+            assert_eq!(sgp4.propagate(14400.0), original.propagate(14400.0));
+        }
+    }
+
+    #[test]
+    fn test_drag_coefficients() {
+        let sgp4 = vanguard(WGSModel::WGS_72);
+        let drag = sgp4.drag_coefficients();
+        assert!(!drag.isimp);
+        assert_eq!(drag.c1, 0.28098e-4 * drag.c2);
+        assert!(drag.c1 > 0.0 && drag.c4 > 0.0 && drag.c5 > 0.0 && drag.d2 > 0.0);
+        assert_eq!(
+            drag.d2,
+            4.0 * sgp4.sgp4init_out.ao * sgp4.sgp4init_out.tsi * drag.c1.powi(2)
+        );
+
+        // the decaying satellite has its perigee below 220 km
+        let tle = TLE::new(
+            "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534".into(),
+            "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        let drag = sgp4.drag_coefficients();
+        assert!(drag.isimp);
+        assert_eq!((drag.d2, drag.d3, drag.d4), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_lunar_solar_periodics() {
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        let periodics = sgp4.lunar_solar_periodics(1440.0).unwrap();
+        let total = periodics.total();
+        assert_eq!(
+            total.eccentricity,
+            periodics.solar.eccentricity + periodics.lunar.eccentricity
+        );
+        assert!(total.eccentricity.abs() > 1e-5 && total.eccentricity.abs() < 1e-2);
+
+        // each body's terms repeat with the period of its mean anomaly
+        let year = TWOPI / 1.19459e-5;
+        let month = TWOPI / 1.5835218e-4;
+        let solar = sgp4.lunar_solar_periodics(1440.0 + year).unwrap().solar;
+        let lunar = sgp4.lunar_solar_periodics(1440.0 + month).unwrap().lunar;
+        assert!((solar.inclination - periodics.solar.inclination).abs() < 1e-12);
+        assert!((lunar.node - periodics.lunar.node).abs() < 1e-12);
+        assert!(
+            (lunar.node
+                - sgp4
+                    .lunar_solar_periodics(1440.0 + 0.5 * month)
+                    .unwrap()
+                    .lunar
+                    .node)
+                .abs()
+                > 1e-6
+        );
+
+        let sgp4 = iss(WGSModel::WGS_72);
+        assert_eq!(sgp4.lunar_solar_periodics(0.0), None);
+    }
+
+    #[test]
+    fn test_resonance_options() {
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let with = |options: ResonanceOptions| {
+            SGP4::builder()
+                .resonance(options)
+                .build_with_tle(tle.clone())
+                .unwrap()
+        };
+        let sgp4 = with(ResonanceOptions::default());
+        assert_eq!(sgp4.resonance(), Resonance::HalfDay);
+        let expected = sgp4.propagate(1e5).unwrap();
+
+        // a finer step changes the state little, and strict mode not at all
+        let fine = with(ResonanceOptions {
+            step_min: 360.0,
+            ..ResonanceOptions::default()
+        });
+        let state = fine.propagate(1e5).unwrap();
+        assert!(mag([0, 1, 2].map(|i| state.position_km[i] - expected.position_km[i])) < 1.0);
+        let strict = ResonanceOptions {
+            strict: true,
+            ..ResonanceOptions::default()
+        };
+        assert_eq!(with(strict).propagate(1e5).unwrap(), expected);
+        let error = with(strict).propagate(f64::INFINITY).unwrap_err().error;
+        assert_eq!(error, PropagationError::ResonanceDiverged);
+
+        // a step far too large for the resonance terms diverges
+        let coarse = ResonanceOptions {
+            step_min: 1e6,
+            ..strict
+        };
+        let error = with(coarse).propagate(1e7).unwrap_err().error;
+        assert_eq!(error, PropagationError::ResonanceDiverged);
+
+        let off = with(ResonanceOptions {
+            half_day: false,
+            ..ResonanceOptions::default()
+        });
+        assert_eq!(off.resonance(), Resonance::None);
+        assert_ne!(off.propagate(1e5).unwrap(), expected);
+
+        let sgp4 = vanguard(WGSModel::WGS_72);
+        assert_eq!(sgp4.resonance(), Resonance::None);
+    }
+
+    #[test]
+    fn test_clone() {
+        // Molniya orbit, to include the deep space terms
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle).unwrap();
+        let copy = sgp4.clone();
+        for t in [1440.0, -720.0, 2880.0] {
+            assert_eq!(copy.propagate(t), sgp4.propagate(t));
+        }
+        assert!(alloc::format!("{sgp4:?}").contains("satnum: 8195"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_concurrent_propagation() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SGP4>();
+
+        // 12 hour resonance, so the integrator runs over several steps
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle).unwrap();
+        let times = [2880.0, 1440.0, -1440.0, 4320.0, 720.0];
+        let expected: Vec<_> = times.iter().map(|&t| sgp4.propagate(t)).collect();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for (&t, expected) in times.iter().rev().zip(expected.iter().rev()) {
+                        assert_eq!(&sgp4.propagate(t), expected);
+                    }
+                });
+            }
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        // Molniya orbit, to include the deep space terms
+        let tle = TLE::new(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813".into(),
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle).unwrap();
+        let json = serde_json::to_string(&sgp4).unwrap();
+        let restored: SGP4 = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.propagate(1440.0), sgp4.propagate(1440.0));
+    }
+
+    #[test]
+    fn test_ground_track() {
+        let sgp4 = iss(WGSModel::WGS_84);
+
+        let track = sgp4.ground_track(0.0, 93.0, 1.0).unwrap();
+        assert_eq!(track.len(), 94);
+        assert_eq!(track[93].t, 93.0);
+        for point in &track {
+            // the geodetic latitude reaches a little past the inclination
+            assert!(point.latitude.abs() < radians(52.0));
+            assert!(point.altitude > 400.0 && point.altitude < 440.0);
+        }
+        // one revolution crosses the antimeridian once
+        let segments = crate::groundtrack::split_at_antimeridian(&track);
+        assert_eq!(segments.len(), 2);
+
+        for step in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let failure = sgp4.ground_track(10.0, 93.0, step).unwrap_err();
+            assert_eq!(failure.error, PropagationError::InvalidStep);
+            assert_eq!(failure.tsince_min, 10.0);
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_propagate_datetime() {
+        use crate::fixtures::vanguard_tle;
+        use chrono::{TimeZone, Utc};
+
+        let tle = vanguard_tle();
+        let epoch = Utc.with_ymd_and_hms(2000, 6, 27, 18, 50, 19).unwrap()
+            + chrono::Duration::microseconds(733568);
+        assert!(
+            (tle.epoch_datetime() - epoch)
+                .num_microseconds()
+                .unwrap()
+                .abs()
+                <= 1
+        );
+
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        let datetime = epoch + chrono::Duration::minutes(360);
+        assert!((sgp4.minutes_since_epoch(&datetime) - 360.0).abs() < 1e-6);
+        assert!(
+            (sgp4.datetime(360.0).unwrap() - datetime)
+                .num_microseconds()
+                .unwrap()
+                .abs()
+                <= 1
+        );
+        assert_eq!(sgp4.datetime(f64::INFINITY), None);
+        let r = sgp4.propagate_at(&datetime).unwrap().position_km;
+        let r_expected = sgp4.propagate(360.0).unwrap().position_km;
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-5);
+        }
+    }
+
+    #[cfg(feature = "hifitime")]
+    #[test]
+    fn test_propagate_hifitime() {
+        use crate::fixtures::vanguard_tle;
+        use hifitime::{Epoch, TimeScale, Unit};
+
+        let tle = vanguard_tle();
+        let epoch = Epoch::from_gregorian_utc(2000, 6, 27, 18, 50, 19, 733_568_000);
+        assert!((tle.epoch_hifitime() - epoch).abs() < Unit::Microsecond * 1);
+
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        let target = (epoch + Unit::Minute * 360).to_time_scale(TimeScale::TAI);
+        assert!((sgp4.minutes_since_epoch(&target) - 360.0).abs() < 1e-6);
+        assert!((sgp4.hifitime_epoch(360.0) - target).abs() < Unit::Microsecond * 1);
+        let r = sgp4.propagate_at(&target).unwrap().position_km;
+        let r_expected = sgp4.propagate(360.0).unwrap().position_km;
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let sgp4 = vanguard(WGSModel::WGS_72);
+
+        let ephemeris: Vec<_> = sgp4
+            .iter(0.0, 4320.0, 360.0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(ephemeris.len(), 13);
+        assert_eq!(sgp4.iter(0.0, 4320.0, 360.0).unwrap().len(), 13);
+        assert_eq!(sgp4.iter(10.0, 0.0, 1.0).unwrap().count(), 0);
+        for step in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let failure = sgp4.iter(0.0, 4320.0, step).err().unwrap();
+            assert_eq!(failure.error, PropagationError::InvalidStep);
+        }
+        let State {
+            epoch,
+            position_km: r,
+            ..
+        } = ephemeris[1];
+        assert!((epoch.minutes_since(sgp4.epoch()) - 360.0).abs() < 1e-6);
+        let r_expected = [-7154.03120202, -3783.17682504, -3536.19412294];
+        for i in 0..3 {
+            assert!((r[i] - r_expected[i]).abs() < 1e-7);
+        }
+        let last = ephemeris.last().unwrap();
+        assert!((last.epoch.minutes_since(sgp4.epoch()) - 4320.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_builder() {
+        let sgp4 = SGP4::builder()
+            .schedule(0.0, 4320.0, 360.0)
+            .build_with_tle(TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap())
+            .unwrap();
+        assert_eq!(sgp4.model(), WGSModel::WGS_72);
+        assert_eq!(sgp4.run().unwrap().count(), 13);
+        assert_eq!(sgp4.satrec.epoch.jd(), 2451722.5);
+        let default = SGP4::default();
+        assert_eq!(
+            (default.model(), default.operation_mode()),
+            (WGSModel::WGS_84, OperationMode::Afspc)
+        );
+
+        // epoch year 00 in the 1900s
+        let sgp4 = SGP4::builder()
+            .year_pivot(0)
+            .build_with_tle(TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap())
+            .unwrap();
+        assert_eq!(
+            sgp4.satrec.epoch,
+            Epoch::from_calendar(1900, days2mdh(1900, 179.78495062))
+        );
+
+        let mut sgp4 = SGP4::builder().checksum_mode(ChecksumMode::Strict).build();
+        let bad_line2 = VANGUARD_LINE2.replace("13667", "13668");
+        assert!(matches!(
+            sgp4.twoline2rv(VANGUARD_LINE1.into(), bad_line2),
+            Err(Sgp4Error::Parse(TleParseError::ChecksumMismatch { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_run_schedule() {
+        let line1 = "1 04632U 70093B   04031.91070959 -.00000084  00000-0  10000-3 0  9955";
+        let line2 = "2 04632  11.4628 273.1101 1450506 207.6000 143.9350  1.20231981 44145";
+        let verification_line2 = alloc::format!("{line2}       -5184       -4896         120");
+
+        let mut sgp4 = SGP4::builder()
+            .run_schedule(RunSchedule::Verification)
+            .build();
+        let (state, schedule) = sgp4
+            .twoline2rv(line1.into(), verification_line2.clone())
+            .unwrap();
+        assert_eq!(schedule, (-5184.0, -4896.0, 120.0));
+        assert_eq!(sgp4.schedule(), schedule);
+        assert_eq!(sgp4.run().unwrap().count(), 3);
+        assert_eq!(sgp4.satnum(), 4632);
+        let (expected, catalog) = SGP4::builder()
+            .build()
+            .twoline2rv(line1.into(), line2.into())
+            .unwrap();
+        assert_eq!(state.position_km, expected.position_km);
+        assert_eq!(catalog, CATALOG_SCHEDULE);
+
+        // the caller's times, whatever follows line 2
+        let manual = RunSchedule::Manual {
+            start: 0.0,
+            stop: 720.0,
+            step: 60.0,
+        };
+        let mut sgp4_manual = SGP4::builder().run_schedule(manual).build();
+        assert_eq!(sgp4_manual.run_schedule(), manual);
+        let (_, schedule) = sgp4_manual
+            .twoline2rv(line1.into(), verification_line2.clone())
+            .unwrap();
+        assert_eq!(schedule, (0.0, 720.0, 60.0));
+        assert_eq!(sgp4_manual.run().unwrap().count(), 13);
+
+        let result = sgp4.twoline2rv(line1.into(), line2.into());
+        assert!(matches!(
+            result,
+            Err(Sgp4Error::Parse(TleParseError::InvalidField {
+                field: "run schedule",
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_from_jd() {
+        let mut sgp4 = SGP4::builder()
+            .run_schedule(RunSchedule::FromJD { step: 60.0 })
+            .build();
+        assert_eq!(
+            sgp4.twoline2rv(VANGUARD_LINE1.into(), VANGUARD_LINE2.into())
+                .unwrap_err(),
+            Sgp4Error::InvalidSchedule
+        );
+
+        sgp4.set_jd_from_yr_doy(2000, 179.78495062, 2000, 181.78495062)
+            .unwrap();
+        let (_, (start, stop, step)) = sgp4
+            .twoline2rv(VANGUARD_LINE1.into(), VANGUARD_LINE2.into())
+            .unwrap();
+        assert!(
+            start.abs() < 1e-6 && (stop - 2880.0).abs() < 1e-6,
+            "{start} {stop}"
+        );
+        assert_eq!(step, 60.0);
+        assert_eq!(sgp4.run().unwrap().count(), 49);
+
+        // the day of the epoch, from midnight to midnight
+        sgp4.set_jd_from_ymdhms(2000, (6, 27, 0, 0, 0.0), 2000, (6, 28, 0, 0, 0.0))
+            .unwrap();
+        let (_, (start, stop, _)) = sgp4
+            .twoline2rv(VANGUARD_LINE1.into(), VANGUARD_LINE2.into())
+            .unwrap();
+        assert!((start - -0.78495062 * DAY2MIN).abs() < 1e-6);
+        assert!((stop - start - DAY2MIN).abs() < 1e-6);
+
+        assert_eq!(
+            sgp4.set_jd_from_yr_doy(2000, 180.0, 2000, 180.0),
+            Err(Sgp4Error::InvalidSchedule)
+        );
+        assert_eq!(
+            sgp4.set_jd_from_yr_doy(2001, 1.0, 2000, 366.0),
+            Err(Sgp4Error::InvalidSchedule)
+        );
+    }
+
+    #[test]
+    fn test_propagate_many() {
+        let sgp4 = iss(WGSModel::WGS_72);
+
+        let times = [0.0, 360.0, 1e7, 720.0];
+        let states = sgp4.propagate_many(&times);
+        assert_eq!(states.len(), times.len());
+        // far past the lifetime of the elements the orbit is no longer valid
+        assert!(states[2].is_err());
+        for (&t, state) in times.iter().zip(&states) {
+            if let Ok(state) = state {
+                assert_eq!(*state, sgp4.propagate(t).unwrap());
+            }
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_propagate_x4() {
+        let sgp4 = iss(WGSModel::WGS_72);
+
+        let times = [-1440.0, 0.0, 1e7, 4320.5];
+        let states = sgp4.propagate_x4(times);
+        for (&t, state) in times.iter().zip(states) {
+            match sgp4.propagate(t) {
+                Ok(expected) => {
+                    let state = state.unwrap();
+                    assert_eq!(state.epoch, expected.epoch);
+                    for i in 0..3 {
+                        assert!((state.position_km[i] - expected.position_km[i]).abs() < 1e-6);
+                        assert!((state.velocity_km_s[i] - expected.velocity_km_s[i]).abs() < 1e-9);
+                    }
+                }
+                Err(error) => assert_eq!(state, Err(error)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_twoline2rv_parse_errors() {
+        let result = SGP4::default().twoline2rv(VANGUARD_LINE1.into(), VANGUARD_LINE2[..60].into());
+        assert_eq!(
+            result.unwrap_err(),
+            Sgp4Error::Parse(TleParseError::LineTooShort {
+                line: 2,
+                length: 60
+            })
+        );
+
+        let result = SGP4::default().twoline2rv(VANGUARD_LINE2.into(), VANGUARD_LINE1.into());
+        assert_eq!(
+            result.unwrap_err(),
+            Sgp4Error::Parse(TleParseError::InvalidLineNumber { line: 1 })
+        );
+
+        let bad_line2 = VANGUARD_LINE2.replace("34.2682", "34.2x82");
+        let result = SGP4::default().twoline2rv(VANGUARD_LINE1.into(), bad_line2);
+        assert_eq!(
+            result.unwrap_err(),
+            Sgp4Error::Parse(TleParseError::InvalidField {
+                line: 2,
+                field: "inclination",
+                text: "34.2x82".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_propagation_error() {
+        let errors = [
+            (PropagationError::InvalidElements, 1),
+            (PropagationError::NegativeMeanMotion, 2),
+            (PropagationError::EccentricityOutOfRange, 3),
+            (PropagationError::NegativeSemilatusRectum, 4),
+            (PropagationError::OrbitalDecay, 6),
+            (PropagationError::ResonanceDiverged, 7),
+            (PropagationError::StaleElements, 8),
+            (PropagationError::InvalidStep, 9),
+        ];
+        for (error, code) in errors {
+            assert_eq!(error.code(), code);
+            assert_eq!(PropagationError::from_code(code), Some(error));
+        }
+        assert_eq!(PropagationError::from_code(0), None);
+        assert_eq!(PropagationError::from_code(5), None);
+
+        let tle = TLE::new(
+            "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534".into(),
+            "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708".into(),
+        )
+        .unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle).unwrap();
+        let failure = sgp4.propagate(60.0).unwrap_err();
+        assert_eq!((failure.satnum, failure.tsince_min), (28872, 60.0));
+        assert_eq!(failure.epoch, sgp4.epoch());
+        assert_eq!(failure.code(), 6);
+        assert_eq!(
+            failure.to_string(),
+            "satellite 28872 at 60 min since epoch JD 2453703.52012661: \
+             satellite has decayed (error 6)"
+        );
+        let error = PropagationError::from(failure);
+        assert_eq!(error, PropagationError::OrbitalDecay);
+        assert_eq!(error.to_string(), "satellite has decayed (error 6)");
+        assert_eq!(
+            Sgp4Error::from(error).to_string(),
+            "invalid elements: satellite has decayed (error 6)"
+        );
+    }
+
+    #[test]
+    fn test_tle_checksum() {
+        assert_eq!(tle_checksum(VANGUARD_LINE1), 3);
+        assert_eq!(tle_checksum(VANGUARD_LINE2), 7);
+
+        let bad_line2 = VANGUARD_LINE2.replace("34.2682", "34.2683");
+        let mut sgp4 = SGP4::default();
+        sgp4.set_checksum_mode(ChecksumMode::Strict);
+        assert_eq!(
+            sgp4.twoline2rv(VANGUARD_LINE1.into(), bad_line2.clone())
+                .unwrap_err(),
+            Sgp4Error::Parse(TleParseError::ChecksumMismatch {
+                line: 2,
+                expected: 8,
+                found: '7'
+            })
+        );
+
+        // reported, but accepted, in the default Warn mode
+        let mut sgp4 = SGP4::default();
+        assert!(
+            sgp4.twoline2rv(VANGUARD_LINE1.into(), bad_line2.clone())
+                .is_ok()
+        );
+        assert_eq!(
+            sgp4.warnings(),
+            [TleParseError::ChecksumMismatch {
+                line: 2,
+                expected: 8,
+                found: '7'
+            }]
+        );
+        let tle = TLE::new(VANGUARD_LINE1.into(), bad_line2.clone()).unwrap();
+        let sgp4 = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Improved, tle).unwrap();
+        assert_eq!(sgp4.warnings().len(), 1);
 
-        (r_init, v_init)
+        let mut sgp4 = SGP4::default();
+        sgp4.set_checksum_mode(ChecksumMode::Ignore);
+        assert!(sgp4.twoline2rv(VANGUARD_LINE1.into(), bad_line2).is_ok());
+        assert!(sgp4.warnings().is_empty());
     }
 }
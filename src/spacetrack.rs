@@ -0,0 +1,341 @@
+use core::fmt;
+use std::error::Error;
+use std::io;
+
+use crate::elements::Elements;
+use crate::http::{HttpClient, HttpResponse, percent_encode};
+use crate::omm::{OmmParseError, parse_json};
+use crate::time::Epoch;
+
+/// Base URL of the Space-Track API
+pub const SPACETRACK_URL: &str = "https://www.space-track.org";
+
+/// Errors of Space-Track queries.
+#[derive(Debug)]
+pub enum SpaceTrackError {
+    /// The request got no response
+    Http(io::Error),
+    /// The response has an error status
+    Status { status: u16, body: String },
+    /// The identity or password was rejected
+    LoginFailed,
+    /// The response doesn't hold valid GP records
+    Parse(OmmParseError),
+}
+
+impl fmt::Display for SpaceTrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpaceTrackError::Http(error) => write!(f, "request failed: {error}"),
+            SpaceTrackError::Status { status, body } => {
+                write!(f, "request failed with status {status}: {body:?}")
+            }
+            SpaceTrackError::LoginFailed => write!(f, "login failed"),
+            SpaceTrackError::Parse(error) => write!(f, "invalid response: {error}"),
+        }
+    }
+}
+
+impl Error for SpaceTrackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpaceTrackError::Http(error) => Some(error),
+            SpaceTrackError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpaceTrackError {
+    fn from(error: io::Error) -> Self {
+        SpaceTrackError::Http(error)
+    }
+}
+
+impl From<OmmParseError> for SpaceTrackError {
+    fn from(error: OmmParseError) -> Self {
+        SpaceTrackError::Parse(error)
+    }
+}
+
+/// Client for the general perturbations (GP) classes of the Space-Track API, returning
+/// the mean elements of the records.
+///
+/// The client logs in on the first query and keeps the session cookie, logging in
+/// again if the session expires. Mind the query limits of Space-Track: fetch many
+/// satellites with one query rather than one query each.
+pub struct SpaceTrackClient<C: HttpClient> {
+    http: C,
+    base_url: String,
+    identity: String,
+    password: String,
+    cookie: Option<String>,
+}
+
+impl<C: HttpClient + fmt::Debug> fmt::Debug for SpaceTrackClient<C> {
+    /// Write the client with the password and the session cookie redacted, so logging
+    /// it doesn't leak the credentials.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpaceTrackClient")
+            .field("http", &self.http)
+            .field("base_url", &self.base_url)
+            .field("identity", &self.identity)
+            .field("password", &"<redacted>")
+            .field("cookie", &self.cookie.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl<C: HttpClient> SpaceTrackClient<C> {
+    /// Create a client with the account identity (e-mail address) and password, sending
+    /// requests through `http`.
+    pub fn new(http: C, identity: &str, password: &str) -> Self {
+        SpaceTrackClient {
+            http,
+            base_url: SPACETRACK_URL.into(),
+            identity: identity.into(),
+            password: password.into(),
+            cookie: None,
+        }
+    }
+
+    /// Send the requests to another server than `SPACETRACK_URL`, e.g. a test instance.
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').into();
+        self
+    }
+
+    /// Log in, keeping the session cookie for the queries.
+    pub fn login(&mut self) -> Result<(), SpaceTrackError> {
+        let body = format!(
+            "identity={}&password={}",
+            percent_encode(&self.identity),
+            percent_encode(&self.password)
+        );
+        let response = self.http.post(
+            &format!("{}/ajaxauth/login", self.base_url),
+            &[("Content-Type", "application/x-www-form-urlencoded")],
+            &body,
+        )?;
+        let response = check_status(response)?;
+        // a rejected login is answered with {"Login":"Failed"}
+        let cookie = response
+            .header("Set-Cookie")
+            .and_then(|cookie| cookie.split(';').next());
+        match cookie {
+            Some(cookie) if !response.body.contains("Failed") => {
+                self.cookie = Some(cookie.trim().into());
+                Ok(())
+            }
+            _ => Err(SpaceTrackError::LoginFailed),
+        }
+    }
+
+    /// Latest elements of the satellites with the given NORAD catalog numbers, in
+    /// order of the numbers. Satellites without elements are left out.
+    pub fn gp_by_norad_ids(&mut self, norad_ids: &[u64]) -> Result<Vec<Elements>, SpaceTrackError> {
+        self.query(&format!(
+            "class/gp/NORAD_CAT_ID/{}/orderby/NORAD_CAT_ID asc",
+            join_ids(norad_ids)
+        ))
+    }
+
+    /// All elements of the satellites with the given NORAD catalog numbers with an
+    /// epoch between `start` and `stop` (UTC, to the second), from the GP history, in
+    /// order of the epochs.
+    pub fn gp_history_by_epoch(
+        &mut self,
+        norad_ids: &[u64],
+        start: Epoch,
+        stop: Epoch,
+    ) -> Result<Vec<Elements>, SpaceTrackError> {
+        self.query(&format!(
+            "class/gp_history/NORAD_CAT_ID/{}/EPOCH/{}--{}/orderby/EPOCH asc",
+            join_ids(norad_ids),
+            format_epoch(start),
+            format_epoch(stop)
+        ))
+    }
+
+    /// Run a GP query, given as the path after "/basicspacedata/query/" without the
+    /// format, e.g. "class/gp/OBJECT_NAME/~~STARLINK/EPOCH/>now-1".
+    pub fn query(&mut self, query: &str) -> Result<Vec<Elements>, SpaceTrackError> {
+        let url = format!(
+            "{}/basicspacedata/query/{}/format/json",
+            self.base_url,
+            encode_query(query)
+        );
+        if self.cookie.is_none() {
+            self.login()?;
+        }
+        let mut response = self.get(&url)?;
+        if response.status == 401 {
+            // the session expired
+            self.login()?;
+            response = self.get(&url)?;
+        }
+        let response = check_status(response)?;
+        Ok(parse_json(&response.body)?
+            .into_iter()
+            .map(Elements::from)
+            .collect())
+    }
+
+    fn get(&mut self, url: &str) -> io::Result<HttpResponse> {
+        let cookie = self.cookie.clone().unwrap_or_default();
+        self.http.get(url, &[("Cookie", &cookie)])
+    }
+}
+
+fn check_status(response: HttpResponse) -> Result<HttpResponse, SpaceTrackError> {
+    if response.is_success() {
+        Ok(response)
+    } else {
+        Err(SpaceTrackError::Status {
+            status: response.status,
+            body: response.body,
+        })
+    }
+}
+
+fn join_ids(norad_ids: &[u64]) -> String {
+    let ids: Vec<String> = norad_ids.iter().map(u64::to_string).collect();
+    ids.join(",")
+}
+
+/// Format an epoch as "YYYY-MM-DD HH:MM:SS", the date and time format of the queries.
+fn format_epoch(epoch: Epoch) -> String {
+    let (year, (month, day, hour, minute, second)) = epoch.to_calendar();
+    format!(
+        "{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{:02}",
+        second.floor() as u64
+    )
+}
+
+/// Percent-encode the segments of a query path, keeping the operators of the query
+/// language that are safe in a path.
+fn encode_query(query: &str) -> String {
+    let segments: Vec<String> = query
+        .split('/')
+        .map(|segment| {
+            percent_encode(segment)
+                .replace("%2C", ",")
+                .replace("%3E", ">")
+                .replace("%3C", "<")
+                .replace("%3A", ":")
+                .replace("%5E", "^")
+        })
+        .collect();
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::{GP, MockHttp};
+
+    fn response(status: u16, cookie: Option<&str>, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: cookie
+                .map(|cookie| vec![("set-cookie".into(), cookie.into())])
+                .unwrap_or_default(),
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn test_gp_by_norad_ids() {
+        let http = MockHttp {
+            responses: vec![
+                response(200, Some("chocolatechip=abc; path=/"), ""),
+                response(200, None, GP),
+                // the session expired before the second query
+                response(401, None, ""),
+                response(200, Some("chocolatechip=def; path=/"), ""),
+                response(200, None, "[]"),
+            ],
+            ..MockHttp::default()
+        };
+        let mut client = SpaceTrackClient::new(http, "user@example.com", "p&ss");
+        let elements = client.gp_by_norad_ids(&[5, 25544]).unwrap();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(
+            (elements[0].satnum, elements[0].name.as_deref()),
+            (5, Some("VANGUARD 1"))
+        );
+        let start = Epoch::from_calendar(2024, (1, 1, 0, 0, 0.0));
+        let elements = client
+            .gp_history_by_epoch(&[5], start, start + 1.5)
+            .unwrap();
+        assert!(elements.is_empty());
+
+        let requests = &client.http.requests;
+        assert_eq!(requests.len(), 5);
+        assert_eq!(
+            requests[0].url,
+            "https://www.space-track.org/ajaxauth/login"
+        );
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some("identity=user%40example.com&password=p%26ss")
+        );
+        assert_eq!(
+            requests[1].url,
+            "https://www.space-track.org/basicspacedata/query/class/gp/NORAD_CAT_ID/5,25544\
+             /orderby/NORAD_CAT_ID%20asc/format/json"
+        );
+        assert_eq!(
+            requests[1].headers,
+            [("Cookie".into(), "chocolatechip=abc".into())]
+        );
+        assert_eq!(
+            requests[4].url,
+            "https://www.space-track.org/basicspacedata/query/class/gp_history/NORAD_CAT_ID/5\
+             /EPOCH/2024-01-01%2000:00:00--2024-01-02%2012:00:00/orderby/EPOCH%20asc/format/json"
+        );
+        assert_eq!(
+            requests[4].headers,
+            [("Cookie".into(), "chocolatechip=def".into())]
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut client = SpaceTrackClient::new(MockHttp::default(), "user@example.com", "p&ss");
+        client.cookie = Some("chocolatechip=abc".into());
+        let debug = format!("{client:?}");
+        assert!(debug.contains("user@example.com"), "{debug}");
+        assert!(
+            !debug.contains("p&ss") && !debug.contains("chocolatechip"),
+            "{debug}"
+        );
+        assert_eq!(debug.matches("<redacted>").count(), 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let http = MockHttp {
+            responses: vec![response(200, None, r#"{"Login":"Failed"}"#)],
+            ..MockHttp::default()
+        };
+        let mut client = SpaceTrackClient::new(http, "user@example.com", "wrong");
+        assert!(matches!(
+            client.gp_by_norad_ids(&[5]),
+            Err(SpaceTrackError::LoginFailed)
+        ));
+
+        let http = MockHttp {
+            responses: vec![
+                response(200, Some("chocolatechip=abc"), ""),
+                response(500, None, "error"),
+            ],
+            ..MockHttp::default()
+        };
+        let mut client = SpaceTrackClient::new(http, "user@example.com", "p&ss");
+        assert!(matches!(
+            client.gp_by_norad_ids(&[5]),
+            Err(SpaceTrackError::Status { status: 500, .. })
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::coe::{ClassicalElements, coe2rv, rv2coe};
+use crate::frames::{Geodetic, ecef_to_geodetic, teme_to_ecef};
+use crate::time::Epoch;
+use crate::utils::mag;
+
+/// Reference frame of a state vector.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Frame {
+    /// True Equator, Mean Equinox, the frame SGP4 works in
+    #[default]
+    Teme,
+    /// Pseudo earth fixed, see `frames::teme_to_ecef`
+    Ecef,
+}
+
+/// Position and velocity of a satellite at an epoch.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State {
+    /// Epoch of the state in UTC
+    pub epoch: Epoch,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+    pub frame: Frame,
+}
+
+impl State {
+    /// State of classical orbital elements referred to `frame`, with the gravitational
+    /// parameter `mu` in km^3/s^2. See `coe::coe2rv`.
+    pub fn from_classical_elements(
+        coe: &ClassicalElements,
+        mu: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> State {
+        let (position_km, velocity_km_s) = coe2rv(coe, mu);
+        State {
+            epoch,
+            position_km,
+            velocity_km_s,
+            frame,
+        }
+    }
+
+    /// Distance from the center of the earth in km
+    pub fn norm(&self) -> f64 {
+        mag(self.position_km)
+    }
+
+    /// Speed in km/s, relative to the rotating earth in the earth fixed frame
+    pub fn speed(&self) -> f64 {
+        mag(self.velocity_km_s)
+    }
+
+    /// The state in the earth fixed frame, taking the epoch as UT1.
+    pub fn to_ecef(&self) -> State {
+        match self.frame {
+            Frame::Teme => {
+                let (position_km, velocity_km_s) = teme_to_ecef(
+                    self.position_km,
+                    self.velocity_km_s,
+                    self.epoch.julian_date(),
+                );
+                State {
+                    epoch: self.epoch,
+                    position_km,
+                    velocity_km_s,
+                    frame: Frame::Ecef,
+                }
+            }
+            Frame::Ecef => *self,
+        }
+    }
+
+    /// Osculating classical orbital elements with the gravitational parameter `mu` in
+    /// km^3/s^2, see `coe::rv2coe`.
+    pub fn to_classical_elements(&self, mu: f64) -> Option<ClassicalElements> {
+        rv2coe(self.position_km, self.velocity_km_s, mu)
+    }
+
+    /// State of a nalgebra position in km and velocity in km/s.
+    #[cfg(feature = "nalgebra")]
+    pub fn from_vector3(
+        epoch: Epoch,
+        position_km: nalgebra::Vector3<f64>,
+        velocity_km_s: nalgebra::Vector3<f64>,
+        frame: Frame,
+    ) -> State {
+        State {
+            epoch,
+            position_km: position_km.into(),
+            velocity_km_s: velocity_km_s.into(),
+            frame,
+        }
+    }
+
+    /// Position in km as a nalgebra vector
+    #[cfg(feature = "nalgebra")]
+    pub fn position_vector3(&self) -> nalgebra::Vector3<f64> {
+        self.position_km.into()
+    }
+
+    /// Velocity in km/s as a nalgebra vector
+    #[cfg(feature = "nalgebra")]
+    pub fn velocity_vector3(&self) -> nalgebra::Vector3<f64> {
+        self.velocity_km_s.into()
+    }
+
+    /// State of a glam position in km and velocity in km/s.
+    #[cfg(feature = "glam")]
+    pub fn from_dvec3(
+        epoch: Epoch,
+        position_km: glam::DVec3,
+        velocity_km_s: glam::DVec3,
+        frame: Frame,
+    ) -> State {
+        State {
+            epoch,
+            position_km: position_km.to_array(),
+            velocity_km_s: velocity_km_s.to_array(),
+            frame,
+        }
+    }
+
+    /// Position in km as a glam vector
+    #[cfg(feature = "glam")]
+    pub fn position_dvec3(&self) -> glam::DVec3 {
+        self.position_km.into()
+    }
+
+    /// Velocity in km/s as a glam vector
+    #[cfg(feature = "glam")]
+    pub fn velocity_dvec3(&self) -> glam::DVec3 {
+        self.velocity_km_s.into()
+    }
+
+    /// WGS-84 geodetic coordinates of the position.
+    pub fn to_geodetic(&self) -> Geodetic {
+        ecef_to_geodetic(self.to_ecef().position_km)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames::teme_to_geodetic;
+
+    #[test]
+    #[cfg(feature = "nalgebra")]
+    fn test_nalgebra_vectors() {
+        let state = State {
+            epoch: Epoch::new(2451545.0, 0.25),
+            position_km: [7000.0, -1200.0, 300.0],
+            velocity_km_s: [0.5, 7.4, -1.1],
+            frame: Frame::Teme,
+        };
+        let position = state.position_vector3();
+        assert!((position.norm() - state.norm()).abs() < 1e-9);
+        assert!((state.velocity_vector3().norm() - state.speed()).abs() < 1e-12);
+        let round_trip =
+            State::from_vector3(state.epoch, position, state.velocity_vector3(), state.frame);
+        assert_eq!(round_trip, state);
+    }
+
+    #[test]
+    #[cfg(feature = "glam")]
+    fn test_glam_vectors() {
+        let state = State {
+            epoch: Epoch::new(2451545.0, 0.25),
+            position_km: [7000.0, -1200.0, 300.0],
+            velocity_km_s: [0.5, 7.4, -1.1],
+            frame: Frame::Teme,
+        };
+        let position = state.position_dvec3();
+        assert!((position.length() - state.norm()).abs() < 1e-9);
+        assert!((state.velocity_dvec3().length() - state.speed()).abs() < 1e-12);
+        let round_trip =
+            State::from_dvec3(state.epoch, position, state.velocity_dvec3(), state.frame);
+        assert_eq!(round_trip, state);
+    }
+
+    #[test]
+    fn test_state() {
+        let state = State {
+            epoch: Epoch::new(2451722.5, 0.78495062),
+            position_km: [7022.46529266, -1400.08296755, 0.03995155],
+            velocity_km_s: [1.893841015, 6.405893759, 4.534807250],
+            frame: Frame::Teme,
+        };
+        assert!((state.norm() - 7160.67393).abs() < 1e-5);
+        assert!((state.speed() - 8.07382).abs() < 1e-5);
+
+        let ecef = state.to_ecef();
+        assert_eq!(ecef.frame, Frame::Ecef);
+        assert!((ecef.norm() - state.norm()).abs() < 1e-8);
+        assert_eq!(ecef.to_ecef(), ecef);
+        let geodetic = teme_to_geodetic(state.position_km, state.epoch.julian_date());
+        assert_eq!(state.to_geodetic(), geodetic);
+    }
+}
@@ -0,0 +1,253 @@
+use core::fmt;
+
+use crate::elements::Elements;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::sgp4::{PropagationError, SGP4};
+
+/// A 6x6 matrix over states (x, y, z in km, vx, vy, vz in km/s), row major
+pub type Matrix6 = [[f64; 6]; 6];
+
+/// Steps of the central differences in the nonsingular elements: inclination, node,
+/// h = e sin(argp), k = e cos(argp) and mean longitude in radians, and a relative step
+/// of the mean motion
+pub(crate) const STEPS: [f64; 6] = [1e-5, 1e-5, 1e-5, 1e-5, 1e-5, 1e-7];
+
+/// Pivots below this make the partials of the states singular
+const SINGULAR: f64 = 1e-12;
+
+/// Errors of the state transition matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StmError {
+    /// A perturbed satellite couldn't be initialized or propagated
+    Propagation(PropagationError),
+    /// The state doesn't determine the elements, e.g. for an equatorial orbit, which
+    /// has no node
+    Singular,
+}
+
+impl From<PropagationError> for StmError {
+    fn from(error: PropagationError) -> Self {
+        StmError::Propagation(error)
+    }
+}
+
+impl fmt::Display for StmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StmError::Propagation(error) => write!(f, "{error}"),
+            StmError::Singular => write!(f, "the state doesn't determine the elements"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StmError::Propagation(error) => Some(error),
+            StmError::Singular => None,
+        }
+    }
+}
+
+/// State transition matrix of a satellite from `t0_min` to `t1_min` minutes since
+/// epoch: the partials of the TEME state at `t1_min` with respect to the state at
+/// `t0_min`, so a small change `dx0` of the state maps to `stm * dx0`.
+///
+/// SGP4 propagates mean elements, not states, so the matrix is found through them:
+/// with `J(t)` the partials of the state at `t` with respect to the elements, found by
+/// central differences, the matrix is `J(t1) J(t0)^-1`. The eccentricity and argument
+/// of perigee are differenced as e sin(argp) and e cos(argp), with the mean anomaly as
+/// the mean longitude, which keeps near circular orbits nonsingular. Each call
+/// initializes twelve perturbed satellites.
+pub fn state_transition_matrix(sgp4: &SGP4, t0_min: f64, t1_min: f64) -> Result<Matrix6, StmError> {
+    let elements = sgp4.elements();
+    let mut partials0 = [[0.0; 6]; 6];
+    let mut partials1 = [[0.0; 6]; 6];
+    for (j, step) in STEPS.iter().enumerate() {
+        let mut states = [[[0.0; 6]; 2]; 2];
+        for (side, sign) in [-1.0, 1.0].into_iter().enumerate() {
+            let perturbed = perturb(&elements, j, sign * step);
+            let perturbed = SGP4::from_elements(sgp4.model(), sgp4.operation_mode(), &perturbed)?;
+            for (k, t) in [t0_min, t1_min].into_iter().enumerate() {
+                let state = perturbed.propagate(t).map_err(|failure| failure.error)?;
+                states[k][side] = [
+                    state.position_km[0],
+                    state.position_km[1],
+                    state.position_km[2],
+                    state.velocity_km_s[0],
+                    state.velocity_km_s[1],
+                    state.velocity_km_s[2],
+                ];
+            }
+        }
+        // the relative step of the mean motion, in the same units as the others
+        let step = if j == 5 {
+            step * elements.no_kozai
+        } else {
+            *step
+        };
+        for i in 0..6 {
+            partials0[i][j] = (states[0][1][i] - states[0][0][i]) / (2.0 * step);
+            partials1[i][j] = (states[1][1][i] - states[1][0][i]) / (2.0 * step);
+        }
+    }
+    let inverse = invert(&partials0).ok_or(StmError::Singular)?;
+    Ok(multiply(&partials1, &inverse))
+}
+
+/// Map the covariance of the TEME state at `t0_min` minutes since epoch, e.g. at epoch,
+/// to `t1_min`, as `stm * covariance * stm^T` with the state transition matrix.
+///
+/// The mapping is linear, so it holds while the uncertainty stays small compared to
+/// the orbit; propagated far, in-track uncertainties grow along the orbit rather than
+/// in a straight line.
+pub fn propagate_covariance(
+    sgp4: &SGP4,
+    covariance: &Matrix6,
+    t0_min: f64,
+    t1_min: f64,
+) -> Result<Matrix6, StmError> {
+    let stm = state_transition_matrix(sgp4, t0_min, t1_min)?;
+    Ok(multiply(&multiply(&stm, covariance), &transpose(&stm)))
+}
+
+/// Elements with the nonsingular element `j` (see `STEPS`) changed by `delta`.
+pub(crate) fn perturb(elements: &Elements, j: usize, delta: f64) -> Elements {
+    let mut elements = elements.clone();
+    let (sinw, cosw) = elements.argpo.sin_cos();
+    let mut equinoctial = [
+        elements.ecco * sinw,
+        elements.ecco * cosw,
+        elements.mo + elements.argpo,
+    ];
+    match j {
+        0 => elements.inclo += delta,
+        1 => elements.nodeo += delta,
+        2..=4 => {
+            equinoctial[j - 2] += delta;
+            let [h, k, longitude] = equinoctial;
+            elements.ecco = h.hypot(k);
+            elements.argpo = h.atan2(k);
+            elements.mo = longitude - elements.argpo;
+        }
+        _ => elements.no_kozai *= 1.0 + delta,
+    }
+    elements
+}
+
+fn multiply(a: &Matrix6, b: &Matrix6) -> Matrix6 {
+    let mut product = [[0.0; 6]; 6];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..6).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+fn transpose(a: &Matrix6) -> Matrix6 {
+    let mut transposed = [[0.0; 6]; 6];
+    for (i, row) in a.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            transposed[j][i] = *value;
+        }
+    }
+    transposed
+}
+
+/// Invert a matrix by Gauss-Jordan elimination with partial pivoting, or `None` if it
+/// is singular. Rows are scaled first, as positions and velocities differ by orders of
+/// magnitude.
+fn invert(a: &Matrix6) -> Option<Matrix6> {
+    let mut a = *a;
+    let mut inverse = [[0.0; 6]; 6];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        let scale = a[i].iter().fold(0.0_f64, |max, value| max.max(value.abs()));
+        if scale == 0.0 {
+            return None;
+        }
+        a[i].iter_mut().for_each(|value| *value /= scale);
+        row[i] = 1.0 / scale;
+    }
+    for column in 0..6 {
+        let pivot =
+            (column..6).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < SINGULAR {
+            return None;
+        }
+        a.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let divisor = a[column][column];
+        a[column].iter_mut().for_each(|value| *value /= divisor);
+        inverse[column]
+            .iter_mut()
+            .for_each(|value| *value /= divisor);
+        for row in 0..6 {
+            let factor = a[row][column];
+            if row == column || factor == 0.0 {
+                continue;
+            }
+            for k in 0..6 {
+                a[row][k] -= factor * a[column][k];
+                inverse[row][k] -= factor * inverse[column][k];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::iss;
+    use crate::sgp4::OperationMode;
+    use crate::utils::WGSModel;
+
+    fn state(sgp4: &SGP4, t: f64) -> [f64; 6] {
+        let state = sgp4.propagate(t).unwrap();
+        let (r, v) = (state.position_km, state.velocity_km_s);
+        [r[0], r[1], r[2], v[0], v[1], v[2]]
+    }
+
+    #[test]
+    fn test_state_transition_matrix() {
+        let sgp4 = iss(WGSModel::WGS_72);
+
+        let identity = state_transition_matrix(&sgp4, 30.0, 30.0).unwrap();
+        for (i, row) in identity.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((value - expected).abs() < 1e-6, "{identity:?}");
+            }
+        }
+
+        // a satellite a little off predicts the change of the state
+        let stm = state_transition_matrix(&sgp4, 0.0, 90.0).unwrap();
+        let mut elements = sgp4.elements();
+        elements.mo += 1e-6;
+        elements.nodeo -= 2e-6;
+        elements.no_kozai *= 1.0 + 1e-8;
+        let perturbed =
+            SGP4::from_elements(WGSModel::WGS_72, OperationMode::Improved, &elements).unwrap();
+        let (x0, x1) = (state(&sgp4, 0.0), state(&sgp4, 90.0));
+        let (y0, y1) = (state(&perturbed, 0.0), state(&perturbed, 90.0));
+        for i in 0..6 {
+            let predicted: f64 = (0..6).map(|j| stm[i][j] * (y0[j] - x0[j])).sum();
+            let scale = if i < 3 { 1e-4 } else { 1e-7 };
+            assert!((predicted - (y1[i] - x1[i])).abs() < scale, "{i}");
+        }
+
+        // the in-track uncertainty grows
+        let mut covariance = [[0.0; 6]; 6];
+        for (i, row) in covariance.iter_mut().enumerate() {
+            row[i] = if i < 3 { 1e-2 } else { 1e-8 };
+        }
+        let later = propagate_covariance(&sgp4, &covariance, 0.0, 1440.0).unwrap();
+        let trace: f64 = (0..3).map(|i| later[i][i]).sum();
+        assert!(trace > 0.1);
+        assert!((later[0][3] - later[3][0]).abs() < 1e-9 * trace);
+        assert!((later[1][5] - later[5][1]).abs() < 1e-9 * trace);
+    }
+}
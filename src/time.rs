@@ -0,0 +1,623 @@
+use alloc::format;
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::ops::{Add, Sub};
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::timescale::LeapSeconds;
+use crate::utils::radians;
+
+/// Convert a year and fractional day of year to (month, day, hour, minute, second).
+///
+/// Day 1.0 is midnight at the start of January 1st.
+pub fn days2mdh(year: u64, days: f64) -> (u64, u64, u64, u64, f64) {
+    let feb = if year.is_multiple_of(4) { 29 } else { 28 };
+    let lmonth = [31, feb, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    let dayofyr = days.floor() as u64;
+
+    // find month and day of month
+    let mut i = 1;
+    let mut inttemp = 0;
+    while dayofyr > inttemp + lmonth[i - 1] && i < 12 {
+        inttemp += lmonth[i - 1];
+        i += 1;
+    }
+    let mon = i as u64;
+    let day = dayofyr - inttemp;
+
+    let (hr, minute, sec) = hms(days - dayofyr as f64);
+    (mon, day, hr, minute, sec)
+}
+
+/// Split a fraction of a day into (hour, minute, second).
+fn hms(fraction: f64) -> (u64, u64, f64) {
+    let mut temp = fraction * DAY2HR;
+    let hr = temp.floor();
+    temp = (temp - hr) * 60.0;
+    let minute = temp.floor();
+    let sec = (temp - minute) * MIN2SEC;
+    (hr as u64, minute as u64, sec)
+}
+
+/// Julian date of a calendar date, split into a whole part (ending in .5, i.e.
+/// midnight) and the fraction of the day.
+///
+/// Valid for the years 1900 to 2100.
+pub fn jday(year: u64, mdhms: (u64, u64, u64, u64, f64)) -> (f64, f64) {
+    let (mon, day, hr, minute, sec) = mdhms;
+    let (year, mon) = (year as f64, mon as f64);
+
+    let jd = 367.0 * year - (7.0 * (year + ((mon + 9.0) / 12.0).floor()) * 0.25).floor()
+        + (275.0 * mon / 9.0).floor()
+        + day as f64
+        + 1721013.5;
+    let jdfrac = (sec + minute as f64 * MIN2SEC + hr as f64 * HR2SEC) / DAY2SEC;
+
+    (jd, jdfrac)
+}
+
+/// Parse an ISO 8601 date and time in UTC into the year and the fractional day of the
+/// year (see `days2mdh`).
+///
+/// Both calendar ("YYYY-MM-DDThh:mm:ss[.f]") and ordinal ("YYYY-DDDThh:mm:ss[.f]")
+/// dates are accepted, with an optional "Z" suffix, as in CCSDS epochs. A date without
+/// a time is midnight.
+pub fn parse_iso8601(text: &str) -> Option<(u64, f64)> {
+    let (year, dayofyr, fraction) = parse_iso8601_parts(text)?;
+    Some((year, dayofyr as f64 + fraction))
+}
+
+/// Parse an ISO 8601 date and time into the year, the day of the year and the
+/// fraction of the day, see `parse_iso8601`.
+fn parse_iso8601_parts(text: &str) -> Option<(u64, u64, f64)> {
+    let text = text.trim_end_matches('Z');
+    let (date, time) = text.split_once('T').unwrap_or((text, "00:00:00"));
+    let mut time = time.splitn(3, ':');
+    let hr: u64 = time.next()?.parse().ok()?;
+    let minute: u64 = time.next()?.parse().ok()?;
+    let sec: f64 = time.next()?.parse().ok()?;
+    if hr > 23 || minute > 59 || !(0.0..61.0).contains(&sec) {
+        return None;
+    }
+
+    let mut date = date.split('-');
+    let (year, dayofyr) = match (date.next(), date.next(), date.next(), date.next()) {
+        (Some(year), Some(mon), Some(day), None) => {
+            let year = year.parse().ok()?;
+            let (mon, day): (u64, u64) = (mon.parse().ok()?, day.parse().ok()?);
+            let dayofyr = mdh2days(year, (mon, day, 0, 0, 0.0));
+            // days past the end of the month roll over into the next
+            if !(1..=12).contains(&mon) || day == 0 || days2mdh(year, dayofyr).0 != mon {
+                return None;
+            }
+            (year, dayofyr as u64)
+        }
+        (Some(year), Some(day), None, None) => (year.parse().ok()?, day.parse().ok()?),
+        _ => return None,
+    };
+    let days_in_year = if year.is_multiple_of(4) { 366 } else { 365 };
+    if !(1..=days_in_year).contains(&dayofyr) {
+        return None;
+    }
+    let (_, fraction) = jday(year, (1, 1, hr, minute, sec));
+    Some((year, dayofyr, fraction))
+}
+
+/// Modified Julian date of a Julian date split into a whole part and a fraction.
+pub fn jd_to_mjd(jd: f64, jdfrac: f64) -> f64 {
+    (jd - JD_TO_MJD_OFFSET) + jdfrac
+}
+
+/// Julian date of a Modified Julian date, split at midnight like `jday`.
+pub fn mjd_to_jd(mjd: f64) -> (f64, f64) {
+    let days = mjd.floor();
+    (days + JD_TO_MJD_OFFSET, mjd - days)
+}
+
+/// Inverse of `days2mdh`: the fractional day of the year of a date in `year`.
+pub fn mdh2days(year: u64, mdhms: (u64, u64, u64, u64, f64)) -> f64 {
+    let (mon, day, hr, minute, sec) = mdhms;
+    let (jd, jdfrac) = jday(year, (mon, day, hr, minute, sec));
+    let (jd_jan1, _) = jday(year, (1, 1, 0, 0, 0.0));
+    jd - jd_jan1 + 1.0 + jdfrac
+}
+
+/// Inverse of `jday`: the calendar date (year, (month, day, hour, minute, second)) of a
+/// Julian date split into a whole part and a fraction.
+///
+/// The split doesn't need to be at midnight; it is normalized first so the fraction
+/// keeps its full precision. The time of day is then found from the fraction alone,
+/// to about 10 nanoseconds, or 20 microseconds when the date isn't split.
+pub fn invjday(jd: f64, jdfrac: f64) -> (u64, (u64, u64, u64, u64, f64)) {
+    let (mut jd, mut jdfrac) = (jd, jdfrac);
+
+    // move whole days from the fraction, and the part of a day from the date
+    let days = jdfrac.floor();
+    jd += days;
+    jdfrac -= days;
+    let dt = jd - jd.floor() - 0.5;
+    if dt.abs() > 1e-8 {
+        jd -= dt;
+        jdfrac += dt;
+    }
+    if jdfrac < 0.0 {
+        jd -= 1.0;
+        jdfrac += 1.0;
+    } else if jdfrac >= 1.0 {
+        jd += 1.0;
+        jdfrac -= 1.0;
+    }
+
+    // find the year and day of year
+    let temp = jd - 2415019.5;
+    let tu = temp / YR2DAY;
+    let mut year = 1900 + tu.floor() as u64;
+    let leapyrs = |year: u64| ((year as f64 - 1901.0) * 0.25).floor();
+    let mut days = (temp - ((year as f64 - 1900.0) * 365.0 + leapyrs(year))).floor();
+    if days + jdfrac < 1.0 {
+        year -= 1;
+        days = (temp - ((year as f64 - 1900.0) * 365.0 + leapyrs(year))).floor();
+    }
+
+    let (mon, day, ..) = days2mdh(year, days);
+    let (hr, minute, sec) = hms(jdfrac);
+    (year, (mon, day, hr, minute, sec))
+}
+
+/// Greenwich mean sidereal time in radians, in [0, 2pi), from the Julian date in UT1.
+///
+/// Uses the full IAU-82 polynomial, as `sgp4init` does for the sidereal time at epoch in
+/// the improved (non-AFSPC) mode, and as the earth fixed conversions do.
+pub fn gstime(jdut1: f64) -> f64 {
+    let tut1 = (jdut1 - J2000) / CENT2DAY;
+    let temp = -6.2e-6 * tut1 * tut1 * tut1
+        + 0.093104 * tut1 * tut1
+        + (876600.0 * HR2SEC + 8640184.812866) * tut1
+        + 67310.54841;
+    let gst = (radians(temp) / 240.0) % TWOPI;
+    if gst < 0.0 { gst + TWOPI } else { gst }
+}
+
+/// Greenwich mean sidereal time in radians, in [0, 2pi), from the Julian date in UT1,
+/// using the series from 1970 of the AFSPC implementation of SGP4.
+///
+/// This is the form `sgp4init` uses in AFSPC mode. It agrees with `gstime` to about
+/// 1e-8 radians over the TLE era.
+pub fn gstime_afspc(jdut1: f64) -> f64 {
+    let ts70 = jdut1 - JD_EPOCH_1950 - 7305.0;
+    let ds70 = (ts70 + 1.0e-8).floor();
+    let tfrac = ts70 - ds70;
+    let c1 = 1.7202791694070362e-2;
+    let thgr70 = 1.7321343856509374;
+    let fk5r = 5.075514194322695e-15;
+    let c1p2p = c1 + TWOPI;
+    let gst = (thgr70 + c1 * ds70 + c1p2p * tfrac + ts70 * ts70 * fk5r) % TWOPI;
+    if gst < 0.0 { gst + TWOPI } else { gst }
+}
+
+/// A Julian date in UTC kept as the date and the fraction of the day, so the fraction
+/// doesn't lose precision to the size of the date, and neither part can be forgotten.
+///
+/// Whole days in the fraction are moved to the date, leaving the fraction in [0, 1).
+/// Arithmetic with `f64` is in days.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Epoch {
+    jd: f64,
+    fraction: f64,
+}
+
+impl Epoch {
+    pub fn new(jd: f64, fraction: f64) -> Epoch {
+        let days = fraction.floor();
+        Epoch {
+            jd: jd + days,
+            fraction: fraction - days,
+        }
+    }
+
+    /// Epoch of a calendar date (year, (month, day, hour, minute, second)), see `jday`.
+    pub fn from_calendar(year: u64, mdhms: (u64, u64, u64, u64, f64)) -> Epoch {
+        let (jd, fraction) = jday(year, mdhms);
+        Epoch::new(jd, fraction)
+    }
+
+    /// Epoch of a Modified Julian date in UTC, see `mjd_to_jd`.
+    pub fn from_mjd(mjd: f64) -> Epoch {
+        let (jd, fraction) = mjd_to_jd(mjd);
+        Epoch::new(jd, fraction)
+    }
+
+    /// Modified Julian date as one number, with a precision of about a microsecond in
+    /// this century.
+    pub fn to_mjd(&self) -> f64 {
+        jd_to_mjd(self.jd, self.fraction)
+    }
+
+    /// Epoch of a GPS week and time of week in seconds, see `LeapSeconds::gps_to_utc`.
+    pub fn from_gps(week: u64, seconds: f64, leap_seconds: &LeapSeconds) -> Epoch {
+        let (jd, fraction) = leap_seconds.gps_to_utc(week, seconds);
+        Epoch::new(jd, fraction)
+    }
+
+    /// GPS week and time of week in seconds, see `LeapSeconds::utc_to_gps`.
+    pub fn to_gps(&self, leap_seconds: &LeapSeconds) -> (u64, f64) {
+        leap_seconds.utc_to_gps(self.jd, self.fraction)
+    }
+
+    /// Calendar date (year, (month, day, hour, minute, second)), see `invjday`.
+    pub fn to_calendar(&self) -> (u64, (u64, u64, u64, u64, f64)) {
+        invjday(self.jd, self.fraction)
+    }
+
+    /// Date part of the Julian date, usually at midnight (ending in .5)
+    pub fn jd(&self) -> f64 {
+        self.jd
+    }
+
+    /// Fraction of the day, in [0, 1)
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// Julian date as one number, with a precision of about 20 microseconds.
+    pub fn julian_date(&self) -> f64 {
+        self.jd + self.fraction
+    }
+
+    /// Minutes from `other` to this epoch.
+    pub fn minutes_since(&self, other: Epoch) -> f64 {
+        (*self - other) * DAY2MIN
+    }
+
+    /// Epoch of an ISO 8601 date and time in UTC, e.g. "2024-03-01T12:34:56.789Z", see
+    /// `parse_iso8601`. Returns `None` if the text isn't a valid date and time.
+    pub fn from_iso8601(text: &str) -> Option<Epoch> {
+        let (year, dayofyr, fraction) = parse_iso8601_parts(text)?;
+        let (jd_jan1, _) = jday(year, (1, 1, 0, 0, 0.0));
+        Some(Epoch::new(jd_jan1 + (dayofyr - 1) as f64, fraction))
+    }
+
+    /// ISO 8601 date and time in UTC to the millisecond, e.g. "2024-01-01T12:00:00.000Z".
+    pub fn to_iso8601(&self) -> String {
+        // round the time since midnight to the millisecond before splitting it, so the
+        // seconds never round up to 60
+        let midnight = (self.jd - 0.5).floor() + 0.5;
+        let ms = (((self.jd - midnight) + self.fraction) * DAY2SEC * 1e3).round() as u64;
+        let ms_per_day = DAY2SEC as u64 * 1000;
+        let (year, (month, day, ..)) = invjday(midnight + (ms / ms_per_day) as f64, 0.0);
+        let ms = ms % ms_per_day;
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            ms / 3_600_000,
+            ms / 60_000 % 60,
+            ms / 1000 % 60,
+            ms % 1000
+        )
+    }
+}
+
+impl Add<f64> for Epoch {
+    type Output = Epoch;
+
+    fn add(self, days: f64) -> Epoch {
+        Epoch::new(self.jd, self.fraction + days)
+    }
+}
+
+impl Sub<f64> for Epoch {
+    type Output = Epoch;
+
+    fn sub(self, days: f64) -> Epoch {
+        Epoch::new(self.jd, self.fraction - days)
+    }
+}
+
+impl Sub for Epoch {
+    type Output = f64;
+
+    /// Days from `other` to `self`
+    fn sub(self, other: Epoch) -> f64 {
+        (self.jd - other.jd) + (self.fraction - other.fraction)
+    }
+}
+
+impl PartialEq for Epoch {
+    fn eq(&self, other: &Epoch) -> bool {
+        *self - *other == 0.0
+    }
+}
+
+impl PartialOrd for Epoch {
+    fn partial_cmp(&self, other: &Epoch) -> Option<Ordering> {
+        (*self - *other).partial_cmp(&0.0)
+    }
+}
+
+/// A point in time that can be converted to a Julian date in UTC, split into the date
+/// at midnight and the day fraction like `jday`.
+///
+/// Implemented for split Julian dates `(jd, jdfrac)`, and for `chrono::DateTime<Utc>`
+/// and `hifitime::Epoch` with the `chrono` and `hifitime` features.
+pub trait ToJday {
+    fn to_jday(&self) -> (f64, f64);
+}
+
+impl ToJday for (f64, f64) {
+    fn to_jday(&self) -> (f64, f64) {
+        *self
+    }
+}
+
+impl ToJday for Epoch {
+    fn to_jday(&self) -> (f64, f64) {
+        (self.jd, self.fraction)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToJday for chrono::DateTime<chrono::Utc> {
+    fn to_jday(&self) -> (f64, f64) {
+        datetime_to_jday(self)
+    }
+}
+
+#[cfg(feature = "hifitime")]
+impl ToJday for hifitime::Epoch {
+    fn to_jday(&self) -> (f64, f64) {
+        hifitime_to_jday(self)
+    }
+}
+
+/// Julian date of a UTC date and time, split into the date at midnight and the day
+/// fraction like `jday`.
+#[cfg(feature = "chrono")]
+pub fn datetime_to_jday(datetime: &chrono::DateTime<chrono::Utc>) -> (f64, f64) {
+    let timestamp = datetime.timestamp();
+    let days = timestamp.div_euclid(DAY2SEC as i64);
+    let sec = timestamp.rem_euclid(DAY2SEC as i64) as f64
+        + f64::from(datetime.timestamp_subsec_nanos()) * 1e-9;
+    (JD_UNIX_EPOCH + days as f64, sec / DAY2SEC)
+}
+
+/// UTC date and time of a Julian date split into a whole part and a fraction, rounded
+/// to the nanosecond, or `None` beyond the range of `chrono::DateTime` or for a
+/// non-finite date.
+#[cfg(feature = "chrono")]
+pub fn jday_to_datetime(jd: f64, jdfrac: f64) -> Option<chrono::DateTime<chrono::Utc>> {
+    if !(jd + jdfrac).is_finite() {
+        return None;
+    }
+    let days = (jd - JD_UNIX_EPOCH).floor();
+    let sec = (jd - JD_UNIX_EPOCH - days + jdfrac) * DAY2SEC;
+    let whole = sec.floor();
+    let nanos = ((sec - whole) * 1e9).round() as i64;
+    let secs = (days as i64)
+        .checked_mul(DAY2SEC as i64)?
+        .checked_add(whole as i64)?;
+    chrono::DateTime::from_timestamp(secs, 0)?
+        .checked_add_signed(chrono::Duration::nanoseconds(nanos))
+}
+
+/// Julian date in UTC of a hifitime epoch in any time scale, split into the date at
+/// midnight and the day fraction like `jday`. Leap seconds are accounted for by hifitime.
+#[cfg(feature = "hifitime")]
+pub fn hifitime_to_jday(epoch: &hifitime::Epoch) -> (f64, f64) {
+    let (year, mon, day, hr, minute, sec, nanos) = epoch.to_gregorian_utc();
+    jday(
+        year as u64,
+        (
+            mon.into(),
+            day.into(),
+            hr.into(),
+            minute.into(),
+            f64::from(sec) + f64::from(nanos) * 1e-9,
+        ),
+    )
+}
+
+/// hifitime epoch in UTC of a Julian date split into a whole part and a fraction.
+#[cfg(feature = "hifitime")]
+pub fn jday_to_hifitime(jd: f64, jdfrac: f64) -> hifitime::Epoch {
+    hifitime::Epoch::from_jde_utc(jd) + hifitime::Unit::Day * jdfrac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::degrees;
+
+    #[test]
+    fn test_days2mdh() {
+        let (mon, day, hr, minute, sec) = days2mdh(2000, 179.78495062);
+        assert_eq!((mon, day, hr, minute), (6, 27, 18, 50));
+        assert!((sec - 19.733568).abs() < 1e-4);
+
+        assert_eq!(days2mdh(2024, 60.0).0, 2);
+        assert_eq!(days2mdh(2023, 60.0).0, 3);
+    }
+
+    #[test]
+    fn test_jday() {
+        let (jd, jdfrac) = jday(2000, (1, 1, 12, 0, 0.0));
+        assert_eq!(jd + jdfrac, J2000);
+        assert_eq!(jday(2000, (1, 1, 0, 0, 0.0)), (J2000_UTC, 0.0));
+    }
+
+    #[test]
+    fn test_mdh2days() {
+        assert!((mdh2days(2000, (6, 27, 18, 50, 19.733568)) - 179.78495062).abs() < 1e-10);
+        assert_eq!(mdh2days(2024, (3, 1, 12, 0, 0.0)), 61.5);
+        assert_eq!(mdh2days(2023, (3, 1, 12, 0, 0.0)), 60.5);
+    }
+
+    #[test]
+    fn test_epoch() {
+        let epoch = Epoch::from_calendar(2000, (1, 1, 12, 0, 0.0));
+        assert_eq!((epoch.jd(), epoch.fraction()), (J2000_UTC, 0.5));
+        assert_eq!(epoch.julian_date(), J2000);
+
+        let later = epoch + 1.75;
+        assert_eq!((later.jd(), later.fraction()), (J2000_UTC + 2.0, 0.25));
+        assert_eq!(later - epoch, 1.75);
+        assert_eq!(later.minutes_since(epoch), 1.75 * DAY2MIN);
+        assert_eq!(later - 1.75, epoch);
+        assert!(later > epoch);
+        assert_eq!(Epoch::new(J2000, 0.0), epoch);
+
+        let (year, (mon, day, hr, minute, _)) = later.to_calendar();
+        assert_eq!((year, mon, day, hr, minute), (2000, 1, 3, 6, 0));
+    }
+
+    #[test]
+    fn test_mjd() {
+        assert_eq!(jd_to_mjd(J2000_UTC, 0.5), 51544.5);
+        assert_eq!(mjd_to_jd(51544.5), (J2000_UTC, 0.5));
+        assert_eq!(mjd_to_jd(-0.25), (JD_TO_MJD_OFFSET - 1.0, 0.75));
+
+        let epoch = Epoch::from_calendar(2024, (1, 1, 12, 0, 0.0));
+        assert_eq!(epoch.to_mjd(), 60310.5);
+        assert_eq!(Epoch::from_mjd(60310.5), epoch);
+        assert_eq!(Epoch::from_mjd(60310.5).jd(), epoch.jd());
+    }
+
+    #[test]
+    fn test_gps() {
+        let leap_seconds = LeapSeconds::default();
+        let epoch = Epoch::from_calendar(2024, (1, 1, 12, 0, 0.0));
+        let (week, seconds) = epoch.to_gps(&leap_seconds);
+        assert_eq!(week, 2295);
+        assert!((seconds - (1.5 * DAY2SEC + 18.0)).abs() < 1e-6);
+        let round_trip = Epoch::from_gps(week, seconds, &leap_seconds);
+        assert!((round_trip - epoch).abs() * DAY2SEC < 1e-6);
+    }
+
+    #[test]
+    fn test_to_iso8601() {
+        let epoch = Epoch::new(2451722.5, 0.78495062);
+        assert_eq!(epoch.to_iso8601(), "2000-06-27T18:50:19.734Z");
+        assert_eq!(
+            Epoch::new(J2000, 0.0).to_iso8601(),
+            "2000-01-01T12:00:00.000Z"
+        );
+        // a millisecond before midnight rounds to the next day
+        let late = Epoch::new(J2000_UTC, 1.0 - 1e-9);
+        assert_eq!(late.to_iso8601(), "2000-01-02T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_parse_iso8601() {
+        let (year, days) = parse_iso8601("2000-06-27T18:50:19.733568").unwrap();
+        assert_eq!(year, 2000);
+        assert!((days - 179.78495062).abs() < 1e-10);
+        assert_eq!(parse_iso8601("2024-001T12:00:00"), Some((2024, 1.5)));
+        assert_eq!(parse_iso8601("2024-03-01"), Some((2024, 61.0)));
+        for invalid in [
+            "2024-13-01T00:00:00",
+            "2023-02-29T00:00:00",
+            "2023-366T00:00:00",
+            "2024-03-01T24:00:00",
+            "2024-03-01T12:00",
+            "2024-03-01-12",
+            "March 1st",
+        ] {
+            assert_eq!(parse_iso8601(invalid), None, "{invalid}");
+        }
+
+        let epoch = Epoch::from_iso8601("2024-03-01T12:34:56.789Z").unwrap();
+        assert_eq!(epoch, Epoch::from_calendar(2024, (3, 1, 12, 34, 56.789)));
+        assert_eq!(epoch.to_iso8601(), "2024-03-01T12:34:56.789Z");
+        let epoch = Epoch::new(2451722.5, 0.78495062);
+        assert_eq!(
+            Epoch::from_iso8601(&epoch.to_iso8601())
+                .unwrap()
+                .to_iso8601(),
+            epoch.to_iso8601()
+        );
+    }
+
+    #[test]
+    fn test_invjday() {
+        let (year, (mon, day, hr, minute, sec)) = invjday(J2000_UTC, 0.5);
+        assert_eq!((year, mon, day, hr, minute), (2000, 1, 1, 12, 0));
+        assert!(sec.abs() < 1e-6);
+
+        // round trip, with the fraction in the whole part
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        for (jd, jdfrac) in [(jd, jdfrac), (jd + jdfrac, 0.0), (jd - 3.0, jdfrac + 3.0)] {
+            let (year, (mon, day, hr, minute, sec)) = invjday(jd, jdfrac);
+            assert_eq!((year, mon, day, hr, minute), (2004, 4, 6, 7, 51));
+            assert!((sec - 28.386009).abs() < 1e-5);
+        }
+        let (_, (.., sec)) = invjday(jd, jdfrac);
+        assert!((sec - 28.386009).abs() < 1e-8);
+
+        // a tenth of a millisecond before midnight is still the same day
+        let (_, (mon, day, hr, minute, sec)) = invjday(jd, 1.0 - 1e-4 / DAY2SEC);
+        assert_eq!((mon, day, hr, minute), (4, 6, 23, 59));
+        assert!((sec - 59.9999).abs() < 1e-8);
+
+        // last second of a leap year
+        let (jd, jdfrac) = jday(2024, (12, 31, 23, 59, 59.5));
+        let (year, (mon, day, hr, minute, sec)) = invjday(jd, jdfrac);
+        assert_eq!((year, mon, day, hr, minute), (2024, 12, 31, 23, 59));
+        assert!((sec - 59.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gstime() {
+        // Vallado, example 3-5
+        let (jd, jdfrac) = jday(1992, (8, 20, 12, 14, 0.0));
+        let gst = gstime(jd + jdfrac);
+        assert!((degrees(gst) - 152.578787810).abs() < 1e-6);
+        // within the 1e-8 radians of the doc over the TLE era
+        for year in (1957..=2056).step_by(3) {
+            let (jd, jdfrac) = jday(year, (3, 1, 6, 0, 0.0));
+            let jdut1 = jd + jdfrac;
+            let difference = (gstime_afspc(jdut1) - gstime(jdut1)).abs();
+            assert!(difference.min(TWOPI - difference) < 1e-8, "{year}");
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_datetime_jday() {
+        use chrono::{TimeZone, Utc};
+
+        let datetime = Utc.with_ymd_and_hms(2004, 4, 6, 7, 51, 28).unwrap()
+            + chrono::Duration::microseconds(386009);
+        let (jd, jdfrac) = datetime_to_jday(&datetime);
+        let (jd_expected, jdfrac_expected) = jday(2004, (4, 6, 7, 51, 28.386009));
+        assert_eq!(jd, jd_expected);
+        assert!((jdfrac - jdfrac_expected).abs() < 1e-15);
+
+        let round_trip = jday_to_datetime(jd, jdfrac).unwrap();
+        assert!((round_trip - datetime).num_nanoseconds().unwrap().abs() < 1000);
+        assert_eq!(
+            jday_to_datetime(J2000, 0.0),
+            Some(Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0).unwrap())
+        );
+        assert_eq!(jday_to_datetime(1e15, 0.0), None);
+        assert_eq!(jday_to_datetime(f64::NAN, 0.0), None);
+    }
+
+    #[cfg(feature = "hifitime")]
+    #[test]
+    fn test_hifitime_jday() {
+        use hifitime::{Epoch, TimeScale, Unit};
+
+        let epoch = Epoch::from_gregorian_utc(2004, 4, 6, 7, 51, 28, 386_009_000);
+        let (jd, jdfrac) = hifitime_to_jday(&epoch);
+        let (jd_expected, jdfrac_expected) = jday(2004, (4, 6, 7, 51, 28.386009));
+        assert_eq!(jd, jd_expected);
+        assert!((jdfrac - jdfrac_expected).abs() < 1e-15);
+        assert!((jday_to_hifitime(jd, jdfrac) - epoch).abs() < Unit::Microsecond * 1);
+
+        // the same instant in TT is 64.184 seconds ahead of UTC in 2004
+        let tt = epoch.to_time_scale(TimeScale::TT);
+        assert_eq!(tt.to_jday(), (jd, jdfrac));
+    }
+}
@@ -0,0 +1,269 @@
+use alloc::vec::Vec;
+
+use crate::constants::*;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::jd_to_mjd;
+use crate::utils::radians;
+
+/// TT - TAI in seconds, fixed by definition
+pub const TT_MINUS_TAI: f64 = 32.184;
+
+/// TAI - GPS time in seconds, fixed since the GPS epoch
+pub const TAI_MINUS_GPS: f64 = 19.0;
+
+/// Julian date of the GPS epoch, 1980 January 6 0h, when GPS time and UTC agreed
+pub const JD_GPS_EPOCH: f64 = 2444244.5;
+
+/// Seconds in a GPS week
+const WEEK2SEC: f64 = 7.0 * DAY2SEC;
+
+/// Leap seconds introduced since 1972, as (Modified Julian date in UTC from which the
+/// offset applies, TAI - UTC in seconds).
+///
+/// From the IERS Bulletin C; no leap second has been announced after 2017 January 1.
+const LEAP_SECONDS: [(f64, f64); 28] = [
+    (41317.0, 10.0), // 1972 Jan 1
+    (41499.0, 11.0), // 1972 Jul 1
+    (41683.0, 12.0), // 1973 Jan 1
+    (42048.0, 13.0), // 1974 Jan 1
+    (42413.0, 14.0), // 1975 Jan 1
+    (42778.0, 15.0), // 1976 Jan 1
+    (43144.0, 16.0), // 1977 Jan 1
+    (43509.0, 17.0), // 1978 Jan 1
+    (43874.0, 18.0), // 1979 Jan 1
+    (44239.0, 19.0), // 1980 Jan 1
+    (44786.0, 20.0), // 1981 Jul 1
+    (45151.0, 21.0), // 1982 Jul 1
+    (45516.0, 22.0), // 1983 Jul 1
+    (46247.0, 23.0), // 1985 Jul 1
+    (47161.0, 24.0), // 1988 Jan 1
+    (47892.0, 25.0), // 1990 Jan 1
+    (48257.0, 26.0), // 1991 Jan 1
+    (48804.0, 27.0), // 1992 Jul 1
+    (49169.0, 28.0), // 1993 Jul 1
+    (49534.0, 29.0), // 1994 Jul 1
+    (50083.0, 30.0), // 1996 Jan 1
+    (50630.0, 31.0), // 1997 Jul 1
+    (51179.0, 32.0), // 1999 Jan 1
+    (53736.0, 33.0), // 2006 Jan 1
+    (54832.0, 34.0), // 2009 Jan 1
+    (56109.0, 35.0), // 2012 Jul 1
+    (57204.0, 36.0), // 2015 Jul 1
+    (57754.0, 37.0), // 2017 Jan 1
+];
+
+/// Table of leap seconds, to convert between UTC and TAI.
+///
+/// `LeapSeconds::default()` holds the leap seconds up to the one of 2017 January 1.
+/// Leap seconds announced later in the IERS Bulletin C can be added with `insert`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeapSeconds {
+    /// (Modified Julian date in UTC, TAI - UTC in seconds), sorted by date
+    entries: Vec<(f64, f64)>,
+}
+
+impl Default for LeapSeconds {
+    fn default() -> Self {
+        LeapSeconds {
+            entries: LEAP_SECONDS.to_vec(),
+        }
+    }
+}
+
+impl LeapSeconds {
+    /// Add a leap second, or change an existing one, given the Modified Julian date in
+    /// UTC from which it applies and the new value of TAI - UTC in seconds.
+    pub fn insert(&mut self, mjd: f64, tai_minus_utc: f64) {
+        match self.entries.binary_search_by(|(d, _)| d.total_cmp(&mjd)) {
+            Ok(i) => self.entries[i].1 = tai_minus_utc,
+            Err(i) => self.entries.insert(i, (mjd, tai_minus_utc)),
+        }
+    }
+
+    /// TAI - UTC in seconds at a Julian date in UTC split into a whole part and a
+    /// fraction.
+    ///
+    /// Before 1972 the offset of 1972 January 1 (10 s) is returned; the rubber seconds
+    /// of the earlier UTC are not modelled.
+    pub fn tai_minus_utc(&self, jd: f64, jdfrac: f64) -> f64 {
+        let mjd = jd_to_mjd(jd, jdfrac);
+        self.entries
+            .iter()
+            .rev()
+            .find(|(d, _)| mjd >= *d)
+            .or(self.entries.first())
+            .map_or(0.0, |(_, offset)| *offset)
+    }
+
+    /// Convert a split Julian date from UTC to TAI.
+    pub fn utc_to_tai(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        (jd, jdfrac + self.tai_minus_utc(jd, jdfrac) / DAY2SEC)
+    }
+
+    /// Convert a split Julian date from TAI to UTC.
+    pub fn tai_to_utc(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        // the offset is tabulated in UTC, so look it up again at the first estimate
+        let estimate = jdfrac - self.tai_minus_utc(jd, jdfrac) / DAY2SEC;
+        (jd, jdfrac - self.tai_minus_utc(jd, estimate) / DAY2SEC)
+    }
+
+    /// Convert a split Julian date from UTC to TT, e.g. for the precession and
+    /// nutation of `frames::PrecessionNutation`.
+    pub fn utc_to_tt(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        let (jd, jdfrac) = self.utc_to_tai(jd, jdfrac);
+        tai_to_tt(jd, jdfrac)
+    }
+
+    /// Convert a split Julian date from TT to UTC.
+    pub fn tt_to_utc(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        let (jd, jdfrac) = tt_to_tai(jd, jdfrac);
+        self.tai_to_utc(jd, jdfrac)
+    }
+
+    /// GPS week and time of week in seconds of a split Julian date in UTC.
+    ///
+    /// The week is counted from the GPS epoch without the rollovers of the 10 bit
+    /// week number broadcast by the satellites.
+    pub fn utc_to_gps(&self, jd: f64, jdfrac: f64) -> (u64, f64) {
+        let offset = (self.tai_minus_utc(jd, jdfrac) - TAI_MINUS_GPS) / DAY2SEC;
+        let seconds = ((jd - JD_GPS_EPOCH) + (jdfrac + offset)) * DAY2SEC;
+        let week = (seconds / WEEK2SEC).floor();
+        (week as u64, seconds - week * WEEK2SEC)
+    }
+
+    /// Split Julian date in UTC of a GPS week and time of week in seconds.
+    pub fn gps_to_utc(&self, week: u64, seconds: f64) -> (f64, f64) {
+        let jd = JD_GPS_EPOCH + 7.0 * week as f64;
+        self.tai_to_utc(jd, (seconds + TAI_MINUS_GPS) / DAY2SEC)
+    }
+
+    /// Convert a split Julian date from UTC to TDB, e.g. for the ephemerides of the
+    /// sun and moon.
+    pub fn utc_to_tdb(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        let (jd, jdfrac) = self.utc_to_tt(jd, jdfrac);
+        tt_to_tdb(jd, jdfrac)
+    }
+
+    /// Convert a split Julian date from TDB to UTC.
+    pub fn tdb_to_utc(&self, jd: f64, jdfrac: f64) -> (f64, f64) {
+        let (jd, jdfrac) = tdb_to_tt(jd, jdfrac);
+        self.tt_to_utc(jd, jdfrac)
+    }
+}
+
+/// Convert a split Julian date from TAI to TT.
+pub fn tai_to_tt(jd: f64, jdfrac: f64) -> (f64, f64) {
+    (jd, jdfrac + TT_MINUS_TAI / DAY2SEC)
+}
+
+/// Convert a split Julian date from TT to TAI.
+pub fn tt_to_tai(jd: f64, jdfrac: f64) -> (f64, f64) {
+    (jd, jdfrac - TT_MINUS_TAI / DAY2SEC)
+}
+
+/// TDB - TT in seconds at a Julian date in TT or TDB, from the two largest periodic
+/// terms (Astronomical Almanac), accurate to about 30 microseconds.
+pub fn tdb_minus_tt(jd: f64, jdfrac: f64) -> f64 {
+    // mean anomaly of the earth
+    let g = radians(357.53 + 0.98560028 * ((jd - J2000) + jdfrac));
+    0.001657 * g.sin() + 0.000014 * (2.0 * g).sin()
+}
+
+/// Convert a split Julian date from TT to TDB.
+pub fn tt_to_tdb(jd: f64, jdfrac: f64) -> (f64, f64) {
+    (jd, jdfrac + tdb_minus_tt(jd, jdfrac) / DAY2SEC)
+}
+
+/// Convert a split Julian date from TDB to TT.
+pub fn tdb_to_tt(jd: f64, jdfrac: f64) -> (f64, f64) {
+    (jd, jdfrac - tdb_minus_tt(jd, jdfrac) / DAY2SEC)
+}
+
+/// Convert a split Julian date from UTC to UT1, given ΔUT1 = UT1 - UTC in seconds from
+/// the IERS Bulletin A.
+pub fn utc_to_ut1(jd: f64, jdfrac: f64, dut1: f64) -> (f64, f64) {
+    (jd, jdfrac + dut1 / DAY2SEC)
+}
+
+/// Convert a split Julian date from UT1 to UTC, given ΔUT1 = UT1 - UTC in seconds.
+pub fn ut1_to_utc(jd: f64, jdfrac: f64, dut1: f64) -> (f64, f64) {
+    (jd, jdfrac - dut1 / DAY2SEC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::jday;
+
+    #[test]
+    fn test_tai_minus_utc() {
+        let leap_seconds = LeapSeconds::default();
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        assert_eq!(leap_seconds.tai_minus_utc(jd, jdfrac), 32.0);
+
+        let (jd, _) = jday(2017, (1, 1, 0, 0, 0.0));
+        assert_eq!(leap_seconds.tai_minus_utc(jd, 0.0), 37.0);
+        assert_eq!(leap_seconds.tai_minus_utc(jd, -1e-9), 36.0);
+        assert_eq!(leap_seconds.tai_minus_utc(jd - 20000.0, 0.0), 10.0);
+
+        let mut leap_seconds = leap_seconds;
+        leap_seconds.insert(62502.0, 38.0);
+        assert_eq!(leap_seconds.tai_minus_utc(jd + 10000.0, 0.0), 38.0);
+        assert_eq!(leap_seconds.tai_minus_utc(jd, 0.0), 37.0);
+    }
+
+    #[test]
+    fn test_utc_tai_ut1() {
+        let leap_seconds = LeapSeconds::default();
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        let (jd_tai, jdfrac_tai) = leap_seconds.utc_to_tai(jd, jdfrac);
+        assert_eq!(jd_tai, jd);
+        assert!(((jdfrac_tai - jdfrac) * DAY2SEC - 32.0).abs() < 1e-6);
+        let (jd_utc, jdfrac_utc) = leap_seconds.tai_to_utc(jd_tai, jdfrac_tai);
+        assert_eq!(jd_utc, jd);
+        assert!((jdfrac_utc - jdfrac).abs() < 1e-15);
+
+        // Vallado example 3-14: ΔUT1 = -0.4399619 s
+        let (_, jdfrac_ut1) = utc_to_ut1(jd, jdfrac, -0.4399619);
+        assert!(((jdfrac_ut1 - jdfrac) * DAY2SEC + 0.4399619).abs() < 1e-6);
+        let (_, jdfrac_utc) = ut1_to_utc(jd, jdfrac_ut1, -0.4399619);
+        assert!((jdfrac_utc - jdfrac).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_gps() {
+        let leap_seconds = LeapSeconds::default();
+        assert_eq!(leap_seconds.utc_to_gps(JD_GPS_EPOCH, 0.0), (0, 0.0));
+
+        // GPS week 2295 started on Sunday 2023 December 31, and GPS time was 18 seconds
+        // ahead of UTC
+        let (jd, jdfrac) = jday(2024, (1, 1, 0, 0, 0.0));
+        let (week, seconds) = leap_seconds.utc_to_gps(jd, jdfrac);
+        assert_eq!(week, 2295);
+        assert!((seconds - (DAY2SEC + 18.0)).abs() < 1e-6);
+
+        let (jd_utc, jdfrac_utc) = leap_seconds.gps_to_utc(week, seconds);
+        assert!(((jd_utc - jd) + (jdfrac_utc - jdfrac)).abs() * DAY2SEC < 1e-6);
+    }
+
+    #[test]
+    fn test_tt_tdb() {
+        // Vallado example 3-14: TT is 07:52:32.570009
+        let leap_seconds = LeapSeconds::default();
+        let (jd, jdfrac) = jday(2004, (4, 6, 7, 51, 28.386009));
+        let (jd_tt, jdfrac_tt) = leap_seconds.utc_to_tt(jd, jdfrac);
+        let (jd_expected, jdfrac_expected) = jday(2004, (4, 6, 7, 52, 32.570009));
+        assert_eq!(jd_tt, jd_expected);
+        assert!((jdfrac_tt - jdfrac_expected).abs() * DAY2SEC < 1e-6);
+        let (_, jdfrac_utc) = leap_seconds.tt_to_utc(jd_tt, jdfrac_tt);
+        assert!((jdfrac_utc - jdfrac).abs() < 1e-15);
+
+        // TDB is ahead by about 1.65 ms in April, with the earth past perihelion
+        let (_, jdfrac_tdb) = leap_seconds.utc_to_tdb(jd, jdfrac);
+        let tdb_minus_tt = (jdfrac_tdb - jdfrac_tt) * DAY2SEC;
+        assert!((tdb_minus_tt - 0.00165).abs() < 1e-5, "{tdb_minus_tt}");
+        let (_, jdfrac_utc) = leap_seconds.tdb_to_utc(jd, jdfrac_tdb);
+        assert!((jdfrac_utc - jdfrac).abs() * DAY2SEC < 1e-9);
+    }
+}
@@ -0,0 +1,1122 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use core::str::FromStr;
+
+use crate::constants::*;
+use crate::elements::Elements;
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::{Epoch, days2mdh, jday};
+use crate::utils::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Classification {
+    #[default]
+    Unclassified,
+    Classified,
+}
+
+impl Classification {
+    pub(crate) fn from(c: char) -> Option<Classification> {
+        match c {
+            'U' => Some(Classification::Unclassified),
+            'C' => Some(Classification::Classified),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Classification::Unclassified => 'U',
+            Classification::Classified => 'C',
+        }
+    }
+}
+
+/// Errors encountered while parsing a two line element set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TleParseError {
+    /// The line is shorter than the 69 columns of a TLE line
+    LineTooShort { line: usize, length: usize },
+    /// The line does not start with its line number
+    InvalidLineNumber { line: usize },
+    /// A field does not hold a valid value
+    InvalidField {
+        line: usize,
+        field: &'static str,
+        text: String,
+    },
+    /// A character that isn't printable ASCII, e.g. a tab, in columns 1-69
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// The checksum in column 69 does not match the line contents
+    ChecksumMismatch {
+        line: usize,
+        expected: u32,
+        found: char,
+    },
+}
+
+impl fmt::Display for TleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TleParseError::LineTooShort { line, length } => write!(
+                f,
+                "line {line} is too short ({length} characters, expected 69)"
+            ),
+            TleParseError::InvalidLineNumber { line } => {
+                write!(f, "line {line} does not start with '{line}'")
+            }
+            TleParseError::InvalidField { line, field, text } => {
+                write!(f, "invalid {field} on line {line}: {text:?}")
+            }
+            TleParseError::InvalidCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "invalid character {found:?} in column {column} of line {line}"
+            ),
+            TleParseError::ChecksumMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "checksum mismatch on line {line}: expected {expected}, found {found:?}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TleParseError {}
+
+/// Errors of formatting elements as TLE lines, for values the fixed columns can't hold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TleFormatError {
+    /// The satellite number is above 339999, the largest alpha-5 number
+    SatnumOutOfRange(u64),
+    /// A value is NaN or infinite, or doesn't fit in the columns of its field, e.g. an
+    /// eccentricity of 1 or more or an exponent of more than one digit
+    InvalidValue { field: &'static str, value: f64 },
+}
+
+impl fmt::Display for TleFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TleFormatError::SatnumOutOfRange(satnum) => {
+                write!(f, "satellite number {satnum} does not fit in alpha-5")
+            }
+            TleFormatError::InvalidValue { field, value } => {
+                write!(f, "{field} {value} does not fit in a TLE")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TleFormatError {}
+
+/// Two digit epoch years below this are in the 2000s, as in the reference implementation
+pub const DEFAULT_YEAR_PIVOT: u64 = 57;
+
+/// How TLE checksums (column 69) are verified while parsing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumMode {
+    /// Reject lines with a wrong checksum
+    Strict,
+    /// Accept lines with a wrong checksum, keeping the mismatch in `TLE::warnings`
+    #[default]
+    Warn,
+    /// Don't verify checksums
+    Ignore,
+}
+
+/// Options of `TLE::parse_with_options`.
+///
+/// By default the lines must follow the column layout of the format, with its zero
+/// padding and without '+' signs outside of exponents. Lenient parsing accepts the
+/// common deviations of hand edited and converted element sets instead: spaces in
+/// place of leading zeros, '+' signs, tabs typed in place of a space, and implied
+/// decimal points written out (e.g. an eccentricity of ".185967").
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseOptions {
+    pub checksum_mode: ChecksumMode,
+    /// Accept the deviations from the column layout listed above
+    pub lenient: bool,
+}
+
+impl ParseOptions {
+    /// Reject wrong checksums and any deviation from the column layout.
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            checksum_mode: ChecksumMode::Strict,
+            lenient: false,
+        }
+    }
+
+    /// Accept common deviations from the column layout, warning about wrong checksums.
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            checksum_mode: ChecksumMode::Warn,
+            lenient: true,
+        }
+    }
+}
+
+/// A parsed two (or three) line element set.
+///
+/// Values are kept in the units used by the TLE format: angles in degrees, mean motion
+/// in revolutions per day.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TLE {
+    pub(crate) name: Option<String>,
+    pub(crate) line1: String,
+    pub(crate) line2: String,
+    pub(crate) satnum: u64,
+    pub(crate) classification: Option<Classification>,
+    pub(crate) intldesg: String,
+    /// two digit year from TLE lines, four digit year from OMM
+    pub(crate) epochyr: u64,
+    pub(crate) epochdays: f64,
+    pub(crate) ndot: f64,
+    pub(crate) nddot: f64,
+    pub(crate) bstar: f64,
+    pub(crate) elnum: u64,
+    pub(crate) inclo: f64,
+    pub(crate) nodeo: f64,
+    pub(crate) ecco: f64,
+    pub(crate) argpo: f64,
+    pub(crate) mo: f64,
+    pub(crate) no_kozai: f64,
+    pub(crate) revnum: u64,
+    /// errors the parse options let through
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) warnings: Vec<TleParseError>,
+}
+
+impl TLE {
+    /// Parse a two line element set, warning about wrong checksums.
+    pub fn new(line1: String, line2: String) -> Result<TLE, TleParseError> {
+        TLE::parse(line1, line2, ChecksumMode::default())
+    }
+
+    /// Parse a three line element set, where the element lines are preceded by the
+    /// object name (e.g. "0 ISS (ZARYA)"). The leading "0 " is optional.
+    pub fn from_3le(line0: String, line1: String, line2: String) -> Result<TLE, TleParseError> {
+        let mut tle = TLE::new(line1, line2)?;
+        tle.set_name(&line0);
+        Ok(tle)
+    }
+
+    /// Set the object name from the title line of a three line element set.
+    pub(crate) fn set_name(&mut self, line0: &str) {
+        let name = line0.trim_start();
+        let name = name.strip_prefix("0 ").unwrap_or(name).trim();
+        self.name = (!name.is_empty()).then(|| name.to_string());
+    }
+
+    /// Parse a two line element set, verifying the checksums as selected.
+    pub fn parse(
+        line1: String,
+        line2: String,
+        checksum_mode: ChecksumMode,
+    ) -> Result<TLE, TleParseError> {
+        let options = ParseOptions {
+            checksum_mode,
+            ..ParseOptions::default()
+        };
+        TLE::parse_with_options(line1, line2, options)
+    }
+
+    /// Parse a two line element set with the given checksum verification and
+    /// strictness.
+    pub fn parse_with_options(
+        line1: String,
+        line2: String,
+        options: ParseOptions,
+    ) -> Result<TLE, TleParseError> {
+        let lenient = options.lenient;
+        let (line1, line2) = if lenient {
+            // a tab in place of a space keeps the columns of the fields
+            (line1.replace('\t', " "), line2.replace('\t', " "))
+        } else {
+            (line1, line2)
+        };
+        let checksum_mode = options.checksum_mode;
+        let mut warnings = Vec::new();
+        for (line, text) in [(1, &line1), (2, &line2)] {
+            if text.len() < 69 {
+                return Err(TleParseError::LineTooShort {
+                    line,
+                    length: text.len(),
+                });
+            }
+            if !text.starts_with(if line == 1 { '1' } else { '2' }) {
+                return Err(TleParseError::InvalidLineNumber { line });
+            }
+            if !lenient
+                && let Some((column, found)) = text
+                    .chars()
+                    .take(69)
+                    .enumerate()
+                    .find(|(_, c)| !(c.is_ascii_graphic() || *c == ' '))
+            {
+                return Err(TleParseError::InvalidCharacter {
+                    line,
+                    column: column + 1,
+                    found,
+                });
+            }
+            if checksum_mode != ChecksumMode::Ignore
+                && let Err(error) = verify_checksum(text, line)
+            {
+                if checksum_mode == ChecksumMode::Strict {
+                    return Err(error);
+                }
+                warnings.push(error);
+            }
+        }
+
+        if !lenient {
+            check_layout(&line1, &line2)?;
+        }
+
+        // parse the first line
+        let text = line1.as_str();
+        let satnum = field(text, 1, 2..7, "satellite number")?;
+        let satnum =
+            alpha5_to_satnum(satnum).ok_or_else(|| invalid_field(1, "satellite number", satnum))?;
+        let classification = Classification::from(text.chars().nth(7).unwrap());
+        let intldesg = field(text, 1, 9..17, "international designator")?.to_string();
+        let epochyr = parse_field(text, 1, 18..20, "epoch year")?;
+        let epochdays = parse_field(text, 1, 20..32, "epoch day")?;
+        let ndot = parse_field(text, 1, 33..43, "first derivative of mean motion")?;
+        let nddot =
+            parse_exponent_field(text, 1, 44..52, "second derivative of mean motion", lenient)?;
+        let bstar = parse_exponent_field(text, 1, 53..61, "bstar", lenient)?;
+        let elnum = parse_field(text, 1, 64..68, "element set number")?;
+
+        // parse the second line
+        let text = line2.as_str();
+        let inclo = parse_field(text, 2, 8..16, "inclination")?;
+        let nodeo = parse_field(text, 2, 17..25, "right ascension")?;
+        let ecco = parse_implied_decimal(text, 2, 26..33, "eccentricity", lenient)?;
+        let argpo = parse_field(text, 2, 34..42, "argument of perigee")?;
+        let mo = parse_field(text, 2, 43..51, "mean anomaly")?;
+        let no_kozai = parse_field(text, 2, 52..63, "mean motion")?;
+        let revnum = parse_field(text, 2, 63..68, "revolution number")?;
+
+        Ok(TLE {
+            name: None,
+            line1,
+            line2,
+            satnum,
+            classification,
+            intldesg,
+            epochyr,
+            epochdays,
+            ndot,
+            nddot,
+            bstar,
+            elnum,
+            inclo,
+            nodeo,
+            ecco,
+            argpo,
+            mo,
+            no_kozai,
+            revnum,
+            warnings,
+        })
+    }
+
+    /// Object name from the title line of a three line element set
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Errors found while parsing that the parse options accepted, e.g. wrong checksums
+    /// in `ChecksumMode::Warn`, for the caller to report
+    pub fn warnings(&self) -> &[TleParseError] {
+        &self.warnings
+    }
+
+    pub fn line1(&self) -> &str {
+        &self.line1
+    }
+
+    pub fn line2(&self) -> &str {
+        &self.line2
+    }
+
+    /// NORAD catalog number
+    pub fn satnum(&self) -> u64 {
+        self.satnum
+    }
+
+    pub fn classification(&self) -> Option<&Classification> {
+        self.classification.as_ref()
+    }
+
+    /// International designator (launch year, launch number and piece, e.g. "98067A")
+    pub fn international_designator(&self) -> &str {
+        &self.intldesg
+    }
+
+    /// Four digit year of the epoch, with the pivot of the reference implementation
+    /// for two digit years (see `epoch_year_with_pivot`)
+    pub fn epoch_year(&self) -> u64 {
+        self.epoch_year_with_pivot(DEFAULT_YEAR_PIVOT)
+    }
+
+    /// Four digit year of the epoch. Two digit years of TLE lines below `pivot` are in
+    /// the 2000s and the others in the 1900s; elements from OMM keep their four digit
+    /// year.
+    pub fn epoch_year_with_pivot(&self, pivot: u64) -> u64 {
+        match self.epochyr {
+            year @ 100.. => year,
+            year if year < pivot => year + 2000,
+            year => year + 1900,
+        }
+    }
+
+    /// Day of the year of the epoch, starting at 1.0 at midnight on January 1st
+    pub fn epoch_days(&self) -> f64 {
+        self.epochdays
+    }
+
+    /// Julian date of the epoch, split into the date at midnight and the day fraction
+    pub fn epoch_jd(&self) -> (f64, f64) {
+        jday(self.epoch_year(), self.epoch_calendar().1)
+    }
+
+    /// Calendar date of the epoch in UTC, (year, (month, day, hour, minute, second))
+    /// as in `time::jday`
+    pub fn epoch_calendar(&self) -> (u64, (u64, u64, u64, u64, f64)) {
+        let year = self.epoch_year();
+        (year, days2mdh(year, self.epochdays))
+    }
+
+    /// Epoch in UTC
+    pub fn epoch(&self) -> Epoch {
+        let (jd, jdfrac) = self.epoch_jd();
+        Epoch::new(jd, jdfrac)
+    }
+
+    /// Epoch as a UTC date and time
+    #[cfg(feature = "chrono")]
+    pub fn epoch_datetime(&self) -> chrono::DateTime<chrono::Utc> {
+        let (jd, jdfrac) = self.epoch_jd();
+        // two-digit years and three-digit days keep the epoch within 1957-2059
+        crate::time::jday_to_datetime(jd, jdfrac).expect("TLE epoch out of range")
+    }
+
+    /// Epoch as a hifitime epoch in UTC
+    #[cfg(feature = "hifitime")]
+    pub fn epoch_hifitime(&self) -> hifitime::Epoch {
+        let (jd, jdfrac) = self.epoch_jd();
+        crate::time::jday_to_hifitime(jd, jdfrac)
+    }
+
+    /// First derivative of the mean motion divided by two, in rev/day^2
+    pub fn mean_motion_dot(&self) -> f64 {
+        self.ndot
+    }
+
+    /// Second derivative of the mean motion divided by six, in rev/day^3
+    pub fn mean_motion_ddot(&self) -> f64 {
+        self.nddot
+    }
+
+    /// Drag term in inverse earth radii
+    pub fn bstar(&self) -> f64 {
+        self.bstar
+    }
+
+    pub fn element_number(&self) -> u64 {
+        self.elnum
+    }
+
+    /// Inclination in degrees
+    pub fn inclination(&self) -> f64 {
+        self.inclo
+    }
+
+    /// Right ascension of the ascending node in degrees
+    pub fn raan(&self) -> f64 {
+        self.nodeo
+    }
+
+    pub fn eccentricity(&self) -> f64 {
+        self.ecco
+    }
+
+    /// Argument of perigee in degrees
+    pub fn arg_of_perigee(&self) -> f64 {
+        self.argpo
+    }
+
+    /// Mean anomaly in degrees
+    pub fn mean_anomaly(&self) -> f64 {
+        self.mo
+    }
+
+    /// Kozai mean motion in rev/day
+    pub fn mean_motion(&self) -> f64 {
+        self.no_kozai
+    }
+
+    /// Revolution number at epoch
+    pub fn rev_number(&self) -> u64 {
+        self.revnum
+    }
+
+    /// Format the elements as TLE lines, with the checksums in column 69.
+    ///
+    /// The lines are rebuilt from the element values, so they also reflect any change
+    /// made to a satellite record the TLE was created from (see `TryFrom<&SatRec>`).
+    /// Values the columns can't hold are an error rather than a misaligned line.
+    pub fn to_lines(&self) -> Result<(String, String), TleFormatError> {
+        let satnum =
+            satnum_to_alpha5(self.satnum).ok_or(TleFormatError::SatnumOutOfRange(self.satnum))?;
+        for (field, value) in [
+            ("epoch day", self.epochdays),
+            ("inclination", self.inclo),
+            ("right ascension", self.nodeo),
+            ("argument of perigee", self.argpo),
+            ("mean anomaly", self.mo),
+            ("mean motion", self.no_kozai),
+        ] {
+            if !value.is_finite() {
+                return Err(TleFormatError::InvalidValue { field, value });
+            }
+        }
+        // the seven digits of the implied decimal point, rounded
+        let ecco = (self.ecco * 1e7).round();
+        if !(0.0..1e7).contains(&ecco) {
+            return Err(TleFormatError::InvalidValue {
+                field: "eccentricity",
+                value: self.ecco,
+            });
+        }
+        let classification = self.classification.map_or(' ', Classification::to_char);
+
+        let mut line1 = format!(
+            "1 {satnum}{classification} {:<8} {:02}{:012.8} {} {} {} 0 {:4}",
+            self.intldesg,
+            self.epochyr % 100,
+            self.epochdays,
+            format_ndot(self.ndot)?,
+            format_exponent("second derivative of mean motion", self.nddot)?,
+            format_exponent("bstar", self.bstar)?,
+            self.elnum % 10000,
+        );
+        let mut line2 = format!(
+            "2 {satnum} {:8.4} {:8.4} {:07} {:8.4} {:8.4} {:11.8}{:5}",
+            self.inclo,
+            self.nodeo,
+            ecco as u64,
+            self.argpo,
+            self.mo,
+            self.no_kozai,
+            self.revnum % 100000,
+        );
+        line1.push_str(&tle_checksum(&line1).to_string());
+        line2.push_str(&tle_checksum(&line2).to_string());
+        Ok((line1, line2))
+    }
+
+    /// Split the TLE into the parts stored in the satellite record.
+    pub(crate) fn into_parts(self) -> (Option<String>, Option<Classification>, String) {
+        (self.name, self.classification, self.intldesg)
+    }
+}
+
+impl TryFrom<&SatRec> for TLE {
+    type Error = TleFormatError;
+
+    /// Convert the elements of a satellite record back to a TLE, failing for values the
+    /// TLE columns can't hold (see `to_lines`).
+    fn try_from(satrec: &SatRec) -> Result<TLE, TleFormatError> {
+        TLE::try_from(&Elements {
+            satnum: satrec.satnum,
+            name: satrec.name.clone(),
+            classification: satrec.classification,
+            intldesg: satrec.intldesg.clone(),
+            epochyr: satrec.epochyr,
+            epochdays: satrec.epochdays,
+            epoch: satrec.epoch,
+            ndot: satrec.ndot,
+            nddot: satrec.nddot,
+            bstar: satrec.bstar,
+            elnum: satrec.elnum,
+            inclo: satrec.inclo,
+            nodeo: satrec.nodeo,
+            ecco: satrec.ecco,
+            argpo: satrec.argpo,
+            mo: satrec.mo,
+            no_kozai: satrec.no_kozai,
+            revnum: satrec.revnum,
+        })
+    }
+}
+
+impl TryFrom<&Elements> for TLE {
+    type Error = TleFormatError;
+
+    /// Convert mean elements to a TLE, e.g. to publish fitted elements (see
+    /// `fit::fit_elements`), failing for values the TLE columns can't hold.
+    fn try_from(elements: &Elements) -> Result<TLE, TleFormatError> {
+        let xpdotp = DAY2MIN / TWOPI;
+        let mut tle = TLE {
+            name: elements.name.clone(),
+            line1: String::new(),
+            line2: String::new(),
+            satnum: elements.satnum,
+            classification: elements.classification,
+            intldesg: elements.intldesg.clone(),
+            epochyr: elements.epochyr,
+            epochdays: elements.epochdays,
+            ndot: elements.ndot * xpdotp * DAY2MIN,
+            nddot: elements.nddot * xpdotp * DAY2MIN.powi(2),
+            bstar: elements.bstar,
+            elnum: elements.elnum,
+            inclo: degrees(elements.inclo),
+            nodeo: degrees(elements.nodeo).rem_euclid(360.0),
+            ecco: elements.ecco,
+            argpo: degrees(elements.argpo).rem_euclid(360.0),
+            mo: degrees(elements.mo).rem_euclid(360.0),
+            no_kozai: elements.no_kozai * xpdotp,
+            revnum: elements.revnum,
+            warnings: Vec::new(),
+        };
+        (tle.line1, tle.line2) = tle.to_lines()?;
+        Ok(tle)
+    }
+}
+
+impl fmt::Display for TLE {
+    /// Write the TLE lines, preceded by the object name for three line element sets.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "{name}")?;
+        }
+        writeln!(f, "{}", self.line1)?;
+        write!(f, "{}", self.line2)
+    }
+}
+
+/// Modulo 10 checksum of a TLE line.
+///
+/// Sums the digits in columns 1-68, counting minus signs as 1 and ignoring all other
+/// characters.
+pub fn tle_checksum(line: &str) -> u32 {
+    line.chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/// Alpha-5 letters in order of value: A = 10 through Z = 33, skipping I and O.
+const ALPHA5_LETTERS: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Decode a satellite number in the Alpha-5 scheme.
+///
+/// Numbers up to 99999 are plain digits. Above that the first character is a letter
+/// standing for the leading two digits (A = 10, ..., Z = 33, skipping I and O), so
+/// "A0001" is 100001 and "Z9999" is 339999.
+pub fn alpha5_to_satnum(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    let rest = chars.as_str();
+    if !rest.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if first.is_ascii_digit() {
+        return text.parse().ok();
+    }
+    if rest.len() != 4 {
+        return None;
+    }
+    let prefix = ALPHA5_LETTERS.find(first.to_ascii_uppercase())? as u64 + 10;
+    Some(prefix * 10000 + rest.parse::<u64>().ok()?)
+}
+
+/// Encode a satellite number in the 5 character Alpha-5 scheme.
+///
+/// Returns None for numbers above 339999, which can't be represented.
+pub fn satnum_to_alpha5(satnum: u64) -> Option<String> {
+    if satnum < 100000 {
+        return Some(format!("{satnum:05}"));
+    }
+    let letter = ALPHA5_LETTERS.chars().nth((satnum / 10000 - 10) as usize)?;
+    Some(format!("{letter}{:04}", satnum % 10000))
+}
+
+/// Parse the start, stop and step of a verification run, in minutes since epoch, that
+/// follow column 69 of line 2 in SGP4-VER.TLE style files.
+pub fn parse_run_schedule(line2: &str) -> Result<(f64, f64, f64), TleParseError> {
+    let text = line2.get(69..).unwrap_or("");
+    let values = text
+        .split_whitespace()
+        .map(str::parse::<f64>)
+        .collect::<Result<Vec<_>, _>>();
+    match values.as_deref() {
+        Ok(&[start, stop, step]) => Ok((start, stop, step)),
+        _ => Err(invalid_field(2, "run schedule", text)),
+    }
+}
+
+/// Check the checksum in column 69 of a TLE line against its contents.
+fn verify_checksum(text: &str, line: usize) -> Result<(), TleParseError> {
+    let expected = tle_checksum(text);
+    let found = text.chars().nth(68).unwrap_or(' ');
+    if found.to_digit(10) == Some(expected) {
+        Ok(())
+    } else {
+        Err(TleParseError::ChecksumMismatch {
+            line,
+            expected,
+            found,
+        })
+    }
+}
+
+/// Layout of the fields of the two lines: the range of columns, the name of the field
+/// and the characters it may hold in strict parsing
+type FieldLayout = (Range<usize>, &'static str, fn(char) -> bool);
+
+const LINE1_LAYOUT: [FieldLayout; 8] = [
+    (2..7, "satellite number", |c| c.is_ascii_alphanumeric()),
+    (18..20, "epoch year", |c| c.is_ascii_digit()),
+    (20..32, "epoch day", |c| c.is_ascii_digit() || c == '.'),
+    (33..43, "first derivative of mean motion", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '-' | '.')
+    }),
+    (44..45, "second derivative of mean motion", |c| {
+        matches!(c, ' ' | '-')
+    }),
+    (45..50, "second derivative of mean motion", |c| {
+        c.is_ascii_digit()
+    }),
+    (53..54, "bstar", |c| matches!(c, ' ' | '-')),
+    (54..59, "bstar", |c| c.is_ascii_digit()),
+];
+
+const LINE2_LAYOUT: [FieldLayout; 7] = [
+    (8..16, "inclination", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '.')
+    }),
+    (17..25, "right ascension", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '.')
+    }),
+    (26..33, "eccentricity", |c| c.is_ascii_digit()),
+    (34..42, "argument of perigee", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '.')
+    }),
+    (43..51, "mean anomaly", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '.')
+    }),
+    (52..63, "mean motion", |c| {
+        c.is_ascii_digit() || matches!(c, ' ' | '.')
+    }),
+    (63..68, "revolution number", |c| {
+        c.is_ascii_digit() || c == ' '
+    }),
+];
+
+/// Check the fields that strict parsing is particular about: zero padded fields must
+/// not have spaces, and signs are only allowed where the format has them.
+fn check_layout(line1: &str, line2: &str) -> Result<(), TleParseError> {
+    for (line, text, layout) in [(1, line1, &LINE1_LAYOUT[..]), (2, line2, &LINE2_LAYOUT[..])] {
+        for (range, name, valid) in layout {
+            let value = text
+                .get(range.clone())
+                .ok_or_else(|| invalid_field(line, name, text))?;
+            if !value.chars().all(valid) {
+                return Err(invalid_field(line, name, value.trim()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Extract the trimmed text of a TLE field.
+fn field<'a>(
+    text: &'a str,
+    line: usize,
+    range: Range<usize>,
+    name: &'static str,
+) -> Result<&'a str, TleParseError> {
+    text.get(range)
+        .map(str::trim)
+        .ok_or_else(|| invalid_field(line, name, text))
+}
+
+fn invalid_field(line: usize, name: &'static str, text: &str) -> TleParseError {
+    TleParseError::InvalidField {
+        line,
+        field: name,
+        text: text.to_string(),
+    }
+}
+
+fn parse_field<T: FromStr>(
+    text: &str,
+    line: usize,
+    range: Range<usize>,
+    name: &'static str,
+) -> Result<T, TleParseError> {
+    let value = field(text, line, range, name)?;
+    value.parse().map_err(|_| invalid_field(line, name, value))
+}
+
+/// Parse a field with an implied leading decimal point, e.g. "1859667" -> 0.1859667.
+/// Lenient parsing also takes the decimal point written out, e.g. ".185967".
+fn parse_implied_decimal(
+    text: &str,
+    line: usize,
+    range: Range<usize>,
+    name: &'static str,
+    lenient: bool,
+) -> Result<f64, TleParseError> {
+    let value = field(text, line, range, name)?;
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let digits = match digits
+        .strip_prefix("0.")
+        .or_else(|| digits.strip_prefix('.'))
+    {
+        Some(fraction) if lenient => fraction,
+        _ => digits,
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == ' ') {
+        return Err(invalid_field(line, name, value));
+    }
+    let mantissa: f64 = (String::from("0.") + &digits.replace(' ', "0"))
+        .parse()
+        .map_err(|_| invalid_field(line, name, value))?;
+    Ok(sign * mantissa)
+}
+
+/// Parse a field in the TLE exponential notation with an implied decimal point,
+/// e.g. " 28098-4" -> 0.28098e-4
+fn parse_exponent_field(
+    text: &str,
+    line: usize,
+    range: Range<usize>,
+    name: &'static str,
+    lenient: bool,
+) -> Result<f64, TleParseError> {
+    let value = field(text, line, range.clone(), name)?;
+    let split = range.end - 2;
+    let mantissa = parse_implied_decimal(text, line, range.start..split, name, lenient)
+        .map_err(|_| invalid_field(line, name, value))?;
+    let exponent: i32 = parse_field(text, line, split..range.end, name)
+        .map_err(|_| invalid_field(line, name, value))?;
+    Ok(mantissa * 10f64.powi(exponent))
+}
+
+/// Format the first derivative of the mean motion without its leading zero,
+/// e.g. 0.00000023 -> " .00000023"
+fn format_ndot(value: f64) -> Result<String, TleFormatError> {
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let digits = format!("{:.8}", value.abs());
+    // only the fraction fits, e.g. " .00016717"
+    let fraction = digits
+        .strip_prefix("0.")
+        .filter(|_| value.is_finite())
+        .ok_or(TleFormatError::InvalidValue {
+            field: "first derivative of mean motion",
+            value,
+        })?;
+    Ok(format!("{sign}.{fraction}"))
+}
+
+/// Format a value in the TLE exponential notation with an implied decimal point,
+/// e.g. 0.28098e-4 -> " 28098-4"
+fn format_exponent(field: &'static str, value: f64) -> Result<String, TleFormatError> {
+    if value == 0.0 {
+        return Ok(" 00000-0".into());
+    }
+    let invalid = TleFormatError::InvalidValue { field, value };
+    if !value.is_finite() {
+        return Err(invalid);
+    }
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let formatted = format!("{:.4e}", value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').ok_or(invalid)?;
+    let exponent = exponent.parse::<i32>().map_err(|_| invalid)? + 1;
+    // a single exponent digit
+    if !(-9..=9).contains(&exponent) {
+        return Err(invalid);
+    }
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    Ok(format!(
+        "{sign}{}{exponent_sign}{}",
+        mantissa.replace('.', ""),
+        exponent.abs()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ISS_LINE1, ISS_LINE2, VANGUARD_LINE1, VANGUARD_LINE2};
+
+    #[test]
+    fn test_parse() {
+        let tle = TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap();
+        assert_eq!(tle.satnum(), 5);
+        assert_eq!(tle.international_designator(), "58002B");
+        assert_eq!(tle.epoch_year(), 2000);
+        assert_eq!(tle.epoch_year_with_pivot(0), 1900);
+        assert_eq!(tle.epoch_days(), 179.78495062);
+        let (year, (mon, day, hr, minute, sec)) = tle.epoch_calendar();
+        assert_eq!((year, mon, day, hr, minute), (2000, 6, 27, 18, 50));
+        assert!((sec - 19.733568).abs() < 1e-4);
+        assert!((tle.epoch() - Epoch::new(2451722.5, 0.78495062)).abs() < 1e-12);
+        assert_eq!(tle.mean_motion_dot(), 0.00000023);
+        assert_eq!(tle.mean_motion_ddot(), 0.0);
+        assert!((tle.bstar() - 0.28098e-4).abs() < 1e-15);
+        assert_eq!(tle.element_number(), 475);
+        assert_eq!(tle.inclination(), 34.2682);
+        assert_eq!(tle.raan(), 348.7242);
+        assert_eq!(tle.eccentricity(), 0.1859667);
+        assert_eq!(tle.arg_of_perigee(), 331.7664);
+        assert_eq!(tle.mean_anomaly(), 19.3264);
+        assert_eq!(tle.mean_motion(), 10.82419157);
+        assert_eq!(tle.rev_number(), 41366);
+        assert!(tle.warnings().is_empty());
+
+        // a wrong checksum is kept as a warning unless strict
+        let line1 = format!("{}4", &VANGUARD_LINE1[..68]);
+        let tle = TLE::new(line1.clone(), VANGUARD_LINE2.into()).unwrap();
+        let mismatch = TleParseError::ChecksumMismatch {
+            line: 1,
+            expected: 3,
+            found: '4',
+        };
+        assert_eq!(tle.warnings(), core::slice::from_ref(&mismatch));
+        assert_eq!(
+            TLE::parse(line1, VANGUARD_LINE2.into(), ChecksumMode::Strict),
+            Err(mismatch)
+        );
+    }
+
+    #[test]
+    fn test_lenient() {
+        let line1 = "1     5U 58002B   00179.78495062 +.00000023 +00000-0 +28098-4 0  4753";
+        let line2 = "2 00005 \t34.2682 348.7242 .185967 331.7664  19.3264 10.82419157413667";
+
+        let tle =
+            TLE::parse_with_options(line1.into(), line2.into(), ParseOptions::lenient()).unwrap();
+        let expected = TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap();
+        assert_eq!(tle.satnum(), 5);
+        assert_eq!(tle.mean_motion_dot(), expected.mean_motion_dot());
+        assert_eq!(tle.bstar(), expected.bstar());
+        assert_eq!(tle.inclination(), expected.inclination());
+        assert_eq!(tle.eccentricity(), 0.185967);
+        assert_eq!(tle.line2(), line2.replace('\t', " "));
+
+        // the layout is checked regardless of the checksums
+        let options = ParseOptions {
+            checksum_mode: ChecksumMode::Ignore,
+            ..ParseOptions::strict()
+        };
+        let strict = |line1: &str, line2: &str| {
+            TLE::parse_with_options(line1.into(), line2.into(), options).unwrap_err()
+        };
+        assert_eq!(
+            strict(line1, VANGUARD_LINE2),
+            TleParseError::InvalidField {
+                line: 1,
+                field: "satellite number",
+                text: "5".into()
+            }
+        );
+        assert_eq!(
+            strict(
+                &VANGUARD_LINE1.replace(" .00000023", "+.00000023"),
+                VANGUARD_LINE2
+            ),
+            TleParseError::InvalidField {
+                line: 1,
+                field: "first derivative of mean motion",
+                text: "+.00000023".into()
+            }
+        );
+        assert_eq!(
+            strict(VANGUARD_LINE1, line2),
+            TleParseError::InvalidCharacter {
+                line: 2,
+                column: 9,
+                found: '\t'
+            }
+        );
+        assert!(matches!(
+            strict(
+                VANGUARD_LINE1,
+                &VANGUARD_LINE2.replace("1859667", ".185967")
+            ),
+            TleParseError::InvalidField {
+                field: "eccentricity",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_to_lines() {
+        for (line1, line2) in [
+            (VANGUARD_LINE1, VANGUARD_LINE2),
+            (
+                "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+                "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656",
+            ),
+            (ISS_LINE1, ISS_LINE2),
+            (
+                "1 A0001U 23001A   23001.00000000 -.00000100  12345-5 -11606-4 0  9999",
+                "2 A0001  98.7000 100.0000 0001000  90.0000 270.0000 14.20000000 10007",
+            ),
+        ] {
+            let tle = TLE::parse(line1.into(), line2.into(), ChecksumMode::Ignore).unwrap();
+            let (formatted1, formatted2) = tle.to_lines().unwrap();
+            assert_eq!(formatted1[..68], line1[..68]);
+            assert_eq!(formatted2[..68], line2[..68]);
+            assert!(TLE::parse(formatted1, formatted2, ChecksumMode::Strict).is_ok());
+        }
+
+        let tle = TLE::new(VANGUARD_LINE1.into(), VANGUARD_LINE2.into()).unwrap();
+        for (bstar, valid) in [(f64::NAN, false), (1e-12, false), (1e-10, true)] {
+            let result = TLE {
+                bstar,
+                ..tle.clone()
+            }
+            .to_lines();
+            assert_eq!(result.is_ok(), valid, "{bstar}");
+        }
+        for ecco in [1.0, 0.99999999, -0.1] {
+            assert_eq!(
+                TLE {
+                    ecco,
+                    ..tle.clone()
+                }
+                .to_lines(),
+                Err(TleFormatError::InvalidValue {
+                    field: "eccentricity",
+                    value: ecco
+                })
+            );
+        }
+        assert_eq!(
+            TLE {
+                satnum: 400000,
+                ..tle.clone()
+            }
+            .to_lines(),
+            Err(TleFormatError::SatnumOutOfRange(400000))
+        );
+        let tle = TLE {
+            ndot: f64::INFINITY,
+            ..tle
+        };
+        assert!(tle.to_lines().is_err());
+    }
+
+    #[test]
+    fn test_from_satrec() {
+        let satrec = SatRec {
+            satnum: 5,
+            classification: Some(Classification::Unclassified),
+            intldesg: "58002B".into(),
+            epochyr: 0,
+            epochdays: 179.78495062,
+            ndot: 0.00000023 / (DAY2MIN.powi(2) / TWOPI),
+            bstar: 0.28098e-4,
+            elnum: 475,
+            inclo: radians(34.2682),
+            nodeo: radians(348.7242),
+            ecco: 0.1859667,
+            argpo: radians(331.7664),
+            mo: radians(19.3264),
+            no_kozai: 10.82419157 * TWOPI / DAY2MIN,
+            revnum: 41366,
+            ..Default::default()
+        };
+        let tle = TLE::try_from(&satrec).unwrap();
+        assert_eq!(tle.line1(), VANGUARD_LINE1);
+        assert_eq!(tle.line2(), VANGUARD_LINE2);
+        assert_eq!(
+            tle.to_string(),
+            format!("{VANGUARD_LINE1}\n{VANGUARD_LINE2}")
+        );
+    }
+
+    #[test]
+    fn test_parse_run_schedule() {
+        let line2 = format!("{VANGUARD_LINE2}       -1440        -720          60");
+        assert_eq!(parse_run_schedule(&line2), Ok((-1440.0, -720.0, 60.0)));
+        assert!(parse_run_schedule(VANGUARD_LINE2).is_err());
+        assert!(parse_run_schedule(&format!("{VANGUARD_LINE2} 0 1440")).is_err());
+        assert!(parse_run_schedule(&format!("{VANGUARD_LINE2} 0 1440 x")).is_err());
+    }
+
+    #[test]
+    fn test_alpha5() {
+        assert_eq!(alpha5_to_satnum("00005"), Some(5));
+        assert_eq!(alpha5_to_satnum("    5"), Some(5));
+        assert_eq!(alpha5_to_satnum("A0001"), Some(100001));
+        assert_eq!(alpha5_to_satnum("H9999"), Some(179999));
+        assert_eq!(alpha5_to_satnum("J0000"), Some(180000));
+        assert_eq!(alpha5_to_satnum("Z9999"), Some(339999));
+        assert_eq!(alpha5_to_satnum("I0000"), None);
+        assert_eq!(alpha5_to_satnum("A00x1"), None);
+
+        assert_eq!(satnum_to_alpha5(5).as_deref(), Some("00005"));
+        assert_eq!(satnum_to_alpha5(100001).as_deref(), Some("A0001"));
+        assert_eq!(satnum_to_alpha5(180000).as_deref(), Some("J0000"));
+        assert_eq!(satnum_to_alpha5(339999).as_deref(), Some("Z9999"));
+        assert_eq!(satnum_to_alpha5(340000), None);
+    }
+
+    #[test]
+    fn test_from_3le() {
+        let tle =
+            TLE::from_3le("0 ISS (ZARYA)".into(), ISS_LINE1.into(), ISS_LINE2.into()).unwrap();
+        assert_eq!(tle.name(), Some("ISS (ZARYA)"));
+        let tle = TLE::from_3le(
+            "ISS (ZARYA)        ".into(),
+            ISS_LINE1.into(),
+            ISS_LINE2.into(),
+        )
+        .unwrap();
+        assert_eq!(tle.name(), Some("ISS (ZARYA)"));
+        let tle = TLE::from_3le("0 ".into(), ISS_LINE1.into(), ISS_LINE2.into()).unwrap();
+        assert_eq!(tle.name(), None);
+        assert_eq!(
+            TLE::new(ISS_LINE1.into(), ISS_LINE2.into()).unwrap().name(),
+            None
+        );
+    }
+}
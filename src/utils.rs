@@ -1,6 +1,10 @@
-use std::f64::consts::PI;
+use alloc::string::String;
+use core::f64::consts::PI;
 
-use crate::sgp4::{Classification, PropagationError};
+#[cfg(all(not(feature = "std"), not(test)))]
+use crate::math::Float;
+use crate::time::Epoch;
+use crate::tle::Classification;
 
 pub fn radians(deg: f64) -> f64 {
     deg * PI / 180.0
@@ -10,14 +14,39 @@ pub fn degrees(rad: f64) -> f64 {
     rad * 180.0 / PI
 }
 
+/// Magnitude of a vector
+pub fn mag(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+pub fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Angle between two vectors in radians, in [0, pi]
+pub fn angle(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (dot(a, b) / (mag(a) * mag(b))).clamp(-1.0, 1.0).acos()
+}
+
 #[allow(non_camel_case_types)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WGSModel {
     WGS_72_LOW_PRECISION,
     WGS_72,
     WGS_84,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GravitationalConstants {
     pub tumin: f64,
     pub mu: f64,
@@ -71,7 +100,8 @@ pub fn get_grav_c(model: &WGSModel) -> GravitationalConstants {
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SatRec {
     pub a: f64,
     pub alta: f64,
@@ -87,7 +117,7 @@ pub struct SatRec {
     pub cc4: f64,
     pub cc5: f64,
     pub omgcof: f64,
-    pub xmcod: f64,
+    pub xmcof: f64,
     pub eta: f64,
     pub sinmao: f64,
     pub delmo: f64,
@@ -105,15 +135,15 @@ pub struct SatRec {
     pub bstar: f64,
     pub xfact: f64,
     pub xlamo: f64,
-    pub atime: f64,
-    pub error: Option<PropagationError>,
-    pub t: f64,
     pub aycof: f64,
     pub xlcof: f64,
     pub x1mth2: f64,
     pub x7thm1: f64,
     pub satnum: u64,
+    /// object name from the title line of a three line element set
+    pub name: Option<String>,
     pub intldesg: String,
+    /// Four digit year of the epoch
     pub epochyr: u64,
     pub epochdays: f64,
     pub ndot: f64,
@@ -121,8 +151,7 @@ pub struct SatRec {
     pub elnum: u64,
     pub revnum: u64,
     pub no_kozai: f64,
-    pub jdsatepoch: f64,
-    pub jdsatepochf: f64,
+    pub epoch: Epoch,
     pub init: bool,
     pub classification: Option<Classification>,
 }
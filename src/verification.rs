@@ -0,0 +1,288 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::sgp4::{OperationMode, SGP4};
+use crate::tle::{TLE, parse_run_schedule};
+use crate::utils::WGSModel;
+
+/// The AIAA SGP4-VER.TLE verification cases, with the run schedule after line 2.
+pub const SGP4_VER_TLE: &str = include_str!("../data/SGP4-VER.TLE");
+
+/// States of the verification cases from the reference C++ implementation
+/// (tcppver.out): a "satnum xx" header per case, then rows of time since epoch in
+/// minutes, TEME position in km and velocity in km/s.
+pub const TCPPVER_OUT: &str = include_str!("../data/tcppver.out");
+
+/// A verification case: a TLE and the run schedule in minutes since epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationCase {
+    pub line1: String,
+    pub line2: String,
+    pub start: f64,
+    pub stop: f64,
+    pub step: f64,
+}
+
+impl VerificationCase {
+    /// Times of the run of the reference driver, see `schedule`.
+    pub fn times(&self) -> Vec<f64> {
+        schedule(self.start, self.stop, self.step)
+    }
+
+    /// Run the case like the reference driver: WGS-72 constants in AFSPC mode, stopping
+    /// at the first propagation error. A case that fails to initialize has no states.
+    pub fn run(&self) -> Result<(u64, Vec<ReferenceState>), String> {
+        let tle = TLE::new(self.line1.clone(), self.line2.clone())
+            .map_err(|error| format!("{}: {error}", self.line1))?;
+        let satnum = tle.satnum();
+        let Ok(sgp4) = SGP4::from_tle(WGSModel::WGS_72, OperationMode::Afspc, tle) else {
+            return Ok((satnum, Vec::new()));
+        };
+        let states = self
+            .times()
+            .into_iter()
+            .map_while(|t| {
+                let state = sgp4.propagate(t).ok()?;
+                Some((t, state.position_km, state.velocity_km_s))
+            })
+            .collect();
+        Ok((satnum, states))
+    }
+}
+
+/// Times at which the reference driver (testcpp) writes states: the epoch, then from
+/// `start` to `stop` in steps of `step`, with the last step cut short to end at `stop`.
+/// Catalog runs use -1440 to 1440 in steps of 10.
+pub fn schedule(start: f64, stop: f64, step: f64) -> Vec<f64> {
+    let mut times = Vec::from([0.0]);
+    let mut tsince = start;
+    if tsince.abs() > 1e-8 {
+        tsince -= step;
+    }
+    while tsince < stop {
+        tsince = (tsince + step).min(stop);
+        times.push(tsince);
+    }
+    times
+}
+
+/// Write the "satnum xx" line that starts the states of a satellite in tcppver.out.
+pub fn write_tcppver_header(out: &mut impl fmt::Write, satnum: u64) -> fmt::Result {
+    writeln!(out, "{satnum} xx")
+}
+
+/// Write a state in the columns of tcppver.out: time since epoch in minutes, TEME
+/// position in km and velocity in km/s.
+pub fn write_tcppver_state(
+    out: &mut impl fmt::Write,
+    t: f64,
+    r: [f64; 3],
+    v: [f64; 3],
+) -> fmt::Result {
+    writeln!(
+        out,
+        " {t:16.8} {:16.8} {:16.8} {:16.8} {:12.9} {:12.9} {:12.9}",
+        r[0], r[1], r[2], v[0], v[1], v[2]
+    )
+}
+
+/// Run the cases of an SGP4-VER.TLE style file and write the states as the reference
+/// driver writes tcppver.out, for comparison with existing tooling.
+pub fn write_tcppver(out: &mut impl fmt::Write, cases: &str) -> Result<(), String> {
+    for case in parse_cases(cases)? {
+        let (satnum, states) = case.run()?;
+        let write = |out: &mut _| {
+            write_tcppver_header(out, satnum)?;
+            states
+                .iter()
+                .try_for_each(|&(t, r, v)| write_tcppver_state(out, t, r, v))
+        };
+        write(out).map_err(|_| String::from("failed to write the states"))?;
+    }
+    Ok(())
+}
+
+/// Deviations of a verification run from the reference states.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationReport {
+    /// Number of cases run
+    pub cases: usize,
+    /// Number of states compared
+    pub states: usize,
+    /// Largest position deviation of any component in km
+    pub max_position_error: f64,
+    /// Largest velocity deviation of any component in km/s
+    pub max_velocity_error: f64,
+    /// Satellite number and time since epoch of the largest position deviation
+    pub worst: Option<(u64, f64)>,
+    /// Cases whose run didn't produce the reference states, e.g. a propagation error
+    /// where the reference has a state or the other way round
+    pub failures: Vec<String>,
+}
+
+/// Parse a file in the format of SGP4-VER.TLE: pairs of TLE lines, with the start,
+/// stop and step of the run following the checksum of line 2. Lines starting with '#'
+/// are comments.
+pub fn parse_cases(text: &str) -> Result<Vec<VerificationCase>, String> {
+    let mut lines = text.lines().filter(|line| !line.starts_with('#'));
+    let mut cases = Vec::new();
+    while let Some(line1) = lines.next() {
+        if line1.trim().is_empty() {
+            continue;
+        }
+        let line2 = lines
+            .next()
+            .ok_or_else(|| format!("missing line 2 after {line1:?}"))?;
+        let (start, stop, step) = parse_run_schedule(line2)
+            .map_err(|_| format!("expected start, stop and step after {line2:?}"))?;
+        cases.push(VerificationCase {
+            line1: line1.into(),
+            line2: line2[..69].into(),
+            start,
+            stop,
+            step,
+        });
+    }
+    Ok(cases)
+}
+
+/// A state as in tcppver.out: time since epoch in minutes, position in km and velocity in km/s.
+pub type ReferenceState = (f64, [f64; 3], [f64; 3]);
+
+/// Parse reference states in the format of tcppver.out into one list per case.
+pub fn parse_states(text: &str) -> Result<Vec<Vec<ReferenceState>>, String> {
+    let mut states: Vec<Vec<_>> = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        if line.trim_end().ends_with("xx") {
+            states.push(Vec::new());
+            continue;
+        }
+        let values = line
+            .split_whitespace()
+            .take(7)
+            .map(str::parse::<f64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("invalid state {line:?}"))?;
+        let [t, x, y, z, vx, vy, vz] = values[..] else {
+            return Err(format!("expected 7 values in {line:?}"));
+        };
+        states
+            .last_mut()
+            .ok_or_else(|| format!("state {line:?} before the first case"))?
+            .push((t, [x, y, z], [vx, vy, vz]));
+    }
+    Ok(states)
+}
+
+/// Run the bundled SGP4-VER cases and compare with the bundled reference states.
+///
+/// A faithful port reproduces the reference positions to about a micrometre; the
+/// deviations grow mainly with the platform's floating point library.
+pub fn verify() -> VerificationReport {
+    verify_with(SGP4_VER_TLE, TCPPVER_OUT).expect("the bundled verification data is valid")
+}
+
+/// Run the cases of an SGP4-VER.TLE style file (see `VerificationCase::run`) and
+/// compare with the states of a tcppver.out style file.
+///
+/// Fails if either file can't be parsed or they don't have the same number of cases.
+pub fn verify_with(cases: &str, states: &str) -> Result<VerificationReport, String> {
+    let cases = parse_cases(cases)?;
+    let states = parse_states(states)?;
+    if cases.len() != states.len() {
+        return Err(format!(
+            "{} cases but reference states for {}",
+            cases.len(),
+            states.len()
+        ));
+    }
+
+    let mut report = VerificationReport::default();
+    for (case, expected) in cases.iter().zip(&states) {
+        report.cases += 1;
+        let (satnum, states) = case.run()?;
+        let mut compared = 0;
+        for &(t, r, v) in &states {
+            let Some((t_expected, r_expected, v_expected)) = expected.get(compared) else {
+                report
+                    .failures
+                    .push(format!("{satnum}: state at {t} min has no reference"));
+                break;
+            };
+            if (t - t_expected).abs() > 1e-6 {
+                report.failures.push(format!(
+                    "{satnum}: state at {t} min, reference at {t_expected} min"
+                ));
+                break;
+            }
+            for i in 0..3 {
+                let dr = (r[i] - r_expected[i]).abs();
+                if dr > report.max_position_error {
+                    report.max_position_error = dr;
+                    report.worst = Some((satnum, t));
+                }
+                report.max_velocity_error =
+                    report.max_velocity_error.max((v[i] - v_expected[i]).abs());
+            }
+            compared += 1;
+        }
+        if compared < expected.len() {
+            report.failures.push(format!(
+                "{satnum}: propagation stopped after {compared} of {} states",
+                expected.len()
+            ));
+        }
+        report.states += compared;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_times() {
+        let case = VerificationCase {
+            line1: String::new(),
+            line2: String::new(),
+            start: -5184.0,
+            stop: -4896.0,
+            step: 120.0,
+        };
+        assert_eq!(case.times(), [0.0, -5184.0, -5064.0, -4944.0, -4896.0]);
+        let case = VerificationCase {
+            start: 0.0,
+            stop: 720.0,
+            step: 360.0,
+            ..case
+        };
+        assert_eq!(case.times(), [0.0, 360.0, 720.0]);
+    }
+
+    #[test]
+    fn test_verify() {
+        let report = verify();
+        assert_eq!(report.cases, 33);
+        assert_eq!(report.states, 666);
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert!(report.max_position_error < 1e-6);
+        assert!(report.max_velocity_error < 1e-9);
+    }
+
+    #[test]
+    fn test_write_tcppver() {
+        let mut out = String::new();
+        write_tcppver(&mut out, SGP4_VER_TLE).unwrap();
+        assert!(out.starts_with(
+            "5 xx\n       0.00000000    7022.46529266   -1400.08296755       0.03995155  \
+             1.893841015  6.405893759  4.534807250\n"
+        ));
+        let report = verify_with(SGP4_VER_TLE, &out).unwrap();
+        assert_eq!(report.states, 666);
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert!(report.max_position_error < 1e-8);
+    }
+}